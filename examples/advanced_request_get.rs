@@ -15,7 +15,7 @@ fn main() {
     let addr: Uri = Uri::try_from("https://www.rust-lang.org/learn").unwrap();
 
     // Containers for a server's response.
-    let raw_head;
+
     let mut body = Vec::new();
 
     // Prepares a request message.
@@ -33,7 +33,7 @@ fn main() {
     // Wraps the stream in BufReader to make it easier to read from it.
     // Reads a response from the server and saves the head to `raw_head`, and the body to `body`.
     let mut stream = BufReader::new(stream);
-    raw_head = stream::read_head(&mut stream);
+    let raw_head = stream::read_head(&mut stream);
     stream.read_to_end(&mut body).unwrap();
 
     // Parses and processes the response.
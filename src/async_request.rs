@@ -0,0 +1,187 @@
+//! sending HTTP requests asynchronously in Tokio runtime
+use crate::{
+    error,
+    request::{Method, Request},
+    response::Response,
+    uri::Uri,
+};
+use std::convert::TryFrom;
+use tokio::io::AsyncWrite;
+
+/// Creates and sends GET request asynchronously. Returns response for this request.
+/// Works like `request::get`, but redirects aren't followed (see `Request::send_async`).
+///
+/// # Examples
+/// ```no_run
+/// use http_req::async_request;
+///
+/// # async fn run() {
+/// let mut writer = Vec::new();
+/// const uri: &str = "https://www.rust-lang.org/learn";
+///
+/// let response = async_request::get(uri, &mut writer).await.unwrap();
+/// # }
+/// ```
+pub async fn get<T, U>(uri: T, writer: &mut U) -> Result<Response, error::Error>
+where
+    T: AsRef<str>,
+    U: AsyncWrite + Unpin,
+{
+    let uri = Uri::try_from(uri.as_ref())?;
+    let response = Request::new(&uri).send_async(writer);
+
+    response.await
+}
+
+/// Creates and sends HEAD request asynchronously. Returns response for this request.
+///
+/// # Examples
+/// ```no_run
+/// use http_req::async_request;
+///
+/// # async fn run() {
+/// const uri: &str = "https://www.rust-lang.org/learn";
+/// let response = async_request::head(uri).await.unwrap();
+/// # }
+/// ```
+pub async fn head<T>(uri: T) -> Result<Response, error::Error>
+where
+    T: AsRef<str>,
+{
+    let mut writer = Vec::new();
+    let uri = Uri::try_from(uri.as_ref())?;
+
+    let response = Request::new(&uri)
+        .method(Method::HEAD)
+        .send_async(&mut writer);
+
+    response.await
+}
+
+/// Creates and sends POST request asynchronously. Returns response for this request.
+///
+/// # Examples
+/// ```no_run
+/// use http_req::async_request;
+///
+/// # async fn run() {
+/// let mut writer = Vec::new();
+/// const uri: &str = "https://www.rust-lang.org/learn";
+/// const body: &[u8; 27] = b"field1=value1&field2=value2";
+///
+/// let response = async_request::post(uri, body, &mut writer).await.unwrap();
+/// # }
+/// ```
+pub async fn post<T, U>(uri: T, body: &[u8], writer: &mut U) -> Result<Response, error::Error>
+where
+    T: AsRef<str>,
+    U: AsyncWrite + Unpin,
+{
+    let uri = Uri::try_from(uri.as_ref())?;
+
+    let response = Request::new(&uri)
+        .method(Method::POST)
+        .body(body)
+        .send_async(writer);
+
+    response.await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{response::StatusCode, test_util::TestServer};
+
+    #[tokio::test]
+    async fn async_get_head_post() {
+        let server = TestServer::start([
+            "HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\nhello",
+            "HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\n",
+            "HTTP/1.1 201 Created\r\n\
+              Transfer-Encoding: chunked\r\n\r\n\
+              3\r\nfoo\r\n3\r\nbar\r\n0\r\n\
+              Grpc-Status: 0\r\n\r\n",
+        ])
+        .unwrap();
+
+        let mut body = Vec::new();
+        let res = get(server.uri("/get"), &mut body).await.unwrap();
+        assert_eq!(res.status_code(), StatusCode::new(200));
+        assert_eq!(body, b"hello");
+
+        let res = head(server.uri("/head")).await.unwrap();
+        assert_eq!(res.content_len(), Some(5));
+
+        let mut body = Vec::new();
+        let res = post(server.uri("/post"), b"data", &mut body).await.unwrap();
+        assert_eq!(res.status_code(), StatusCode::new(201));
+        assert_eq!(res.headers().get("Grpc-Status"), Some(&"0".to_string()));
+        assert_eq!(body, b"foobar");
+
+        let requests = server.join().unwrap();
+        assert_eq!(requests[0].head().method(), &Method::GET);
+        assert_eq!(requests[0].head().target(), "/get");
+        assert_eq!(requests[1].head().method(), &Method::HEAD);
+        assert_eq!(requests[1].head().target(), "/head");
+        assert_eq!(requests[2].head().method(), &Method::POST);
+        assert_eq!(requests[2].head().target(), "/post");
+        assert_eq!(requests[2].body(), b"data");
+    }
+
+    #[tokio::test]
+    async fn async_unsupported_settings() {
+        let uri = Uri::try_from("http://localhost/").unwrap();
+        let token = crate::cancel::CancelToken::new();
+        let mut writer = Vec::new();
+
+        let res = Request::new(&uri)
+            .cancel_token(&token)
+            .send_async(&mut writer)
+            .await;
+        assert!(
+            matches!(res, Err(error::Error::IO(e)) if e.kind() == std::io::ErrorKind::Unsupported)
+        );
+
+        #[cfg(unix)]
+        {
+            let path = std::path::Path::new("/tmp/http_req.sock");
+            let res = Request::new(&uri)
+                .unix_socket(path)
+                .send_async(&mut writer)
+                .await;
+            assert!(
+                matches!(res, Err(error::Error::IO(e)) if e.kind() == std::io::ErrorKind::Unsupported)
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn async_keepalive() {
+        let server = TestServer::start(["HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok"]).unwrap();
+        let addr = server.uri("/");
+        let uri = Uri::try_from(addr.as_str()).unwrap();
+        let keepalive = crate::stream::Keepalive::new(std::time::Duration::from_secs(30));
+
+        let mut body = Vec::new();
+        let res = Request::new(&uri)
+            .tcp_keepalive(Some(keepalive))
+            .send_async(&mut body)
+            .await
+            .unwrap();
+        assert_eq!(res.status_code(), StatusCode::new(200));
+        assert!(res.peer_certificates().is_empty());
+        assert_eq!(body, b"ok");
+
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn futures_are_send() {
+        fn assert_send<T: Send>(_: T) {}
+
+        let mut writer = Vec::new();
+        assert_send(get("http://localhost/", &mut writer));
+        assert_send(head("http://localhost/"));
+        assert_send(post("http://localhost/", b"data", &mut writer));
+    }
+}
@@ -0,0 +1,582 @@
+//! asynchronous TCP stream for Tokio runtime
+#[cfg(any(feature = "tokio-native-tls", feature = "tokio-rust-tls"))]
+use crate::tls::AsyncConn;
+use crate::{
+    dns,
+    error::Error,
+    response::is_interim,
+    stream::{interleave_addrs, Keepalive, CONNECTION_ATTEMPT_DELAY},
+    tls,
+    uri::Uri,
+    CR_LF, LF,
+};
+#[cfg(not(target_family = "wasm"))]
+use socket2::SockRef;
+use std::{
+    future::{poll_fn, Future},
+    io,
+    net::SocketAddr,
+    path::Path,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::{
+    io::{AsyncBufRead, AsyncBufReadExt, AsyncRead, AsyncWrite, ReadBuf},
+    net::TcpStream,
+    time::{self, Sleep},
+};
+
+const BUF_SIZE: usize = 16 * 1000;
+
+/// Wrapper around asynchronous TCP stream of Tokio runtime for HTTP and HTTPS protocols.
+/// Allows to perform common operations on underlying stream.
+///
+/// HTTPS requires `tokio-native-tls` or `tokio-rust-tls` feature.
+#[derive(Debug)]
+pub struct AsyncStream {
+    transport: Transport,
+    read_timeout: Option<Duration>,
+    write_timeout: Option<Duration>,
+    read_timer: Option<Pin<Box<Sleep>>>,
+    write_timer: Option<Pin<Box<Sleep>>>,
+}
+
+/// Connection underlying `AsyncStream`.
+#[derive(Debug)]
+#[allow(clippy::large_enum_variant)]
+enum Transport {
+    Http(TcpStream),
+    #[cfg(any(feature = "tokio-native-tls", feature = "tokio-rust-tls"))]
+    Https(AsyncConn<TcpStream>),
+}
+
+impl AsyncStream {
+    /// Opens a TCP connection to a remote host with a connection timeout (if specified).
+    /// Timeout limits both resolving the host and connecting.
+    pub async fn connect(
+        uri: &Uri<'_>,
+        connect_timeout: Option<Duration>,
+    ) -> Result<AsyncStream, Error> {
+        let host = match uri.host() {
+            Some(h) => h,
+            None => return Err(Error::Parse(uri.missing_host_err())),
+        };
+        let port = uri.corr_port();
+
+        let connect = async {
+            let addrs = dns::resolve_async(host, port).await?;
+            connect_addrs(&addrs, None).await
+        };
+
+        let stream = with_timeout(connect_timeout, connect).await?;
+        Ok(AsyncStream::new(Transport::Http(stream)))
+    }
+
+    /// Opens a TCP connection to the first reachable address from `addrs`
+    /// with a connection timeout (if specified).
+    pub async fn connect_to(
+        addrs: &[SocketAddr],
+        connect_timeout: Option<Duration>,
+    ) -> Result<AsyncStream, Error> {
+        let stream = connect_addrs(addrs, connect_timeout).await?;
+        Ok(AsyncStream::new(Transport::Http(stream)))
+    }
+
+    /// Creates a stream over `transport` without timeouts.
+    fn new(transport: Transport) -> AsyncStream {
+        AsyncStream {
+            transport,
+            read_timeout: None,
+            write_timeout: None,
+            read_timer: None,
+            write_timer: None,
+        }
+    }
+
+    /// Tries to establish a secure connection over TLS.
+    ///
+    /// Checks if `uri` scheme denotes a HTTPS protocol:
+    /// - If yes, attemps to establish a secure connection
+    /// - Otherwise, returns the `stream` without any modification
+    pub async fn try_to_https(
+        stream: AsyncStream,
+        uri: &Uri<'_>,
+        root_cert_file_pem: Option<&Path>,
+    ) -> Result<AsyncStream, Error> {
+        let mut cnf = tls::Config::default();
+
+        if let (Transport::Http(_), "https", Some(p)) =
+            (&stream.transport, uri.scheme(), root_cert_file_pem)
+        {
+            cnf.add_root_cert_file_pem(p)?;
+        }
+
+        AsyncStream::try_to_https_with(stream, uri, &cnf).await
+    }
+
+    /// Tries to establish a secure connection over TLS using the given configuration.
+    /// Behaves like `try_to_https` otherwise.
+    pub async fn try_to_https_with(
+        stream: AsyncStream,
+        uri: &Uri<'_>,
+        cnf: &tls::Config,
+    ) -> Result<AsyncStream, Error> {
+        match stream.transport {
+            Transport::Http(http_stream) if uri.scheme() == "https" => {
+                let host = match uri.host() {
+                    Some(h) => h,
+                    None => return Err(Error::Parse(uri.missing_host_err())),
+                };
+
+                Ok(AsyncStream {
+                    transport: secure(cnf, host, http_stream).await?,
+                    ..stream
+                })
+            }
+            _ => Ok(stream),
+        }
+    }
+
+    /// Sets the read timeout. If a single read doesn't complete within `dur`,
+    /// it fails with `io::ErrorKind::TimedOut`.
+    pub fn set_read_timeout(&mut self, dur: Option<Duration>) -> Result<(), Error> {
+        self.read_timeout = check_timeout(dur)?;
+        self.read_timer = None;
+        Ok(())
+    }
+
+    /// Sets the write timeout. If a single write (or flush) doesn't complete within `dur`,
+    /// it fails with `io::ErrorKind::TimedOut`.
+    pub fn set_write_timeout(&mut self, dur: Option<Duration>) -> Result<(), Error> {
+        self.write_timeout = check_timeout(dur)?;
+        self.write_timer = None;
+        Ok(())
+    }
+
+    /// Sets the value of the `TCP_NODELAY` option on the underlying TCP stream.
+    /// If set, segments are sent as soon as possible, even if there is only a small amount of data.
+    pub fn set_nodelay(&mut self, nodelay: bool) -> Result<(), Error> {
+        Ok(self.tcp_stream().set_nodelay(nodelay)?)
+    }
+
+    /// Enables TCP keepalive on the underlying TCP stream with the given parameters,
+    /// or disables it if `keepalive` is `None`.
+    ///
+    /// Does nothing on WebAssembly targets.
+    #[cfg(not(target_family = "wasm"))]
+    pub fn set_keepalive(&mut self, keepalive: Option<Keepalive>) -> Result<(), Error> {
+        let socket = SockRef::from(self.tcp_stream());
+
+        match keepalive {
+            Some(keepalive) => socket.set_tcp_keepalive(&keepalive.into())?,
+            None => socket.set_keepalive(false)?,
+        }
+
+        Ok(())
+    }
+
+    /// Enables TCP keepalive on the underlying TCP stream with the given parameters,
+    /// or disables it if `keepalive` is `None`.
+    ///
+    /// Does nothing on WebAssembly targets.
+    #[cfg(target_family = "wasm")]
+    pub fn set_keepalive(&mut self, _keepalive: Option<Keepalive>) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Returns a reference to the underlying TCP stream.
+    fn tcp_stream(&self) -> &TcpStream {
+        match &self.transport {
+            Transport::Http(stream) => stream,
+            #[cfg(any(feature = "tokio-native-tls", feature = "tokio-rust-tls"))]
+            Transport::Https(conn) => conn.get_ref(),
+        }
+    }
+
+    /// Returns the socket address of the remote peer of this stream.
+    pub fn peer_addr(&self) -> Result<SocketAddr, Error> {
+        Ok(self.tcp_stream().peer_addr()?)
+    }
+
+    /// Returns certificates presented by the server (see `Conn::peer_certificates`).
+    /// Returns an empty list for connections without TLS.
+    pub fn peer_certificates(&self) -> Vec<tls::Certificate> {
+        match &self.transport {
+            #[cfg(any(feature = "tokio-native-tls", feature = "tokio-rust-tls"))]
+            Transport::Https(conn) => conn.peer_certificates(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Establishes a secure connection over `stream`.
+#[cfg(any(feature = "tokio-native-tls", feature = "tokio-rust-tls"))]
+async fn secure(cnf: &tls::Config, host: &str, stream: TcpStream) -> Result<Transport, Error> {
+    Ok(Transport::Https(cnf.connect_async(host, stream).await?))
+}
+
+/// Fails, as no asynchronous TLS library is enabled.
+#[cfg(not(any(feature = "tokio-native-tls", feature = "tokio-rust-tls")))]
+async fn secure(_cnf: &tls::Config, _host: &str, _stream: TcpStream) -> Result<Transport, Error> {
+    Err(Error::IO(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "HTTPS requires `tokio-native-tls` or `tokio-rust-tls` feature",
+    )))
+}
+
+/// Connects to the target host with a specified timeout, which limits both resolving
+/// the host and connecting. Works like `stream::connect_with_timeout`, but without
+/// blocking the executor.
+pub async fn connect_with_timeout<T, U>(host: T, port: u16, timeout: U) -> io::Result<TcpStream>
+where
+    Duration: From<U>,
+    T: AsRef<str>,
+{
+    let connect = async {
+        let addrs = dns::resolve_async(host.as_ref(), port).await?;
+        connect_addrs(&addrs, None).await
+    };
+
+    with_timeout(Some(Duration::from(timeout)), connect).await
+}
+
+/// Connects to the first reachable address from `addrs` with a timeout (if specified).
+///
+/// Follows Happy Eyeballs (RFC 8305) like `stream::connect_addrs`, but connection attempts
+/// are raced within the current task instead of separate threads.
+pub async fn connect_addrs(
+    addrs: &[SocketAddr],
+    timeout: Option<Duration>,
+) -> io::Result<TcpStream> {
+    type Attempt = Pin<Box<dyn Future<Output = io::Result<TcpStream>> + Send>>;
+
+    let mut addrs = interleave_addrs(addrs).into_iter();
+    let mut attempts: Vec<Attempt> = Vec::new();
+    let mut delay = Box::pin(time::sleep(Duration::ZERO));
+    let mut last_err = None;
+
+    let connect = poll_fn(|cx| loop {
+        let mut i = 0;
+
+        while i < attempts.len() {
+            match attempts[i].as_mut().poll(cx) {
+                Poll::Ready(Ok(stream)) => return Poll::Ready(Ok(stream)),
+                Poll::Ready(Err(err)) => {
+                    drop(attempts.swap_remove(i));
+                    last_err = Some(err);
+                }
+                Poll::Pending => i += 1,
+            }
+        }
+
+        // Next attempt starts once the previous ones failed or the delay elapsed.
+        if attempts.is_empty() || delay.as_mut().poll(cx).is_ready() {
+            match addrs.next() {
+                Some(addr) => {
+                    attempts.push(Box::pin(TcpStream::connect(addr)));
+                    delay
+                        .as_mut()
+                        .reset(time::Instant::now() + CONNECTION_ATTEMPT_DELAY);
+                    continue;
+                }
+                None if attempts.is_empty() => {
+                    return Poll::Ready(Err(last_err.take().unwrap_or_else(|| {
+                        io::Error::new(
+                            io::ErrorKind::AddrNotAvailable,
+                            "No addresses to connect to",
+                        )
+                    })))
+                }
+                None => (),
+            }
+        }
+
+        return Poll::Pending;
+    });
+
+    with_timeout(timeout, connect).await
+}
+
+/// Fails with `io::ErrorKind::TimedOut` if `connect` doesn't complete within `timeout`.
+async fn with_timeout<F>(timeout: Option<Duration>, connect: F) -> io::Result<TcpStream>
+where
+    F: Future<Output = io::Result<TcpStream>>,
+{
+    match timeout {
+        Some(timeout) => match time::timeout(timeout, connect).await {
+            Ok(res) => res,
+            Err(_) => Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "Connection timed out",
+            )),
+        },
+        None => connect.await,
+    }
+}
+
+/// Checks if `dur` is a valid timeout, like `TcpStream::set_read_timeout` does.
+fn check_timeout(dur: Option<Duration>) -> Result<Option<Duration>, Error> {
+    match dur {
+        Some(dur) if dur.is_zero() => Err(Error::IO(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "cannot set a 0 duration timeout",
+        ))),
+        _ => Ok(dur),
+    }
+}
+
+/// Limits the time, for which an operation may stay pending.
+///
+/// `timer` is started when the operation returns `Poll::Pending` for the first time,
+/// and it's reset once the operation completes. If the timer fires first,
+/// the operation fails with `io::ErrorKind::TimedOut`.
+fn poll_timeout<T>(
+    res: Poll<io::Result<T>>,
+    timer: &mut Option<Pin<Box<Sleep>>>,
+    timeout: Option<Duration>,
+    cx: &mut Context<'_>,
+) -> Poll<io::Result<T>> {
+    let timeout = match (res.is_pending(), timeout) {
+        (true, Some(timeout)) => timeout,
+        (true, None) => return Poll::Pending,
+        (false, _) => {
+            *timer = None;
+            return res;
+        }
+    };
+
+    let sleep = timer.get_or_insert_with(|| Box::pin(time::sleep(timeout)));
+
+    match sleep.as_mut().poll(cx) {
+        Poll::Ready(()) => {
+            *timer = None;
+            Poll::Ready(Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "Operation timed out",
+            )))
+        }
+        Poll::Pending => Poll::Pending,
+    }
+}
+
+impl AsyncRead for AsyncStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        let res = match &mut this.transport {
+            Transport::Http(stream) => Pin::new(stream).poll_read(cx, buf),
+            #[cfg(any(feature = "tokio-native-tls", feature = "tokio-rust-tls"))]
+            Transport::Https(conn) => Pin::new(conn).poll_read(cx, buf),
+        };
+
+        poll_timeout(res, &mut this.read_timer, this.read_timeout, cx)
+    }
+}
+
+impl AsyncWrite for AsyncStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        let res = match &mut this.transport {
+            Transport::Http(stream) => Pin::new(stream).poll_write(cx, buf),
+            #[cfg(any(feature = "tokio-native-tls", feature = "tokio-rust-tls"))]
+            Transport::Https(conn) => Pin::new(conn).poll_write(cx, buf),
+        };
+
+        poll_timeout(res, &mut this.write_timer, this.write_timeout, cx)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        let res = match &mut this.transport {
+            Transport::Http(stream) => Pin::new(stream).poll_flush(cx),
+            #[cfg(any(feature = "tokio-native-tls", feature = "tokio-rust-tls"))]
+            Transport::Https(conn) => Pin::new(conn).poll_flush(cx),
+        };
+
+        poll_timeout(res, &mut this.write_timer, this.write_timeout, cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        let res = match &mut this.transport {
+            Transport::Http(stream) => Pin::new(stream).poll_shutdown(cx),
+            #[cfg(any(feature = "tokio-native-tls", feature = "tokio-rust-tls"))]
+            Transport::Https(conn) => Pin::new(conn).poll_shutdown(cx),
+        };
+
+        poll_timeout(res, &mut this.write_timer, this.write_timeout, cx)
+    }
+}
+
+/// Reads the head of HTTP response from `reader`. Works like `stream::read_head`,
+/// but without blocking the executor.
+pub async fn read_head<B>(reader: &mut B) -> Vec<u8>
+where
+    B: AsyncBufRead + Unpin,
+{
+    let mut buf = Vec::with_capacity(BUF_SIZE);
+    let mut head_start = 0;
+
+    loop {
+        match reader.read_until(LF, &mut buf).await {
+            Ok(0) | Err(_) => break,
+            Ok(len) => {
+                let full_len = buf.len();
+
+                if len == 2 && &buf[full_len - 2..] == CR_LF {
+                    if is_interim(&buf[head_start..]) {
+                        head_start = full_len;
+                        continue;
+                    }
+
+                    break;
+                }
+            }
+        }
+    }
+
+    buf
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{request::Method, test_util::TestServer};
+    use std::{convert::TryFrom, io::Write, net::TcpListener, thread};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt, BufReader as AsyncBufReader};
+
+    const RESPONSE: &[u8] = b"HTTP/1.1 100 Continue\r\n\r\n\
+                              HTTP/1.1 200 OK\r\n\
+                              Content-Length: 5\r\n\r\n\
+                              hello";
+
+    #[tokio::test]
+    async fn async_stream_connect() {
+        let server = TestServer::start([RESPONSE]).unwrap();
+        let addr = server.addr();
+
+        let uri = format!("http://{}/", addr);
+        let uri = Uri::try_from(uri.as_str()).unwrap();
+        let mut stream = AsyncStream::connect(&uri, Some(Duration::from_secs(1)))
+            .await
+            .unwrap();
+
+        assert_eq!(stream.peer_addr().unwrap(), addr);
+        stream.set_nodelay(true).unwrap();
+        stream.write_all(b"GET / HTTP/1.1\r\n\r\n").await.unwrap();
+
+        let mut reader = AsyncBufReader::new(stream);
+        let head = read_head(&mut reader).await;
+        let mut body = Vec::new();
+        reader.read_to_end(&mut body).await.unwrap();
+
+        assert_eq!(head, &RESPONSE[..RESPONSE.len() - 5]);
+        assert_eq!(body, b"hello");
+        let requests = server.join().unwrap();
+        assert_eq!(requests[0].head().method(), &Method::GET);
+        assert_eq!(requests[0].head().target(), "/");
+    }
+
+    #[tokio::test]
+    async fn async_stream_try_to_https() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        // Server answers before the TLS handshake, so it fails.
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            stream
+                .write_all(b"HTTP/1.1 400 Bad Request\r\n\r\n")
+                .unwrap();
+        });
+
+        let uri = format!("http://{}/", addr);
+        let uri = Uri::try_from(uri.as_str()).unwrap();
+        let stream = AsyncStream::connect(&uri, None).await.unwrap();
+        let stream = AsyncStream::try_to_https(stream, &uri, None).await.unwrap();
+        assert!(matches!(stream.transport, Transport::Http(_)));
+        assert!(stream.peer_certificates().is_empty());
+
+        let uri = format!("https://{}/", addr);
+        let uri = Uri::try_from(uri.as_str()).unwrap();
+        let res = AsyncStream::try_to_https(stream, &uri, None).await;
+        assert!(res.is_err());
+
+        server.join().unwrap();
+    }
+
+    #[tokio::test]
+    async fn async_stream_set_read_timeout() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            stream.write_all(b"HTTP/1.1").unwrap();
+            thread::sleep(Duration::from_millis(500));
+        });
+
+        let uri = format!("http://{}/", addr);
+        let uri = Uri::try_from(uri.as_str()).unwrap();
+        let mut stream = AsyncStream::connect(&uri, None).await.unwrap();
+
+        assert!(stream.set_read_timeout(Some(Duration::ZERO)).is_err());
+        stream
+            .set_read_timeout(Some(Duration::from_millis(100)))
+            .unwrap();
+        stream
+            .set_write_timeout(Some(Duration::from_millis(100)))
+            .unwrap();
+
+        let mut buf = [0; 16];
+        let len = stream.read(&mut buf).await.unwrap();
+        assert_eq!(&buf[..len], b"HTTP/1.1");
+
+        let err = stream.read(&mut buf).await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+
+        server.join().unwrap();
+    }
+
+    #[tokio::test]
+    async fn fn_connect_addrs() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        // Port of a closed listener refuses connections.
+        let closed = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+
+        let stream = connect_addrs(&[closed, addr], Some(Duration::from_secs(1)))
+            .await
+            .unwrap();
+        assert_eq!(stream.peer_addr().unwrap(), addr);
+
+        let err = connect_addrs(&[closed], None).await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::ConnectionRefused);
+
+        let err = connect_addrs(&[], None).await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::AddrNotAvailable);
+
+        let stream = connect_with_timeout("127.0.0.1", addr.port(), Duration::from_secs(1))
+            .await
+            .unwrap();
+        assert_eq!(stream.peer_addr().unwrap(), addr);
+
+        let pending = std::future::pending();
+        let err = with_timeout(Some(Duration::from_millis(10)), pending)
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+    }
+}
@@ -0,0 +1,373 @@
+//! responding to authentication challenges
+use crate::request::{Authentication, Method};
+use md5::Md5;
+use sha2::{Digest, Sha256};
+use std::{
+    fmt::{self, Write},
+    process,
+    sync::atomic::{AtomicU64, Ordering},
+    time::{SystemTime, UNIX_EPOCH},
+};
+use zeroize::Zeroizing;
+
+/// Authentication challenge sent by a server in the `WWW-Authenticate` header.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Challenge {
+    scheme: String,
+    params: Vec<(String, String)>,
+}
+
+impl Challenge {
+    /// Parses all challenges contained in the value of a `WWW-Authenticate` header.
+    ///
+    /// # Examples
+    /// ```
+    /// use http_req::auth::Challenge;
+    ///
+    /// let challenges = Challenge::parse_all(r#"Digest realm="api", nonce="abc", Basic realm="api""#);
+    ///
+    /// assert_eq!(challenges.len(), 2);
+    /// assert_eq!(challenges[0].scheme(), "Digest");
+    /// assert_eq!(challenges[0].param("nonce"), Some("abc"));
+    /// assert_eq!(challenges[1].scheme(), "Basic");
+    /// ```
+    pub fn parse_all(header: &str) -> Vec<Challenge> {
+        let mut challenges: Vec<Challenge> = Vec::new();
+
+        for item in split_unquoted(header, ',') {
+            let item = item.trim();
+            let eq = item.find('=').unwrap_or(item.len());
+
+            let param = match item[..eq].find(' ') {
+                Some(space) => {
+                    challenges.push(Challenge {
+                        scheme: item[..space].to_string(),
+                        params: Vec::new(),
+                    });
+                    item[space..].trim_start()
+                }
+                None if eq == item.len() && !item.is_empty() => {
+                    challenges.push(Challenge {
+                        scheme: item.to_string(),
+                        params: Vec::new(),
+                    });
+                    continue;
+                }
+                None => item,
+            };
+
+            if let (Some(challenge), Some((key, val))) =
+                (challenges.last_mut(), param.split_once('='))
+            {
+                challenge
+                    .params
+                    .push((key.trim().to_string(), unquote(val.trim())));
+            }
+        }
+
+        challenges
+    }
+
+    /// Returns the authentication scheme, e.g. `Basic` or `Digest`.
+    pub fn scheme(&self) -> &str {
+        &self.scheme
+    }
+
+    /// Returns the value of parameter `key` (case-insensitive).
+    pub fn param(&self, key: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// Username and password used to answer authentication challenges.
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) struct Credentials<'a> {
+    pub(crate) username: &'a str,
+    pub(crate) password: &'a str,
+}
+
+impl fmt::Debug for Credentials<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Credentials")
+            .field("username", &self.username)
+            .field("password", &"<redacted>")
+            .finish()
+    }
+}
+
+impl Credentials<'_> {
+    /// Creates the value of `Authorization` header answering the strongest supported
+    /// challenge in `header`. Digest is preferred over Basic.
+    pub(crate) fn authorize(
+        &self,
+        header: &str,
+        method: &Method,
+        resource: &str,
+        body: Option<&[u8]>,
+    ) -> Option<Zeroizing<String>> {
+        let challenges = Challenge::parse_all(header);
+        let find = |scheme: &str| {
+            challenges
+                .iter()
+                .find(|c| c.scheme.eq_ignore_ascii_case(scheme))
+        };
+
+        if let Some(challenge) = find("Digest") {
+            if let Some(val) = self.digest(challenge, method, resource, body) {
+                return Some(val);
+            }
+        }
+
+        find("Basic").map(|_| {
+            let (_, val) = Authentication::basic(self.username, self.password).header();
+            Zeroizing::new(val)
+        })
+    }
+
+    /// Answers a `Digest` challenge (RFC 7616). Returns `None` if the challenge uses
+    /// an unsupported algorithm or quality of protection.
+    fn digest(
+        &self,
+        challenge: &Challenge,
+        method: &Method,
+        resource: &str,
+        body: Option<&[u8]>,
+    ) -> Option<Zeroizing<String>> {
+        let realm = challenge.param("realm").unwrap_or_default();
+        let nonce = challenge.param("nonce")?;
+        let algorithm = challenge.param("algorithm").unwrap_or("MD5");
+
+        let (hash, sess): (fn(&[u8]) -> String, bool) =
+            match algorithm.to_ascii_uppercase().as_str() {
+                "MD5" => (hex_digest::<Md5>, false),
+                "MD5-SESS" => (hex_digest::<Md5>, true),
+                "SHA-256" => (hex_digest::<Sha256>, false),
+                "SHA-256-SESS" => (hex_digest::<Sha256>, true),
+                _ => return None,
+            };
+
+        let qop = match challenge.param("qop") {
+            Some(qop) => {
+                let options: Vec<_> = qop.split(',').map(str::trim).collect();
+                if options.contains(&"auth") {
+                    Some("auth")
+                } else if options.contains(&"auth-int") {
+                    Some("auth-int")
+                } else {
+                    return None;
+                }
+            }
+            None => None,
+        };
+
+        let cnonce = cnonce();
+        let nc = "00000001";
+
+        let secret = Zeroizing::new(format!("{}:{}:{}", self.username, realm, self.password));
+        let mut ha1 = Zeroizing::new(hash(secret.as_bytes()));
+        if sess {
+            ha1 = Zeroizing::new(hash(format!("{}:{}:{}", *ha1, nonce, cnonce).as_bytes()));
+        }
+
+        let ha2 = match qop {
+            Some("auth-int") => {
+                let body_hash = hash(body.unwrap_or_default());
+                hash(format!("{}:{}:{}", method, resource, body_hash).as_bytes())
+            }
+            _ => hash(format!("{}:{}", method, resource).as_bytes()),
+        };
+
+        let response = match qop {
+            Some(qop) => {
+                hash(format!("{}:{}:{}:{}:{}:{}", *ha1, nonce, nc, cnonce, qop, ha2).as_bytes())
+            }
+            None => hash(format!("{}:{}:{}", *ha1, nonce, ha2).as_bytes()),
+        };
+
+        let mut val = Zeroizing::new(String::with_capacity(256));
+        write!(
+            val,
+            r#"Digest username="{}", realm="{}", nonce="{}", uri="{}", algorithm={}, response="{}""#,
+            quote(self.username),
+            quote(realm),
+            quote(nonce),
+            resource,
+            algorithm,
+            response
+        )
+        .ok()?;
+
+        if let Some(qop) = qop {
+            write!(val, r#", qop={}, nc={}, cnonce="{}""#, qop, nc, cnonce).ok()?;
+        }
+
+        if let Some(opaque) = challenge.param("opaque") {
+            write!(val, r#", opaque="{}""#, quote(opaque)).ok()?;
+        }
+
+        Some(val)
+    }
+}
+
+/// Splits `s` at every `sep`, which is not inside a quoted string.
+fn split_unquoted(s: &str, sep: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let (mut start, mut quoted, mut escaped) = (0, false, false);
+
+    for (i, c) in s.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if quoted && c == '\\' {
+            escaped = true;
+        } else if c == '"' {
+            quoted = !quoted;
+        } else if c == sep && !quoted {
+            parts.push(&s[start..i]);
+            start = i + 1;
+        }
+    }
+
+    parts.push(&s[start..]);
+    parts
+}
+
+/// Removes quotes and escapes from a quoted string. Other values are returned as is.
+fn unquote(s: &str) -> String {
+    match s.strip_prefix('"').and_then(|s| s.strip_suffix('"')) {
+        Some(inner) => {
+            let mut res = String::with_capacity(inner.len());
+            let mut chars = inner.chars();
+
+            while let Some(c) = chars.next() {
+                match c {
+                    '\\' => res.extend(chars.next()),
+                    c => res.push(c),
+                }
+            }
+
+            res
+        }
+        None => s.to_string(),
+    }
+}
+
+/// Escapes `s`, so it can be placed inside a quoted string.
+fn quote(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Returns the hex-encoded digest of `data`.
+fn hex_digest<D: Digest>(data: &[u8]) -> String {
+    D::digest(data).iter().fold(String::new(), |mut s, b| {
+        let _ = write!(s, "{:02x}", b);
+        s
+    })
+}
+
+/// Generates a client nonce, unique for every call.
+fn cnonce() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    let time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    let count = COUNTER.fetch_add(1, Ordering::Relaxed);
+    let seed = format!("{}:{}:{}", time, process::id(), count);
+
+    hex_digest::<Sha256>(seed.as_bytes())[..32].to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CREDENTIALS: Credentials = Credentials {
+        username: "Mufasa",
+        password: "Circle of Life",
+    };
+
+    #[test]
+    fn challenge_parse_all() {
+        let challenges = Challenge::parse_all(
+            r#"Newauth realm="apps", type=1, title="Login to \"apps\"", Basic realm="simple""#,
+        );
+
+        assert_eq!(
+            challenges,
+            vec![
+                Challenge {
+                    scheme: "Newauth".to_string(),
+                    params: vec![
+                        ("realm".to_string(), "apps".to_string()),
+                        ("type".to_string(), "1".to_string()),
+                        ("title".to_string(), "Login to \"apps\"".to_string()),
+                    ],
+                },
+                Challenge {
+                    scheme: "Basic".to_string(),
+                    params: vec![("realm".to_string(), "simple".to_string())],
+                },
+            ]
+        );
+
+        let challenges = Challenge::parse_all("Negotiate, Basic realm=x");
+        assert_eq!(challenges[0].scheme(), "Negotiate");
+        assert_eq!(challenges[1].param("REALM"), Some("x"));
+    }
+
+    #[test]
+    fn credentials_basic() {
+        let val = CREDENTIALS
+            .authorize(r#"Basic realm="x""#, &Method::GET, "/", None)
+            .unwrap();
+
+        assert_eq!(*val, "Basic TXVmYXNhOkNpcmNsZSBvZiBMaWZl");
+        assert!(CREDENTIALS
+            .authorize("Bearer", &Method::GET, "/", None)
+            .is_none());
+    }
+
+    #[test]
+    fn credentials_digest() {
+        // Example from RFC 7616, section 3.9.1, with a fixed cnonce.
+        let challenge = &Challenge::parse_all(
+            r#"Digest realm="http-auth@example.org", qop="auth, auth-int", algorithm=MD5, nonce="7ypf/xlj9XXwfDPEoM4URrv/xwf94BcCAzFZH4GiTo0v", opaque="FQhe/qaU925kfnzjCev0ciny7QMkPqMAFRtzCUYo5tdS""#,
+        )[0];
+
+        let val = CREDENTIALS
+            .digest(challenge, &Method::GET, "/dir/index.html", None)
+            .unwrap();
+
+        assert!(val.starts_with(
+            r#"Digest username="Mufasa", realm="http-auth@example.org", nonce="7ypf/xlj9XXwfDPEoM4URrv/xwf94BcCAzFZH4GiTo0v", uri="/dir/index.html", algorithm=MD5, response=""#
+        ));
+        assert!(val.contains(", qop=auth, nc=00000001, cnonce=\""));
+        assert!(val.ends_with(r#", opaque="FQhe/qaU925kfnzjCev0ciny7QMkPqMAFRtzCUYo5tdS""#));
+
+        let unsupported = &Challenge::parse_all("Digest nonce=1, algorithm=SHA-512-256")[0];
+        assert!(CREDENTIALS
+            .digest(unsupported, &Method::GET, "/", None)
+            .is_none());
+    }
+
+    #[test]
+    fn fn_hex_digest() {
+        // Response computed by RFC 7616, section 3.9.1 for cnonce "f2/wE4q74E6zIJEtWaHKaf5wv/H5QzzpXusqGemxURZJ".
+        let ha1 = hex_digest::<Md5>(b"Mufasa:http-auth@example.org:Circle of Life");
+        let ha2 = hex_digest::<Md5>(b"GET:/dir/index.html");
+        let response = hex_digest::<Md5>(
+            format!(
+                "{}:7ypf/xlj9XXwfDPEoM4URrv/xwf94BcCAzFZH4GiTo0v:00000001:f2/wE4q74E6zIJEtWaHKaf5wv/H5QzzpXusqGemxURZJ:auth:{}",
+                ha1, ha2
+            )
+            .as_bytes(),
+        );
+
+        assert_eq!(response, "8ca523f5e9506fed4657c9700eebdbec");
+        assert_ne!(cnonce(), cnonce());
+    }
+}
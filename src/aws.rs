@@ -0,0 +1,326 @@
+//! signing requests with AWS Signature Version 4
+use crate::{date::to_civil, request::RequestMessage};
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+use std::{
+    fmt::{self, Write},
+    str,
+    time::SystemTime,
+};
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+
+const ALGORITHM: &str = "AWS4-HMAC-SHA256";
+
+/// Signs requests with AWS Signature Version 4 for a service in a region.
+///
+/// Signing adds `X-Amz-Date` (and `X-Amz-Security-Token` for temporary credentials)
+/// and `Authorization` headers. All headers of the message are signed. For `s3`,
+/// `X-Amz-Content-Sha256` header with the hash of the body is added as well.
+///
+/// # Examples
+/// ```
+/// use http_req::{aws::AwsSigner, request::RequestMessage, uri::Uri};
+/// use std::{convert::TryFrom, time::SystemTime};
+///
+/// let uri = Uri::try_from("https://sqs.us-east-1.amazonaws.com/?Action=ListQueues").unwrap();
+/// let signer = AwsSigner::new("AKIDEXAMPLE", "secret", "us-east-1", "sqs");
+///
+/// let mut msg = RequestMessage::new(&uri);
+/// signer.sign(&mut msg, SystemTime::now());
+/// ```
+#[derive(PartialEq, Zeroize, ZeroizeOnDrop)]
+pub struct AwsSigner {
+    access_key_id: String,
+    secret_access_key: String,
+    session_token: Option<String>,
+    region: String,
+    service: String,
+}
+
+impl AwsSigner {
+    /// Creates a new `AwsSigner` with long-term credentials.
+    pub fn new<T, U>(
+        access_key_id: &T,
+        secret_access_key: &U,
+        region: &str,
+        service: &str,
+    ) -> AwsSigner
+    where
+        T: ToString + ?Sized,
+        U: ToString + ?Sized,
+    {
+        AwsSigner {
+            access_key_id: access_key_id.to_string(),
+            secret_access_key: secret_access_key.to_string(),
+            session_token: None,
+            region: region.to_string(),
+            service: service.to_string(),
+        }
+    }
+
+    /// Sets the session token of temporary credentials (e.g. in AWS Lambda).
+    ///
+    /// # Examples
+    /// ```
+    /// use http_req::aws::AwsSigner;
+    ///
+    /// let mut signer = AwsSigner::new("AKIDEXAMPLE", "secret", "us-east-1", "sqs");
+    /// signer.session_token("token");
+    /// ```
+    pub fn session_token<T>(&mut self, token: &T) -> &mut Self
+    where
+        T: ToString + ?Sized,
+    {
+        self.session_token = Some(token.to_string());
+        self
+    }
+
+    /// Signs `msg` as of `time`. Headers must not be changed after signing.
+    pub fn sign(&self, msg: &mut RequestMessage, time: SystemTime) {
+        let (year, month, day, hour, min, sec) = to_civil(time);
+        let date = format!("{:04}{:02}{:02}", year, month, day);
+        let amz_date = format!("{}T{:02}{:02}{:02}Z", date, hour, min, sec);
+        let payload_hash = hex(&Sha256::digest(msg.body.unwrap_or_default()));
+
+        msg.header("X-Amz-Date", &amz_date);
+
+        if let Some(token) = &self.session_token {
+            msg.header("X-Amz-Security-Token", token);
+        }
+
+        if self.service == "s3" {
+            msg.header("X-Amz-Content-Sha256", &payload_hash);
+        }
+
+        let (canonical_request, signed_headers) = self.canonical_request(msg, &payload_hash);
+        let scope = format!("{}/{}/{}/aws4_request", date, self.region, self.service);
+        let string_to_sign = format!(
+            "{}\n{}\n{}\n{}",
+            ALGORITHM,
+            amz_date,
+            scope,
+            hex(&Sha256::digest(canonical_request.as_bytes()))
+        );
+
+        let key = Zeroizing::new(["AWS4", &self.secret_access_key].concat());
+        let mut signing_key = hmac(key.as_bytes(), date.as_bytes());
+        for part in [&self.region, &self.service, "aws4_request"] {
+            signing_key = hmac(&signing_key, part.as_bytes());
+        }
+
+        let signature = hex(&hmac(&signing_key, string_to_sign.as_bytes()));
+        signing_key.zeroize();
+
+        msg.header(
+            "Authorization",
+            &format!(
+                "{} Credential={}/{}, SignedHeaders={}, Signature={}",
+                ALGORITHM, self.access_key_id, scope, signed_headers, signature
+            ),
+        );
+    }
+
+    /// Returns the canonical request and the list of signed headers for `msg`.
+    fn canonical_request(&self, msg: &RequestMessage, payload_hash: &str) -> (String, String) {
+        let path = msg.uri.path().unwrap_or("/");
+        let path = match self.service.as_str() {
+            // S3 expects paths encoded once, other services twice.
+            "s3" => encode(&decode(path), false),
+            _ => encode(&encode(&decode(path), false), false),
+        };
+
+        let mut query: Vec<_> = msg
+            .uri
+            .query()
+            .unwrap_or_default()
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| {
+                let (key, val) = pair.split_once('=').unwrap_or((pair, ""));
+                (encode(&decode(key), true), encode(&decode(val), true))
+            })
+            .collect();
+        query.sort();
+
+        let query: Vec<_> = query
+            .iter()
+            .map(|(key, val)| format!("{}={}", key, val))
+            .collect();
+
+        let mut headers: Vec<_> = msg
+            .headers
+            .iter()
+            .filter(|(key, _)| !key.eq_ignore_ascii_case("Authorization"))
+            .map(|(key, val)| {
+                let val: Vec<_> = val.split_whitespace().collect();
+                (key.to_ascii_lowercase(), val.join(" "))
+            })
+            .collect();
+        headers.sort();
+
+        let signed_headers: Vec<_> = headers.iter().map(|(key, _)| key.as_str()).collect();
+        let signed_headers = signed_headers.join(";");
+
+        let mut canonical_request = format!("{}\n{}\n{}\n", msg.method, path, query.join("&"));
+        for (key, val) in &headers {
+            canonical_request = canonical_request + key + ":" + val + "\n";
+        }
+        canonical_request = canonical_request + "\n" + &signed_headers + "\n" + payload_hash;
+
+        (canonical_request, signed_headers)
+    }
+}
+
+impl fmt::Debug for AwsSigner {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("AwsSigner")
+            .field("access_key_id", &self.access_key_id)
+            .field("region", &self.region)
+            .field("service", &self.service)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Returns HMAC-SHA256 of `data` with `key`.
+fn hmac(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any size");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Returns lowercase hex encoding of `bytes`.
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().fold(String::new(), |mut s, b| {
+        let _ = write!(s, "{:02x}", b);
+        s
+    })
+}
+
+/// Percent-encodes all characters except unreserved ones (and `/` unless `slash` is set).
+fn encode(s: &str, slash: bool) -> String {
+    let mut res = String::with_capacity(s.len());
+
+    for b in s.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                res.push(b as char)
+            }
+            b'/' if !slash => res.push('/'),
+            _ => {
+                let _ = write!(res, "%{:02X}", b);
+            }
+        }
+    }
+
+    res
+}
+
+/// Decodes percent-encoded characters. Invalid sequences are left as they are.
+fn decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut res = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3).and_then(|h| str::from_utf8(h).ok());
+
+        match (bytes[i], hex.and_then(|h| u8::from_str_radix(h, 16).ok())) {
+            (b'%', Some(b)) => {
+                res.push(b);
+                i += 3;
+            }
+            (b, _) => {
+                res.push(b);
+                i += 1;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&res).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{response::Headers, uri::Uri};
+    use std::{
+        convert::TryFrom,
+        time::{Duration, UNIX_EPOCH},
+    };
+
+    // Credentials and time used in the AWS Signature Version 4 test suite.
+    const ACCESS_KEY_ID: &str = "AKIDEXAMPLE";
+    const SECRET_ACCESS_KEY: &str = "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY";
+    const TIME: u64 = 1440938160;
+
+    fn sign(uri: &str) -> String {
+        let uri = Uri::try_from(uri).unwrap();
+        let signer = AwsSigner::new(ACCESS_KEY_ID, SECRET_ACCESS_KEY, "us-east-1", "service");
+
+        let mut headers = Headers::new();
+        headers.insert("Host", "example.amazonaws.com");
+
+        let mut msg = RequestMessage::new(&uri);
+        msg.headers(headers);
+        signer.sign(&mut msg, UNIX_EPOCH + Duration::from_secs(TIME));
+
+        assert_eq!(
+            msg.headers.get("X-Amz-Date"),
+            Some(&"20150830T123600Z".to_string())
+        );
+        msg.headers.get("Authorization").unwrap().to_string()
+    }
+
+    #[test]
+    fn aws_signer_get_vanilla() {
+        assert_eq!(
+            sign("https://example.amazonaws.com/"),
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/service/aws4_request, \
+            SignedHeaders=host;x-amz-date, \
+            Signature=5fa00fa31553b73ebf1942676e86291e8372ff2a2260956d9b8aae1d763fbf31"
+        );
+    }
+
+    #[test]
+    fn aws_signer_query_order() {
+        assert_eq!(
+            sign("https://example.amazonaws.com/?Param2=value2&Param1=value1"),
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/service/aws4_request, \
+            SignedHeaders=host;x-amz-date, \
+            Signature=b97d918cfa904a5beff61c982a1b6f458b799221646efd99d3219ec94cdf2500"
+        );
+    }
+
+    #[test]
+    fn aws_signer_session_token() {
+        let uri = Uri::try_from("https://bucket.s3.amazonaws.com/a%20b").unwrap();
+        let mut signer = AwsSigner::new(ACCESS_KEY_ID, SECRET_ACCESS_KEY, "us-east-1", "s3");
+        signer.session_token("token");
+
+        let mut msg = RequestMessage::new(&uri);
+        msg.body(b"body");
+        signer.sign(&mut msg, UNIX_EPOCH + Duration::from_secs(TIME));
+
+        assert_eq!(
+            msg.headers.get("X-Amz-Security-Token"),
+            Some(&"token".to_string())
+        );
+        assert_eq!(
+            msg.headers.get("X-Amz-Content-Sha256"),
+            Some(&hex(&Sha256::digest(b"body")))
+        );
+
+        let (canonical_request, signed_headers) =
+            signer.canonical_request(&msg, &hex(&Sha256::digest(b"body")));
+        assert!(canonical_request.starts_with("GET\n/a%20b\n\n"));
+        assert!(signed_headers.contains("x-amz-security-token"));
+        assert!(!format!("{:?}", signer).contains(SECRET_ACCESS_KEY));
+    }
+
+    #[test]
+    fn fn_encode_decode() {
+        assert_eq!(encode("a b/c~", false), "a%20b/c~");
+        assert_eq!(encode("a b/c~", true), "a%20b%2Fc~");
+        assert_eq!(decode("a%20b%2fc%zz"), "a b/c%zz");
+    }
+}
@@ -0,0 +1,266 @@
+//! sharing a body of a response between multiple readers
+use crate::{error::Error, sink::BodySink};
+use std::{
+    io::{self, Read},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc::{self, Receiver, SyncSender},
+        Arc,
+    },
+};
+
+/// What `Broadcast` does with a reader whose buffer is full.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum SlowConsumer {
+    /// Waits until the reader catches up. The slowest reader limits the speed of the transfer.
+    Wait,
+    /// Disconnects the reader, which then fails with `io::ErrorKind::Other`.
+    /// Other readers are not affected.
+    Disconnect,
+}
+
+/// Message sent to readers.
+#[derive(Debug)]
+enum Message {
+    Chunk(Arc<[u8]>),
+    End,
+    Abort(String),
+}
+
+/// Sending half of a single reader.
+#[derive(Debug)]
+struct Subscriber {
+    sender: SyncSender<Message>,
+    pending: Arc<AtomicUsize>,
+}
+
+/// `BodySink` passing the body of a response to several readers, each consuming it
+/// at its own pace (typically in a separate thread).
+///
+/// Every reader buffers at most `capacity` chunks of the body. When the buffer of a reader
+/// is full, `Broadcast` either waits for it or disconnects it, depending on `SlowConsumer`.
+/// Readers get EOF once the whole body has been received and an error if the request fails.
+///
+/// With `SlowConsumer::Wait`, readers mustn't be read in the thread sending the request,
+/// as it would block when their buffers are full.
+///
+/// # Examples
+/// ```no_run
+/// use http_req::{
+///     broadcast::{Broadcast, SlowConsumer},
+///     request::Request,
+///     uri::Uri,
+/// };
+/// use std::{convert::TryFrom, io::{self, Read}, thread};
+///
+/// let uri = Uri::try_from("https://www.rust-lang.org/learn").unwrap();
+/// let mut broadcast = Broadcast::new(16, SlowConsumer::Wait);
+///
+/// let mut reader = broadcast.subscribe();
+/// let counter = thread::spawn(move || io::copy(&mut reader, &mut io::sink()));
+///
+/// let mut reader = broadcast.subscribe();
+/// let saver = thread::spawn(move || {
+///     let mut body = Vec::new();
+///     reader.read_to_end(&mut body).map(|_| body)
+/// });
+///
+/// Request::new(&uri).send(&mut broadcast).unwrap();
+///
+/// let len = counter.join().unwrap().unwrap();
+/// let body = saver.join().unwrap().unwrap();
+/// assert_eq!(len as usize, body.len());
+/// ```
+#[derive(Debug)]
+pub struct Broadcast {
+    subscribers: Vec<Subscriber>,
+    capacity: usize,
+    policy: SlowConsumer,
+}
+
+impl Broadcast {
+    /// Creates a new `Broadcast`, which buffers up to `capacity` chunks for every reader.
+    pub fn new(capacity: usize, policy: SlowConsumer) -> Broadcast {
+        Broadcast {
+            subscribers: Vec::new(),
+            capacity: capacity.max(1),
+            policy,
+        }
+    }
+
+    /// Adds a new reader. It receives the data written after it has been added.
+    pub fn subscribe(&mut self) -> BroadcastReader {
+        // One more slot is left for the final message, so it can always be delivered.
+        let (sender, receiver) = mpsc::sync_channel(self.capacity + 1);
+        let pending = Arc::new(AtomicUsize::new(0));
+
+        self.subscribers.push(Subscriber {
+            sender,
+            pending: Arc::clone(&pending),
+        });
+
+        BroadcastReader {
+            receiver,
+            pending,
+            chunk: Arc::from(&[][..]),
+            pos: 0,
+            done: false,
+        }
+    }
+
+    /// Returns the number of connected readers.
+    pub fn readers(&self) -> usize {
+        self.subscribers.len()
+    }
+
+    /// Sends the final message to all readers and disconnects them.
+    fn close(&mut self, msg: impl Fn() -> Message) {
+        for subscriber in self.subscribers.drain(..) {
+            subscriber.sender.send(msg()).unwrap_or(());
+        }
+    }
+}
+
+impl BodySink for Broadcast {
+    fn write_chunk(&mut self, chunk: &[u8]) -> io::Result<()> {
+        let chunk: Arc<[u8]> = Arc::from(chunk);
+        let (capacity, policy) = (self.capacity, self.policy);
+
+        self.subscribers.retain(|subscriber| {
+            if policy == SlowConsumer::Disconnect
+                && subscriber.pending.load(Ordering::Acquire) >= capacity
+            {
+                return false;
+            }
+
+            subscriber.pending.fetch_add(1, Ordering::AcqRel);
+            subscriber
+                .sender
+                .send(Message::Chunk(Arc::clone(&chunk)))
+                .is_ok()
+        });
+
+        Ok(())
+    }
+
+    fn finish(&mut self, _total_len: u64) -> io::Result<()> {
+        self.close(|| Message::End);
+        Ok(())
+    }
+
+    fn abort(&mut self, err: &Error) {
+        self.close(|| Message::Abort(err.to_string()));
+    }
+}
+
+/// Reader of a body passed through `Broadcast`.
+#[derive(Debug)]
+pub struct BroadcastReader {
+    receiver: Receiver<Message>,
+    pending: Arc<AtomicUsize>,
+    chunk: Arc<[u8]>,
+    pos: usize,
+    done: bool,
+}
+
+impl Read for BroadcastReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.pos == self.chunk.len() && !self.done {
+            match self.receiver.recv() {
+                Ok(Message::Chunk(chunk)) => {
+                    self.pending.fetch_sub(1, Ordering::AcqRel);
+                    self.chunk = chunk;
+                    self.pos = 0;
+                }
+                Ok(Message::End) => self.done = true,
+                Ok(Message::Abort(err)) => return Err(io::Error::other(err)),
+                Err(_) => {
+                    return Err(io::Error::other(
+                        "broadcast disconnected before the end of the body",
+                    ))
+                }
+            }
+        }
+
+        let len = buf.len().min(self.chunk.len() - self.pos);
+        buf[..len].copy_from_slice(&self.chunk[self.pos..self.pos + len]);
+        self.pos += len;
+
+        Ok(len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn broadcast_wait() {
+        let mut broadcast = Broadcast::new(1, SlowConsumer::Wait);
+        let readers: Vec<_> = (0..3)
+            .map(|_| {
+                let mut reader = broadcast.subscribe();
+                thread::spawn(move || {
+                    let mut body = Vec::new();
+                    reader.read_to_end(&mut body).map(|_| body)
+                })
+            })
+            .collect();
+
+        for chunk in [&b"hello"[..], b" ", b"world"] {
+            broadcast.write_chunk(chunk).unwrap();
+        }
+        broadcast.finish(11).unwrap();
+        assert_eq!(broadcast.readers(), 0);
+
+        for reader in readers {
+            assert_eq!(reader.join().unwrap().unwrap(), b"hello world");
+        }
+    }
+
+    #[test]
+    fn broadcast_disconnect() {
+        let mut broadcast = Broadcast::new(1, SlowConsumer::Disconnect);
+        let mut slow = broadcast.subscribe();
+        let mut fast = broadcast.subscribe();
+        let mut buf = [0; 8];
+
+        broadcast.write_chunk(b"abc").unwrap();
+        assert_eq!(fast.read(&mut buf).unwrap(), 3);
+
+        broadcast.write_chunk(b"def").unwrap();
+        assert_eq!(broadcast.readers(), 1);
+        broadcast.finish(6).unwrap();
+
+        assert_eq!(slow.read(&mut buf).unwrap(), 3);
+        assert!(slow.read(&mut buf).is_err());
+
+        assert_eq!(fast.read(&mut buf).unwrap(), 3);
+        assert_eq!(&buf[..3], b"def");
+        assert_eq!(fast.read(&mut buf).unwrap(), 0);
+    }
+
+    #[test]
+    fn broadcast_abort() {
+        let mut broadcast = Broadcast::new(4, SlowConsumer::Wait);
+        let mut reader = broadcast.subscribe();
+        let mut body = Vec::new();
+
+        broadcast.write_chunk(b"abc").unwrap();
+        broadcast.abort(&Error::Timeout);
+
+        let err = reader.read_to_end(&mut body).unwrap_err();
+        assert_eq!(err.to_string(), Error::Timeout.to_string());
+        assert_eq!(body, b"abc");
+    }
+
+    #[test]
+    fn broadcast_reader_dropped() {
+        let mut broadcast = Broadcast::new(1, SlowConsumer::Wait);
+        drop(broadcast.subscribe());
+
+        broadcast.write_chunk(b"abc").unwrap();
+        assert_eq!(broadcast.readers(), 0);
+    }
+}
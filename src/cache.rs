@@ -0,0 +1,759 @@
+//! caching responses according to RFC 7234
+use crate::{
+    date::parse_http_date,
+    error::{Error, ParseErr},
+    response::{find_slice, split_list, FullResponse, Headers, Response, StatusCode, CR_LF_2},
+};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::HashMap,
+    fmt::{self, Write},
+    fs,
+    path::PathBuf,
+    str,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// Statuses, which can be cached without explicit freshness information (RFC 7231, section 6.1).
+const HEURISTIC_STATUSES: [u16; 10] = [200, 203, 204, 300, 301, 404, 405, 410, 414, 501];
+
+/// Upper limit of heuristic freshness, so responses without explicit expiration time
+/// are not kept for longer than a day.
+const MAX_HEURISTIC_LIFETIME: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Statuses, for which a stale response can be used instead if `stale-if-error` allows it
+/// (RFC 5861, section 4).
+const ERROR_STATUSES: [u16; 4] = [500, 502, 503, 504];
+
+/// Distinguishes temporary files of entries written concurrently by `DiskStore`.
+static TMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Storage of cached responses, keyed by URI.
+///
+/// Failures of the storage (e.g. I/O errors) should not be reported. Requests are sent
+/// to the server if there is no cached response.
+pub trait CacheStore: Send + Sync {
+    /// Returns the entry stored for `key`.
+    fn get(&self, key: &str) -> Option<CacheEntry>;
+
+    /// Stores `entry` for `key`, replacing the previous one.
+    fn put(&self, key: &str, entry: CacheEntry);
+
+    /// Removes the entry stored for `key`.
+    fn remove(&self, key: &str);
+}
+
+/// Stores cached responses in memory.
+#[derive(Debug, Default)]
+pub struct MemoryStore(Mutex<HashMap<String, CacheEntry>>);
+
+impl MemoryStore {
+    /// Creates a new, empty `MemoryStore`.
+    pub fn new() -> MemoryStore {
+        MemoryStore::default()
+    }
+
+    /// Returns the map of entries, even if another thread panicked while holding the lock.
+    fn entries(&self) -> std::sync::MutexGuard<'_, HashMap<String, CacheEntry>> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl CacheStore for MemoryStore {
+    fn get(&self, key: &str) -> Option<CacheEntry> {
+        self.entries().get(key).cloned()
+    }
+
+    fn put(&self, key: &str, entry: CacheEntry) {
+        self.entries().insert(key.to_string(), entry);
+    }
+
+    fn remove(&self, key: &str) {
+        self.entries().remove(key);
+    }
+}
+
+/// Stores cached responses in files in a directory, one file per URI
+/// (see `CacheEntry::to_bytes`).
+#[derive(Debug, PartialEq, Clone)]
+pub struct DiskStore {
+    dir: PathBuf,
+}
+
+impl DiskStore {
+    /// Creates a new `DiskStore` keeping files in `dir`. The directory is created
+    /// when the first entry is stored.
+    pub fn new<P: Into<PathBuf>>(dir: P) -> DiskStore {
+        DiskStore { dir: dir.into() }
+    }
+
+    /// Returns path of the file for `key`.
+    fn path(&self, key: &str) -> PathBuf {
+        let name =
+            Sha256::digest(key.as_bytes())[..16]
+                .iter()
+                .fold(String::new(), |mut name, b| {
+                    let _ = write!(name, "{:02x}", b);
+                    name
+                });
+
+        self.dir.join(name + ".http")
+    }
+}
+
+impl CacheStore for DiskStore {
+    fn get(&self, key: &str) -> Option<CacheEntry> {
+        CacheEntry::from_bytes(&fs::read(self.path(key)).ok()?).ok()
+    }
+
+    fn put(&self, key: &str, entry: CacheEntry) {
+        // The entry is written to a temporary file first, so readers never see it partially.
+        let path = self.path(key);
+        let id = TMP_COUNTER.fetch_add(1, Ordering::Relaxed);
+        let tmp = path.with_extension(format!("tmp{}-{}", std::process::id(), id));
+
+        let res = fs::create_dir_all(&self.dir)
+            .and_then(|_| fs::write(&tmp, entry.to_bytes()))
+            .and_then(|_| fs::rename(&tmp, &path));
+
+        if res.is_err() {
+            let _ = fs::remove_file(&tmp);
+        }
+    }
+
+    fn remove(&self, key: &str) {
+        let _ = fs::remove_file(self.path(key));
+    }
+}
+
+/// Response stored in a cache with its body and information needed to decide
+/// if it's still fresh.
+#[derive(Debug, PartialEq, Clone)]
+pub struct CacheEntry {
+    response: Response,
+    body: Vec<u8>,
+    request_time: SystemTime,
+    response_time: SystemTime,
+    vary: Headers,
+}
+
+impl CacheEntry {
+    /// Creates a new `CacheEntry` for `response` to a request with `request_headers`.
+    /// The request was sent at `request_time` and the response received at `response_time`.
+    pub fn new(
+        response: FullResponse,
+        request_headers: &Headers,
+        request_time: SystemTime,
+        response_time: SystemTime,
+    ) -> CacheEntry {
+        let (response, body) = response.into_parts();
+        let mut vary = Headers::new();
+
+        for name in vary_names(&response) {
+            if let Some(value) = request_headers.get(name) {
+                vary.insert(name, value);
+            }
+        }
+
+        CacheEntry {
+            response,
+            body,
+            request_time,
+            response_time,
+            vary,
+        }
+    }
+
+    /// Returns the cached response.
+    pub const fn response(&self) -> &Response {
+        &self.response
+    }
+
+    /// Returns body of the cached response.
+    pub fn body(&self) -> &[u8] {
+        &self.body
+    }
+
+    /// Returns how long the response is fresh for, according to `Cache-Control: max-age`,
+    /// `Expires` or, as a heuristic, 10% of the time since `Last-Modified` (up to a day).
+    pub fn freshness_lifetime(&self) -> Duration {
+        let headers = self.response.headers();
+        let directives = directives(headers);
+
+        if let Some(max_age) = directive(&directives, "max-age") {
+            return Duration::from_secs(max_age.and_then(|v| v.parse().ok()).unwrap_or(0));
+        }
+
+        if let Some(expires) = headers.get("Expires") {
+            // Invalid dates (e.g. "0") mean that the response has already expired.
+            return parse_http_date(expires)
+                .and_then(|expires| expires.duration_since(self.date()).ok())
+                .unwrap_or_default();
+        }
+
+        match headers.last_modified() {
+            Some(modified) if HEURISTIC_STATUSES.contains(&self.response.status_code().into()) => {
+                let age = self.date().duration_since(modified).unwrap_or_default();
+                (age / 10).min(MAX_HEURISTIC_LIFETIME)
+            }
+            _ => Duration::ZERO,
+        }
+    }
+
+    /// Returns age of the response at `now` (RFC 7234, section 4.2.3).
+    pub fn age(&self, now: SystemTime) -> Duration {
+        let age_value = self
+            .response
+            .headers()
+            .get("Age")
+            .and_then(|age| age.trim().parse().ok())
+            .map(Duration::from_secs)
+            .unwrap_or_default();
+
+        let apparent_age = since(self.response_time, self.date());
+        let response_delay = since(self.response_time, self.request_time);
+        let initial_age = apparent_age.max(age_value + response_delay);
+
+        initial_age + since(now, self.response_time)
+    }
+
+    /// Checks if the response can be used at `now` without revalidation.
+    pub fn is_fresh(&self, now: SystemTime) -> bool {
+        let directives = directives(self.response.headers());
+
+        directive(&directives, "no-cache").is_none() && self.age(now) < self.freshness_lifetime()
+    }
+
+    /// Checks if the response can be used at `now` when revalidation with request
+    /// headers `request_headers` fails, because `stale-if-error` directive of the response
+    /// or the request allows it to be that much stale (RFC 5861, section 4).
+    ///
+    /// Responses with `must-revalidate` directive are never used after an error.
+    pub fn is_usable_on_error(&self, request_headers: &Headers, now: SystemTime) -> bool {
+        let response_directives = directives(self.response.headers());
+        if directive(&response_directives, "must-revalidate").is_some() {
+            return false;
+        }
+
+        let staleness = self.age(now).saturating_sub(self.freshness_lifetime());
+        let limit = |directives: &[(String, Option<String>)]| {
+            directive(directives, "stale-if-error")
+                .flatten()
+                .and_then(|limit| limit.parse().ok())
+                .map(Duration::from_secs)
+        };
+
+        [
+            limit(&response_directives),
+            limit(&directives(request_headers)),
+        ]
+        .into_iter()
+        .flatten()
+        .any(|limit| staleness <= limit)
+    }
+
+    /// Serializes this entry: times of the request and the response, values of request
+    /// headers selected by `Vary`, the head of the response and its body.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut entry = format!(
+            "{} {}\r\n",
+            unix_secs(self.request_time),
+            unix_secs(self.response_time)
+        );
+
+        for (key, val) in self.vary.iter() {
+            entry = entry + key + ": " + val + "\r\n";
+        }
+
+        entry = format!("{}\r\n{}\r\n", entry, self.response.status());
+
+        for (key, val) in self.response.headers().iter() {
+            entry = entry + key + ": " + val + "\r\n";
+        }
+
+        let mut entry = (entry + "\r\n").into_bytes();
+        entry.extend(&self.body);
+        entry
+    }
+
+    /// Parses an entry serialized with `to_bytes`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<CacheEntry, Error> {
+        let meta_end = find_slice(bytes, &CR_LF_2).ok_or(ParseErr::Invalid)?;
+        let (meta, rest) = (str::from_utf8(&bytes[..meta_end])?, &bytes[meta_end..]);
+        let mut lines = meta.lines();
+
+        let (request_time, response_time) = lines
+            .next()
+            .and_then(|times| times.split_once(' '))
+            .ok_or(ParseErr::Invalid)?;
+
+        let mut vary = Headers::new();
+        for line in lines.filter(|line| !line.is_empty()) {
+            let (key, val) = line.split_once(':').ok_or(ParseErr::HeadersErr)?;
+            vary.insert(key.trim(), val.trim());
+        }
+
+        let head_end = find_slice(rest, &CR_LF_2).ok_or(ParseErr::Invalid)?;
+        let response = Response::from_head(&rest[..head_end])?;
+
+        Ok(CacheEntry {
+            response,
+            body: rest[head_end..].to_vec(),
+            request_time: UNIX_EPOCH
+                + Duration::from_secs(request_time.parse().map_err(ParseErr::from)?),
+            response_time: UNIX_EPOCH
+                + Duration::from_secs(response_time.parse().map_err(ParseErr::from)?),
+            vary,
+        })
+    }
+
+    /// Returns the date of the response or, if it's missing, the time it was received.
+    fn date(&self) -> SystemTime {
+        self.response
+            .headers()
+            .get("Date")
+            .and_then(|date| parse_http_date(date))
+            .unwrap_or(self.response_time)
+    }
+
+    /// Checks if request headers selected by `Vary` match those of the cached request.
+    fn matches(&self, request_headers: &Headers) -> bool {
+        vary_names(&self.response).all(|name| self.vary.get(name) == request_headers.get(name))
+    }
+}
+
+/// Outcome of looking up a request in a `Cache`.
+pub(crate) enum Lookup {
+    /// The cached response can be used without contacting the server.
+    Fresh(CacheEntry),
+    /// The cached response has to be revalidated with the server.
+    Stale(CacheEntry),
+    Miss,
+}
+
+/// Private HTTP cache for GET requests (RFC 7234), which keeps responses in a `CacheStore`.
+///
+/// Fresh responses are served from the store without contacting the server. Stale ones
+/// are revalidated with `If-None-Match` and `If-Modified-Since` headers; if the server
+/// responds with `304 Not Modified`, the cached body is used. If revalidation fails, or the
+/// server responds with 500, 502, 503 or 504 status, the stale response is used instead
+/// as long as `stale-if-error` directive allows it. `Cache-Control` (including `no-store`,
+/// `no-cache` and `must-revalidate`), `Expires`, `ETag`, `Last-Modified` and `Vary` headers
+/// are taken into account.
+///
+/// `stale-while-revalidate` isn't supported, as there's no client that could refresh
+/// responses in the background; stale responses are always revalidated before being used.
+///
+/// # Examples
+/// ```
+/// use http_req::{cache::Cache, request::Request, uri::Uri};
+/// use std::convert::TryFrom;
+///
+/// let uri = Uri::try_from("https://www.rust-lang.org/learn").unwrap();
+/// let cache = Cache::on_disk(std::env::temp_dir().join("http_req-cache"));
+///
+/// let request = Request::new(&uri)
+///     .cache(&cache);
+/// ```
+pub struct Cache {
+    store: Box<dyn CacheStore>,
+}
+
+impl Cache {
+    /// Creates a new `Cache` keeping responses in `store`.
+    pub fn new<S: CacheStore + 'static>(store: S) -> Cache {
+        Cache {
+            store: Box::new(store),
+        }
+    }
+
+    /// Creates a new `Cache` keeping responses in memory.
+    pub fn in_memory() -> Cache {
+        Cache::new(MemoryStore::new())
+    }
+
+    /// Creates a new `Cache` keeping responses in files in `dir`.
+    pub fn on_disk<P: Into<PathBuf>>(dir: P) -> Cache {
+        Cache::new(DiskStore::new(dir))
+    }
+
+    /// Returns the store of this `Cache`.
+    pub fn store(&self) -> &dyn CacheStore {
+        self.store.as_ref()
+    }
+
+    /// Looks up the response for a request to `key` with `request_headers` at `now`.
+    pub(crate) fn lookup(&self, key: &str, request_headers: &Headers, now: SystemTime) -> Lookup {
+        let entry = match self.store.get(key) {
+            Some(entry) if entry.matches(request_headers) => entry,
+            _ => return Lookup::Miss,
+        };
+
+        let directives = directives(request_headers);
+        let max_age = directive(&directives, "max-age")
+            .flatten()
+            .and_then(|max_age| max_age.parse().ok())
+            .map(Duration::from_secs);
+        let no_cache = directive(&directives, "no-cache").is_some()
+            || request_headers
+                .get("Pragma")
+                .is_some_and(|pragma| pragma.contains("no-cache"));
+
+        if !no_cache && entry.is_fresh(now) && max_age.is_none_or(|max| entry.age(now) <= max) {
+            Lookup::Fresh(entry)
+        } else {
+            Lookup::Stale(entry)
+        }
+    }
+
+    /// Handles `response` received for a request to `key` and returns the response,
+    /// which should be passed to the caller. Stores the response if it's cacheable,
+    /// or refreshes `stale` entry if the server responded with `304 Not Modified`.
+    pub(crate) fn update(
+        &self,
+        key: &str,
+        request_headers: &Headers,
+        stale: Option<CacheEntry>,
+        response: FullResponse,
+        request_time: SystemTime,
+        response_time: SystemTime,
+    ) -> FullResponse {
+        let response = match stale {
+            Some(stale) if response.status_code() == StatusCode::new(304) => {
+                let (mut cached, body) = (stale.response, stale.body);
+
+                for (key, val) in response.headers().iter() {
+                    if !["Content-Length", "Transfer-Encoding"]
+                        .iter()
+                        .any(|h| key.eq_ignore_ascii_case(h))
+                    {
+                        cached.headers_mut().insert(key.as_str(), val);
+                    }
+                }
+
+                cached.with_body(body)
+            }
+            _ => response,
+        };
+
+        if is_storable(response.response(), request_headers) {
+            let entry = CacheEntry::new(
+                response.clone(),
+                request_headers,
+                request_time,
+                response_time,
+            );
+            self.store.put(key, entry);
+        } else if response.status_code() != StatusCode::new(304) {
+            self.store.remove(key);
+        }
+
+        response
+    }
+}
+
+impl fmt::Debug for Cache {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Cache").finish_non_exhaustive()
+    }
+}
+
+/// Checks if the request with `request_headers` must not use the cache at all.
+pub(crate) fn is_no_store(request_headers: &Headers) -> bool {
+    directive(&directives(request_headers), "no-store").is_some()
+}
+
+/// Checks if the body of `response` to a request with `request_headers` is needed by the
+/// cache, because the response is going to be stored, it revalidates a stored one or it
+/// may be replaced with a stored one (see `is_error`).
+pub(crate) fn needs_body(response: &Response, request_headers: &Headers) -> bool {
+    response.status_code() == StatusCode::new(304)
+        || is_error(response)
+        || is_storable(response, request_headers)
+}
+
+/// Checks if `response` is an error, which a stale response can be used instead of.
+pub(crate) fn is_error(response: &Response) -> bool {
+    ERROR_STATUSES.contains(&response.status_code().into())
+}
+
+/// Checks if `response` to a request with `request_headers` can be stored.
+fn is_storable(response: &Response, request_headers: &Headers) -> bool {
+    let headers = response.headers();
+    let directives = directives(headers);
+    let code = u16::from(response.status_code());
+
+    let explicit = directive(&directives, "max-age").is_some() || headers.get("Expires").is_some();
+    let validated = headers.etag().is_some() || headers.last_modified().is_some();
+
+    !is_no_store(request_headers)
+        && directive(&directives, "no-store").is_none()
+        && !vary_names(response).any(|name| name == "*")
+        && code != 206
+        && code != 304
+        && (explicit || (validated && HEURISTIC_STATUSES.contains(&code)))
+}
+
+/// Returns names of request headers listed in `Vary` header of `response`.
+fn vary_names(response: &Response) -> impl Iterator<Item = &str> {
+    response
+        .headers()
+        .get("Vary")
+        .map(|vary| vary.split(',').map(str::trim).filter(|n| !n.is_empty()))
+        .into_iter()
+        .flatten()
+}
+
+/// Returns directives of `Cache-Control` header, with lowercase names and unquoted values.
+fn directives(headers: &Headers) -> Vec<(String, Option<String>)> {
+    let value = headers.get("Cache-Control").map_or("", String::as_str);
+
+    split_list(value)
+        .into_iter()
+        .map(|directive| match directive.split_once('=') {
+            Some((name, val)) => (
+                name.trim().to_ascii_lowercase(),
+                Some(val.trim().trim_matches('"').to_string()),
+            ),
+            None => (directive.to_ascii_lowercase(), None),
+        })
+        .collect()
+}
+
+/// Returns value of directive `name`: `None` if it's missing, `Some(None)` if it
+/// has no value.
+fn directive<'d>(
+    directives: &'d [(String, Option<String>)],
+    name: &str,
+) -> Option<Option<&'d str>> {
+    directives
+        .iter()
+        .find(|(key, _)| key == name)
+        .map(|(_, val)| val.as_deref())
+}
+
+/// Returns time elapsed between `earlier` and `later`, or zero if `later` is earlier.
+fn since(later: SystemTime, earlier: SystemTime) -> Duration {
+    later.duration_since(earlier).unwrap_or_default()
+}
+
+/// Returns number of seconds since UNIX epoch.
+fn unix_secs(time: SystemTime) -> u64 {
+    since(time, UNIX_EPOCH).as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TIME: u64 = 1_000_000_000;
+
+    fn cached(head: &str, request_headers: &Headers) -> CacheEntry {
+        let response = Response::from_head(head.as_bytes()).unwrap();
+        let time = UNIX_EPOCH + Duration::from_secs(TIME);
+
+        CacheEntry::new(
+            response.with_body(b"body".to_vec()),
+            request_headers,
+            time,
+            time,
+        )
+    }
+
+    fn at(secs: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(TIME + secs)
+    }
+
+    #[test]
+    fn cache_entry_freshness() {
+        let headers = Headers::new();
+        let date = crate::date::fmt_http_date(at(0));
+
+        let entry = cached(
+            "HTTP/1.1 200 OK\r\nCache-Control: max-age=60\r\n\r\n",
+            &headers,
+        );
+        assert_eq!(entry.freshness_lifetime(), Duration::from_secs(60));
+        assert!(entry.is_fresh(at(59)));
+        assert!(!entry.is_fresh(at(60)));
+
+        let head = format!(
+            "HTTP/1.1 200 OK\r\nDate: {}\r\nExpires: {}\r\nAge: 10\r\n\r\n",
+            date,
+            crate::date::fmt_http_date(at(100))
+        );
+        let entry = cached(&head, &headers);
+        assert_eq!(entry.freshness_lifetime(), Duration::from_secs(100));
+        assert_eq!(entry.age(at(5)), Duration::from_secs(15));
+
+        let head = format!("HTTP/1.1 200 OK\r\nDate: {}\r\nExpires: 0\r\n\r\n", date);
+        assert_eq!(cached(&head, &headers).freshness_lifetime(), Duration::ZERO);
+
+        let head = format!(
+            "HTTP/1.1 200 OK\r\nDate: {}\r\nLast-Modified: {}\r\n\r\n",
+            date,
+            crate::date::fmt_http_date(UNIX_EPOCH + Duration::from_secs(TIME - 1000))
+        );
+        assert_eq!(
+            cached(&head, &headers).freshness_lifetime(),
+            Duration::from_secs(100)
+        );
+
+        let head = "HTTP/1.1 200 OK\r\nCache-Control: max-age=60, no-cache\r\n\r\n";
+        assert!(!cached(head, &headers).is_fresh(at(0)));
+    }
+
+    #[test]
+    fn cache_entry_usable_on_error() {
+        let headers = Headers::new();
+
+        let entry = cached(
+            "HTTP/1.1 200 OK\r\nCache-Control: max-age=60, stale-if-error=30\r\n\r\n",
+            &headers,
+        );
+        assert!(entry.is_usable_on_error(&headers, at(90)));
+        assert!(!entry.is_usable_on_error(&headers, at(91)));
+
+        let entry = cached(
+            "HTTP/1.1 200 OK\r\nCache-Control: max-age=60\r\n\r\n",
+            &headers,
+        );
+        assert!(!entry.is_usable_on_error(&headers, at(61)));
+
+        let mut request_headers = Headers::new();
+        request_headers.insert("Cache-Control", "stale-if-error=10");
+        assert!(entry.is_usable_on_error(&request_headers, at(61)));
+        assert!(!entry.is_usable_on_error(&request_headers, at(71)));
+
+        let entry = cached(
+            "HTTP/1.1 200 OK\r\nCache-Control: max-age=60, must-revalidate, stale-if-error=30\r\n\r\n",
+            &headers,
+        );
+        assert!(!entry.is_usable_on_error(&headers, at(61)));
+    }
+
+    #[test]
+    fn cache_entry_bytes() {
+        let mut headers = Headers::new();
+        headers.insert("Accept-Language", "pl");
+
+        let entry = cached(
+            "HTTP/1.1 200 OK\r\nVary: Accept-Language, Accept\r\nETag: \"x\"\r\n\r\n",
+            &headers,
+        );
+        assert_eq!(CacheEntry::from_bytes(&entry.to_bytes()).unwrap(), entry);
+        assert!(CacheEntry::from_bytes(b"invalid").is_err());
+
+        assert!(entry.matches(&headers));
+        assert!(!entry.matches(&Headers::new()));
+
+        headers.insert("Accept", "text/html");
+        assert!(!entry.matches(&headers));
+    }
+
+    #[test]
+    fn cache_lookup_update() {
+        let cache = Cache::in_memory();
+        let headers = Headers::new();
+        let time = at(0);
+
+        let response = |head: &str| {
+            Response::from_head(head.as_bytes())
+                .unwrap()
+                .with_body(b"body".to_vec())
+        };
+
+        assert!(matches!(cache.lookup("a", &headers, time), Lookup::Miss));
+
+        // Not cacheable: no freshness information nor validators.
+        cache.update(
+            "a",
+            &headers,
+            None,
+            response("HTTP/1.1 200 OK\r\n\r\n"),
+            time,
+            time,
+        );
+        assert!(matches!(cache.lookup("a", &headers, time), Lookup::Miss));
+
+        let head = "HTTP/1.1 200 OK\r\nCache-Control: max-age=10\r\nETag: \"1\"\r\n\r\n";
+        cache.update("a", &headers, None, response(head), time, time);
+        assert!(matches!(
+            cache.lookup("a", &headers, at(5)),
+            Lookup::Fresh(_)
+        ));
+        assert!(matches!(
+            cache.lookup("a", &headers, at(10)),
+            Lookup::Stale(_)
+        ));
+
+        let mut no_cache = Headers::new();
+        no_cache.insert("Cache-Control", "no-cache");
+        assert!(matches!(
+            cache.lookup("a", &no_cache, at(5)),
+            Lookup::Stale(_)
+        ));
+
+        let mut max_age = Headers::new();
+        max_age.insert("Cache-Control", "max-age=2");
+        assert!(matches!(
+            cache.lookup("a", &max_age, at(5)),
+            Lookup::Stale(_)
+        ));
+
+        let stale = match cache.lookup("a", &headers, at(10)) {
+            Lookup::Stale(entry) => entry,
+            _ => unreachable!(),
+        };
+        let head = "HTTP/1.1 304 Not Modified\r\nCache-Control: max-age=20\r\n\r\n";
+        let updated = cache.update("a", &headers, Some(stale), response(head), at(10), at(10));
+
+        assert_eq!(updated.status_code(), StatusCode::new(200));
+        assert_eq!(
+            updated.headers().get("Cache-Control"),
+            Some(&"max-age=20".to_string())
+        );
+        assert!(matches!(
+            cache.lookup("a", &headers, at(25)),
+            Lookup::Fresh(_)
+        ));
+
+        let head = "HTTP/1.1 200 OK\r\nCache-Control: no-store\r\n\r\n";
+        cache.update("a", &headers, None, response(head), time, time);
+        assert!(matches!(cache.lookup("a", &headers, time), Lookup::Miss));
+
+        no_cache.insert("Cache-Control", "no-store");
+        assert!(is_no_store(&no_cache));
+    }
+
+    #[test]
+    fn disk_store() {
+        let dir = std::env::temp_dir().join(format!("http_req-cache-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let store = DiskStore::new(&dir);
+
+        let entry = cached("HTTP/1.1 200 OK\r\nETag: \"x\"\r\n\r\n", &Headers::new());
+        assert_eq!(store.get("http://a/"), None);
+
+        store.put("http://a/", entry.clone());
+        assert_eq!(store.get("http://a/"), Some(entry.clone()));
+        assert_eq!(store.get("http://b/"), None);
+
+        store.remove("http://a/");
+        assert_eq!(store.get("http://a/"), None);
+
+        // Concurrent writes of the same key don't share a temporary file.
+        std::thread::scope(|scope| {
+            for _ in 0..8 {
+                scope.spawn(|| store.put("http://a/", entry.clone()));
+            }
+        });
+        assert_eq!(store.get("http://a/"), Some(entry));
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,177 @@
+//! cancelling requests from other threads
+use std::{
+    io,
+    net::{Shutdown, TcpStream},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        mpsc::Sender,
+        Arc, Mutex,
+    },
+};
+
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+
+/// Handle used to interrupt a blocking operation.
+#[derive(Debug)]
+pub(crate) enum Handle {
+    /// Wakes up a thread waiting for connection attempts.
+    Waker(Sender<io::Result<TcpStream>>),
+    /// Shuts down a connection, so pending reads and writes return immediately.
+    Tcp(TcpStream),
+    #[cfg(unix)]
+    Unix(UnixStream),
+}
+
+impl Handle {
+    fn interrupt(&self) {
+        match self {
+            Handle::Waker(sender) => sender.send(Err(cancelled())).unwrap_or(()),
+            Handle::Tcp(stream) => stream.shutdown(Shutdown::Both).unwrap_or(()),
+            #[cfg(unix)]
+            Handle::Unix(stream) => stream.shutdown(Shutdown::Both).unwrap_or(()),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct State {
+    cancelled: AtomicBool,
+    next_id: AtomicUsize,
+    handles: Mutex<Vec<(usize, Handle)>>,
+}
+
+/// Token, which allows to cancel requests from another thread.
+///
+/// Cancelling interrupts a pending connection attempt or a blocked read immediately,
+/// instead of waiting for timeouts of the OS. Requests using a cancelled token fail
+/// with `Error::Cancelled`. Name resolution can't be interrupted, so a request cancelled
+/// while resolving the host fails once the resolution completes.
+///
+/// # Examples
+/// ```
+/// use http_req::{cancel::CancelToken, request::Request, uri::Uri};
+/// use std::{convert::TryFrom, thread, time::Duration};
+///
+/// let uri = Uri::try_from("https://www.rust-lang.org/learn").unwrap();
+/// let token = CancelToken::new();
+///
+/// let canceller = token.clone();
+/// thread::spawn(move || {
+///     thread::sleep(Duration::from_millis(100));
+///     canceller.cancel();
+/// });
+///
+/// let request = Request::new(&uri)
+///     .cancel_token(&token);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<State>);
+
+impl CancelToken {
+    /// Creates a new, not cancelled token.
+    pub fn new() -> CancelToken {
+        CancelToken::default()
+    }
+
+    /// Cancels all requests using this token, now and in the future.
+    pub fn cancel(&self) {
+        self.0.cancelled.store(true, Ordering::Release);
+
+        for (_, handle) in self.handles().iter() {
+            handle.interrupt();
+        }
+    }
+
+    /// Checks if the token has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.cancelled.load(Ordering::Acquire)
+    }
+
+    /// Registers `handle` to be interrupted on cancellation, until the returned
+    /// `Registration` is dropped. If the token is already cancelled, `handle` is
+    /// interrupted immediately.
+    pub(crate) fn register(&self, handle: Handle) -> Registration<'_> {
+        let id = self.0.next_id.fetch_add(1, Ordering::Relaxed);
+        let mut handles = self.handles();
+
+        if self.is_cancelled() {
+            handle.interrupt();
+        }
+
+        handles.push((id, handle));
+        Registration { token: self, id }
+    }
+
+    fn handles(&self) -> std::sync::MutexGuard<'_, Vec<(usize, Handle)>> {
+        self.0.handles.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Keeps a `Handle` registered in a `CancelToken`.
+#[derive(Debug)]
+pub(crate) struct Registration<'t> {
+    token: &'t CancelToken,
+    id: usize,
+}
+
+impl Drop for Registration<'_> {
+    fn drop(&mut self) {
+        self.token.handles().retain(|(id, _)| *id != self.id);
+    }
+}
+
+/// Returns error reported by interrupted operations.
+pub(crate) fn cancelled() -> io::Error {
+    io::Error::new(io::ErrorKind::Interrupted, "Request cancelled")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{io::Read, net::TcpListener, sync::mpsc, thread};
+
+    #[test]
+    fn cancel_token_waker() {
+        let token = CancelToken::new();
+        let (sender, receiver) = mpsc::channel();
+
+        let registration = token.register(Handle::Waker(sender));
+        assert_eq!(token.handles().len(), 1);
+        token.cancel();
+
+        assert!(token.is_cancelled());
+        assert!(receiver.recv().unwrap().is_err());
+
+        drop(registration);
+        assert!(token.handles().is_empty());
+    }
+
+    #[test]
+    fn cancel_token_tcp() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let _server = listener.accept().unwrap();
+
+        let token = CancelToken::new();
+        let _registration = token.register(Handle::Tcp(stream.try_clone().unwrap()));
+
+        let canceller = token.clone();
+        thread::spawn(move || canceller.cancel());
+
+        // Blocks until the stream is shut down.
+        assert_eq!(stream.read(&mut [0; 8]).unwrap(), 0);
+    }
+
+    #[test]
+    fn cancel_token_cancelled() {
+        let token = CancelToken::new();
+        token.cancel();
+
+        let (sender, receiver) = mpsc::channel();
+        let _registration = token.register(Handle::Waker(sender));
+        assert!(receiver.try_recv().unwrap().is_err());
+        assert!(token.clone().is_cancelled());
+        assert!(!CancelToken::new().is_cancelled());
+    }
+}
@@ -1,6 +1,14 @@
 //! support for Transfer-Encoding: chunked
-use crate::CR_LF;
-use std::io::{self, BufRead, BufReader, Error, ErrorKind, Read};
+use crate::{response::Headers, CR_LF};
+use std::io::{self, BufRead, BufReader, Error, Read};
+#[cfg(feature = "tokio")]
+use std::{
+    mem,
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+#[cfg(feature = "tokio")]
+use tokio::io::{AsyncBufRead, AsyncRead, BufReader as AsyncBufReader, ReadBuf};
 
 const MAX_LINE_LENGTH: usize = 4096;
 
@@ -13,6 +21,7 @@ pub struct ChunkReader<R> {
     err: Option<Error>,
     n: usize,
     reader: BufReader<R>,
+    trailers: Headers,
 }
 
 impl<R> Read for ChunkReader<R>
@@ -33,11 +42,9 @@ where
                     break;
                 }
 
-                if let Ok(_) = self.reader.read_exact(&mut footer) {
-                    if &footer != CR_LF {
-                        self.err = Some(error_malformed_chunked_encoding());
-                        break;
-                    }
+                if self.reader.read_exact(&mut footer).is_ok() && &footer != CR_LF {
+                    self.err = Some(error_malformed_chunked_encoding());
+                    break;
                 }
 
                 self.check_end = false;
@@ -82,10 +89,7 @@ where
         }
 
         match self.err.as_ref() {
-            Some(v) => Err(Error::new(
-                v.kind(),
-                format!("wrapper by chunked: {}", v.to_string()),
-            )),
+            Some(v) => Err(Error::new(v.kind(), format!("wrapper by chunked: {}", v))),
             None => Ok(consumed),
         }
     }
@@ -115,6 +119,7 @@ where
             err: None,
             n: 0,
             reader: value,
+            trailers: Headers::new(),
         }
     }
 }
@@ -134,9 +139,29 @@ where
             err: None,
             n: 0,
             reader: BufReader::new(reader),
+            trailers: Headers::new(),
         }
     }
 
+    /// Returns trailer headers sent after the last chunk.
+    /// They are available once the whole body has been read.
+    ///
+    /// # Examples
+    /// ```
+    /// use http_req::chunked::ChunkReader;
+    /// use std::io::Read;
+    ///
+    /// let data: &[u8] = b"3\r\nfoo\r\n0\r\nDigest: sha-256=abc\r\n\r\n";
+    /// let mut reader = ChunkReader::new(data);
+    /// let mut body = Vec::new();
+    /// reader.read_to_end(&mut body).unwrap();
+    ///
+    /// assert_eq!(reader.trailers().get("Digest"), Some(&"sha-256=abc".to_string()));
+    /// ```
+    pub fn trailers(&self) -> &Headers {
+        &self.trailers
+    }
+
     fn begin_chunk(&mut self) {
         // chunk-size CRLF
         let line = match read_chunk_line(&mut self.reader) {
@@ -149,10 +174,49 @@ where
 
         match parse_hex_uint(line) {
             Ok(v) => self.n = v,
-            Err(err) => self.err = Some(Error::new(ErrorKind::Other, err)),
+            Err(err) => self.err = Some(Error::other(err)),
         }
 
         self.eof = self.n == 0;
+
+        if self.eof && self.err.is_none() {
+            self.read_trailers();
+        }
+    }
+
+    fn read_trailers(&mut self) {
+        // trailer-section CRLF
+        let mut trailers = String::new();
+
+        loop {
+            let line = match read_trailer_line(&mut self.reader) {
+                Ok(v) => v,
+                Err(err) => {
+                    self.err = Some(err);
+                    return;
+                }
+            };
+
+            if line.is_empty() {
+                break;
+            }
+
+            match std::str::from_utf8(&line) {
+                Ok(v) => {
+                    trailers.push_str(v);
+                    trailers.push('\n');
+                }
+                Err(_) => {
+                    self.err = Some(error_malformed_chunked_encoding());
+                    return;
+                }
+            }
+        }
+
+        match trailers.parse() {
+            Ok(v) => self.trailers = v,
+            Err(_) => self.err = Some(error_malformed_chunked_encoding()),
+        }
     }
 
     fn chunk_header_avaliable(&self) -> bool {
@@ -160,19 +224,224 @@ where
     }
 }
 
+/// Asynchronous version of `ChunkReader` for Tokio runtime.
+///
+/// Decodes the body without blocking the executor. Like `ChunkReader`, it ends the body
+/// at EOF of `reader`, even if the last chunk is incomplete.
+#[cfg(feature = "tokio")]
+#[derive(Debug)]
+pub struct AsyncChunkReader<R> {
+    state: State,
+    line: Vec<u8>,
+    trailer_lines: String,
+    reader: AsyncBufReader<R>,
+    trailers: Headers,
+}
+
+/// Part of chunked body that `AsyncChunkReader` expects next.
+#[cfg(feature = "tokio")]
+#[derive(Debug, Clone, Copy)]
+enum State {
+    Size,
+    Data(usize),
+    DataEnd,
+    Trailers,
+    Done,
+}
+
+#[cfg(feature = "tokio")]
+impl<R> AsyncChunkReader<R>
+where
+    R: AsyncRead + Unpin,
+{
+    /// Creates a new `AsyncChunkReader` from `reader`
+    pub fn new(reader: R) -> Self {
+        Self::from(AsyncBufReader::new(reader))
+    }
+
+    /// Returns trailer headers sent after the last chunk.
+    /// They are available once the whole body has been read.
+    pub fn trailers(&self) -> &Headers {
+        &self.trailers
+    }
+
+    /// Reads the rest of the current line into `self.line`, up to `MAX_LINE_LENGTH` bytes.
+    /// Stops at EOF, even if the line is incomplete.
+    fn poll_line(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        loop {
+            let buf = ready!(Pin::new(&mut self.reader).poll_fill_buf(cx))?;
+
+            if buf.is_empty() {
+                return Poll::Ready(Ok(()));
+            }
+
+            let (len, done) = match buf.iter().position(|&c| c == b'\n') {
+                Some(i) => (i + 1, true),
+                None => (buf.len(), false),
+            };
+
+            self.line.extend_from_slice(&buf[..len]);
+            Pin::new(&mut self.reader).consume(len);
+
+            if self.line.len() > MAX_LINE_LENGTH {
+                return Poll::Ready(Err(error_line_too_long()));
+            }
+
+            if done {
+                return Poll::Ready(Ok(()));
+            }
+        }
+    }
+
+    /// Reads the CRLF following data of a chunk.
+    fn poll_data_end(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        while self.line.len() < 2 {
+            let buf = ready!(Pin::new(&mut self.reader).poll_fill_buf(cx))?;
+
+            if buf.is_empty() {
+                break;
+            }
+
+            let len = buf.len().min(2 - self.line.len());
+            self.line.extend_from_slice(&buf[..len]);
+            Pin::new(&mut self.reader).consume(len);
+        }
+
+        let line = mem::take(&mut self.line);
+
+        match line.len() == 2 && line != CR_LF {
+            true => Poll::Ready(Err(error_malformed_chunked_encoding())),
+            false => Poll::Ready(Ok(())),
+        }
+    }
+
+    /// Parses the line with size of the next chunk.
+    fn begin_chunk(&mut self) -> io::Result<()> {
+        let mut line = mem::take(&mut self.line);
+        trim_trailing_whitespace(&mut line);
+        remove_chunk_extension(&mut line);
+
+        self.state = match parse_hex_uint(line).map_err(Error::other)? {
+            0 => State::Trailers,
+            n => State::Data(n),
+        };
+
+        Ok(())
+    }
+
+    /// Adds the line of trailer section, parsing the trailers once it's complete.
+    fn add_trailer_line(&mut self) -> io::Result<()> {
+        let mut line = mem::take(&mut self.line);
+        trim_trailer_line(&mut line);
+
+        if line.is_empty() {
+            self.trailers = mem::take(&mut self.trailer_lines)
+                .parse()
+                .map_err(|_| error_malformed_chunked_encoding())?;
+            self.state = State::Done;
+
+            return Ok(());
+        }
+
+        match std::str::from_utf8(&line) {
+            Ok(v) => {
+                self.trailer_lines.push_str(v);
+                self.trailer_lines.push('\n');
+                Ok(())
+            }
+            Err(_) => Err(error_malformed_chunked_encoding()),
+        }
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<R> From<AsyncBufReader<R>> for AsyncChunkReader<R>
+where
+    R: AsyncRead + Unpin,
+{
+    fn from(value: AsyncBufReader<R>) -> Self {
+        AsyncChunkReader {
+            state: State::Size,
+            line: Vec::new(),
+            trailer_lines: String::new(),
+            reader: value,
+            trailers: Headers::new(),
+        }
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<R> AsyncBufRead for AsyncChunkReader<R>
+where
+    R: AsyncRead + Unpin,
+{
+    fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
+        let this = self.get_mut();
+
+        let n = loop {
+            match this.state {
+                State::Size => {
+                    ready!(this.poll_line(cx))?;
+                    this.begin_chunk()?;
+                }
+                State::Data(0) => this.state = State::DataEnd,
+                State::Data(n) => break n,
+                State::DataEnd => {
+                    ready!(this.poll_data_end(cx))?;
+                    this.state = State::Size;
+                }
+                State::Trailers => {
+                    ready!(this.poll_line(cx))?;
+                    this.add_trailer_line()?;
+                }
+                State::Done => return Poll::Ready(Ok(&[])),
+            }
+        };
+
+        let buf = ready!(Pin::new(&mut this.reader).poll_fill_buf(cx))?;
+        Poll::Ready(Ok(&buf[..buf.len().min(n)]))
+    }
+
+    fn consume(self: Pin<&mut Self>, amt: usize) {
+        let this = self.get_mut();
+
+        if let State::Data(n) = &mut this.state {
+            *n -= amt;
+        }
+
+        Pin::new(&mut this.reader).consume(amt)
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<R> AsyncRead for AsyncChunkReader<R>
+where
+    R: AsyncRead + Unpin,
+{
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let data = ready!(self.as_mut().poll_fill_buf(cx))?;
+        let len = data.len().min(buf.remaining());
+        buf.put_slice(&data[..len]);
+        self.consume(len);
+
+        Poll::Ready(Ok(()))
+    }
+}
+
 fn error_line_too_long() -> Error {
-    Error::new(ErrorKind::Other, "header line too long")
+    Error::other("header line too long")
 }
 
 fn error_malformed_chunked_encoding() -> Error {
-    Error::new(ErrorKind::Other, "malformed chunked encoding")
+    Error::other("malformed chunked encoding")
 }
 
 fn is_ascii_space(b: u8) -> bool {
-    match b {
-        b' ' | b'\t' | b'\n' | b'\r' => true,
-        _ => false,
-    }
+    matches!(b, b' ' | b'\t' | b'\n' | b'\r')
 }
 
 fn parse_hex_uint<'a>(data: Vec<u8>) -> Result<usize, &'a str> {
@@ -197,6 +466,34 @@ fn parse_hex_uint<'a>(data: Vec<u8>) -> Result<usize, &'a str> {
 }
 
 fn read_chunk_line<R>(b: &mut BufReader<R>) -> io::Result<Vec<u8>>
+where
+    R: Read,
+{
+    let mut line = read_line(b)?;
+
+    trim_trailing_whitespace(&mut line);
+    remove_chunk_extension(&mut line);
+
+    Ok(line)
+}
+
+fn read_trailer_line<R>(b: &mut BufReader<R>) -> io::Result<Vec<u8>>
+where
+    R: Read,
+{
+    let mut line = read_line(b)?;
+    trim_trailer_line(&mut line);
+
+    Ok(line)
+}
+
+fn trim_trailer_line(v: &mut Vec<u8>) {
+    while v.last().is_some_and(|&c| is_ascii_space(c)) {
+        v.pop();
+    }
+}
+
+fn read_line<R>(b: &mut BufReader<R>) -> io::Result<Vec<u8>>
 where
     R: Read,
 {
@@ -207,9 +504,6 @@ where
         return Err(error_line_too_long());
     }
 
-    trim_trailing_whitespace(&mut line);
-    remove_chunk_extension(&mut line);
-
     Ok(line)
 }
 
@@ -220,7 +514,7 @@ fn remove_chunk_extension(v: &mut Vec<u8>) {
 }
 
 fn trim_trailing_whitespace(v: &mut Vec<u8>) {
-    if v.len() == 0 {
+    if v.is_empty() {
         return;
     }
 
@@ -293,4 +587,73 @@ mod tests {
         reader.read_to_end(&mut writer).expect("failed to dechunk");
         assert_eq!("hello, world! 0123456789abcdef".as_bytes(), &writer[..]);
     }
+    #[test]
+    fn read_trailers() {
+        {
+            let data: &[u8] = b"3\r\nfoo\r\n0\r\n\
+                                Grpc-Status: 0\r\n\
+                                Grpc-Message: ok; done\r\n\r\n";
+            let mut reader = ChunkReader::new(data);
+            let mut writer = vec![];
+            reader.read_to_end(&mut writer).expect("failed to dechunk");
+
+            assert_eq!("foo".as_bytes(), &writer[..]);
+            assert_eq!(reader.trailers().get("grpc-status"), Some(&"0".to_string()));
+            assert_eq!(
+                reader.trailers().get("Grpc-Message"),
+                Some(&"ok; done".to_string())
+            );
+        }
+        {
+            let data: &[u8] = b"3\r\nfoo\r\n0\r\n\r\n";
+            let mut reader = ChunkReader::new(data);
+            let mut writer = vec![];
+            reader.read_to_end(&mut writer).expect("failed to dechunk");
+
+            assert_eq!(reader.trailers(), &Headers::new());
+        }
+        {
+            let data: &[u8] = b"3\r\nfoo\r\n0\r\ninvalid trailer\r\n\r\n";
+            let mut reader = ChunkReader::new(data);
+            let mut writer = vec![];
+
+            assert!(reader.read_to_end(&mut writer).is_err());
+        }
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn async_read() {
+        use tokio::io::AsyncReadExt;
+
+        let data: &[u8] = b"7\r\nhello, \r\n17;ext\r\nworld! 0123456789abcdef\r\n0\r\n\
+                            Grpc-Status: 0\r\n\r\n";
+        let mut reader = AsyncChunkReader::new(data);
+        let mut writer = vec![];
+        reader.read_to_end(&mut writer).await.unwrap();
+
+        assert_eq!("hello, world! 0123456789abcdef".as_bytes(), &writer[..]);
+        assert_eq!(reader.trailers().get("grpc-status"), Some(&"0".to_string()));
+
+        // Buffer of a single byte splits every line and chunk.
+        let mut reader = AsyncChunkReader::from(AsyncBufReader::with_capacity(1, data));
+        let mut writer = vec![];
+        reader.read_to_end(&mut writer).await.unwrap();
+
+        assert_eq!("hello, world! 0123456789abcdef".as_bytes(), &writer[..]);
+        assert_eq!(reader.trailers().get("Grpc-Status"), Some(&"0".to_string()));
+
+        let data: &[u8] = b"7\r\n1234567";
+        let mut reader = AsyncChunkReader::new(data);
+        let mut writer = vec![];
+        reader.read_to_end(&mut writer).await.unwrap();
+
+        assert_eq!("1234567".as_bytes(), &writer[..]);
+
+        let data: &[u8] = b"3\r\nfooXX3\r\nbar\r\n0\r\n\r\n";
+        let mut reader = AsyncChunkReader::new(data);
+        let mut writer = vec![];
+
+        assert!(reader.read_to_end(&mut writer).await.is_err());
+    }
 }
@@ -0,0 +1,188 @@
+//! parsing cookies set by servers
+use crate::{date::parse_http_date, error::ParseErr};
+use std::{str, time::SystemTime};
+
+/// Value of `SameSite` attribute of a cookie.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum SameSite {
+    Strict,
+    Lax,
+    None,
+}
+
+/// Cookie set by a server with `Set-Cookie` header (RFC 6265).
+///
+/// # Examples
+/// ```
+/// use http_req::cookie::Cookie;
+///
+/// let cookie: Cookie = "id=a3fWa; Path=/docs; Secure; HttpOnly".parse().unwrap();
+///
+/// assert_eq!(cookie.name(), "id");
+/// assert_eq!(cookie.value(), "a3fWa");
+/// assert_eq!(cookie.path(), Some("/docs"));
+/// assert!(cookie.secure());
+/// assert!(cookie.http_only());
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+pub struct Cookie {
+    name: String,
+    value: String,
+    expires: Option<SystemTime>,
+    max_age: Option<i64>,
+    domain: Option<String>,
+    path: Option<String>,
+    secure: bool,
+    http_only: bool,
+    same_site: Option<SameSite>,
+}
+
+impl Cookie {
+    /// Returns name of the cookie.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns value of the cookie.
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// Returns time of expiry from `Expires` attribute.
+    pub const fn expires(&self) -> Option<SystemTime> {
+        self.expires
+    }
+
+    /// Returns number of seconds until expiry from `Max-Age` attribute. Zero or negative
+    /// values mean that the cookie has expired.
+    pub const fn max_age(&self) -> Option<i64> {
+        self.max_age
+    }
+
+    /// Returns domain from `Domain` attribute, without the leading dot.
+    pub fn domain(&self) -> Option<&str> {
+        self.domain.as_deref()
+    }
+
+    /// Returns path from `Path` attribute.
+    pub fn path(&self) -> Option<&str> {
+        self.path.as_deref()
+    }
+
+    /// Checks if the cookie has `Secure` attribute.
+    pub const fn secure(&self) -> bool {
+        self.secure
+    }
+
+    /// Checks if the cookie has `HttpOnly` attribute.
+    pub const fn http_only(&self) -> bool {
+        self.http_only
+    }
+
+    /// Returns value of `SameSite` attribute.
+    pub const fn same_site(&self) -> Option<SameSite> {
+        self.same_site
+    }
+}
+
+impl str::FromStr for Cookie {
+    type Err = ParseErr;
+
+    /// Parses value of `Set-Cookie` header. Unknown and invalid attributes are ignored.
+    fn from_str(s: &str) -> Result<Cookie, ParseErr> {
+        let mut parts = s.split(';');
+        let (name, value) = parts
+            .next()
+            .unwrap_or_default()
+            .split_once('=')
+            .ok_or(ParseErr::Invalid)?;
+        let name = name.trim();
+
+        if name.is_empty() {
+            return Err(ParseErr::Invalid);
+        }
+
+        let mut cookie = Cookie {
+            name: name.to_string(),
+            value: value.trim().to_string(),
+            expires: None,
+            max_age: None,
+            domain: None,
+            path: None,
+            secure: false,
+            http_only: false,
+            same_site: None,
+        };
+
+        for attr in parts {
+            let (key, val) = attr.split_once('=').unwrap_or((attr, ""));
+            let (key, val) = (key.trim().to_ascii_lowercase(), val.trim());
+
+            match key.as_str() {
+                "expires" => cookie.expires = parse_http_date(val).or(cookie.expires),
+                "max-age" => cookie.max_age = val.parse().ok().or(cookie.max_age),
+                "domain" if !val.is_empty() => {
+                    cookie.domain = Some(val.trim_start_matches('.').to_ascii_lowercase())
+                }
+                "path" if val.starts_with('/') => cookie.path = Some(val.to_string()),
+                "secure" => cookie.secure = true,
+                "httponly" => cookie.http_only = true,
+                "samesite" => {
+                    cookie.same_site = match val.to_ascii_lowercase().as_str() {
+                        "strict" => Some(SameSite::Strict),
+                        "lax" => Some(SameSite::Lax),
+                        "none" => Some(SameSite::None),
+                        _ => cookie.same_site,
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        Ok(cookie)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn cookie_from_str() {
+        let cookie: Cookie = "SID = 31d4d96e407aad42 ; Expires=Sun, 06 Nov 1994 08:49:37 GMT; \
+            Max-Age=-1; Domain=.Example.com; Path=/; secure; HTTPONLY; SameSite=lax; Priority=High"
+            .parse()
+            .unwrap();
+
+        assert_eq!(
+            cookie,
+            Cookie {
+                name: "SID".to_string(),
+                value: "31d4d96e407aad42".to_string(),
+                expires: Some(UNIX_EPOCH + Duration::from_secs(784111777)),
+                max_age: Some(-1),
+                domain: Some("example.com".to_string()),
+                path: Some("/".to_string()),
+                secure: true,
+                http_only: true,
+                same_site: Some(SameSite::Lax),
+            }
+        );
+    }
+
+    #[test]
+    fn cookie_from_str_invalid() {
+        assert!("no-value".parse::<Cookie>().is_err());
+        assert!("=value".parse::<Cookie>().is_err());
+
+        let cookie: Cookie = "a=; Expires=never; Max-Age=x; Path=relative; SameSite=any"
+            .parse()
+            .unwrap();
+        assert_eq!(cookie.value(), "");
+        assert_eq!(cookie.expires(), None);
+        assert_eq!(cookie.max_age(), None);
+        assert_eq!(cookie.path(), None);
+        assert_eq!(cookie.same_site(), None);
+    }
+}
@@ -0,0 +1,268 @@
+//! parsing and formatting HTTP-dates
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+const SECS_PER_DAY: i64 = 86400;
+
+/// Parses HTTP-date in any of formats accepted by RFC 9110:
+/// - IMF-fixdate: `Sun, 06 Nov 1994 08:49:37 GMT`
+/// - obsolete RFC 850 format: `Sunday, 06-Nov-94 08:49:37 GMT`
+/// - ANSI C's asctime() format: `Sun Nov  6 08:49:37 1994`
+///
+/// # Examples
+/// ```
+/// use http_req::date::parse_http_date;
+/// use std::time::{Duration, UNIX_EPOCH};
+///
+/// let time = parse_http_date("Sun, 06 Nov 1994 08:49:37 GMT").unwrap();
+/// assert_eq!(time, UNIX_EPOCH + Duration::from_secs(784111777));
+/// ```
+pub fn parse_http_date(date: &str) -> Option<SystemTime> {
+    let date = date.trim();
+
+    let (day, month, year, time) = match date.split_once(", ") {
+        Some((_, rest)) => {
+            let mut parts = rest.split(' ');
+            let first = parts.next()?;
+
+            let (day, month, year) = if first.contains('-') {
+                let mut dmy = first.split('-');
+                let (day, month, year) = (dmy.next()?, dmy.next()?, dmy.next()?);
+
+                if year.len() != 2 {
+                    return None;
+                }
+
+                // Two-digit years are interpreted as in the closest century.
+                let year: i64 = year.parse().ok()?;
+                let year = if year < 70 { 2000 + year } else { 1900 + year };
+                (day, month, year)
+            } else {
+                let (month, year) = (parts.next()?, parts.next()?);
+                (first, month, year.parse().ok()?)
+            };
+
+            let time = parts.next()?;
+            if parts.next()? != "GMT" || parts.next().is_some() {
+                return None;
+            }
+
+            (day, month, year, time)
+        }
+        None => {
+            let mut parts = date.split_whitespace();
+            parts.next()?;
+
+            let (month, day, time) = (parts.next()?, parts.next()?, parts.next()?);
+            let year = parts.next()?.parse().ok()?;
+
+            if parts.next().is_some() {
+                return None;
+            }
+
+            (day, month, year, time)
+        }
+    };
+
+    let day: u32 = day.parse().ok()?;
+    let month = MONTHS.iter().position(|m| *m == month)? as u32 + 1;
+
+    let mut hms = time.split(':');
+    let hour: i64 = hms.next()?.parse().ok()?;
+    let min: i64 = hms.next()?.parse().ok()?;
+    let sec: i64 = hms.next()?.parse().ok()?;
+
+    if hms.next().is_some() {
+        return None;
+    }
+
+    from_civil(year, month, day, hour, min, sec)
+}
+
+/// Returns time for the given date and time of day (UTC). Returns `None` if any of
+/// the values is out of range, including years outside of 1-9999.
+pub(crate) fn from_civil(
+    year: i64,
+    month: u32,
+    day: u32,
+    hour: i64,
+    min: i64,
+    sec: i64,
+) -> Option<SystemTime> {
+    if !(1..=9999).contains(&year)
+        || month == 0
+        || month > 12
+        || day == 0
+        || day > days_in_month(year, month)
+        || !(0..24).contains(&hour)
+        || !(0..60).contains(&min)
+        || !(0..=60).contains(&sec)
+    {
+        return None;
+    }
+
+    let secs = days_from_civil(year, month, day) * SECS_PER_DAY + hour * 3600 + min * 60 + sec;
+
+    if secs >= 0 {
+        UNIX_EPOCH.checked_add(Duration::from_secs(secs as u64))
+    } else {
+        UNIX_EPOCH.checked_sub(Duration::from_secs(secs.unsigned_abs()))
+    }
+}
+
+/// Formats `time` as IMF-fixdate, e.g. `Sun, 06 Nov 1994 08:49:37 GMT`.
+/// Times before the Unix epoch are formatted as the epoch.
+///
+/// # Examples
+/// ```
+/// use http_req::date::fmt_http_date;
+/// use std::time::{Duration, UNIX_EPOCH};
+///
+/// let time = UNIX_EPOCH + Duration::from_secs(784111777);
+/// assert_eq!(fmt_http_date(time), "Sun, 06 Nov 1994 08:49:37 GMT");
+/// ```
+pub fn fmt_http_date(time: SystemTime) -> String {
+    let (year, month, day, hour, min, sec) = to_civil(time);
+    let days = days_from_civil(year, month, day);
+    // 1970-01-01 was a Thursday.
+    let weekday = WEEKDAYS[((days + 3) % 7) as usize];
+
+    format!(
+        "{}, {:02} {} {} {:02}:{:02}:{:02} GMT",
+        weekday,
+        day,
+        MONTHS[month as usize - 1],
+        year,
+        hour,
+        min,
+        sec
+    )
+}
+
+/// Returns date and time of day (UTC) of `time`, as (year, month, day, hour, min, sec).
+/// Times before the Unix epoch are treated as the epoch.
+pub(crate) fn to_civil(time: SystemTime) -> (i64, u32, u32, i64, i64, i64) {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64;
+
+    let secs_of_day = secs % SECS_PER_DAY;
+    let (year, month, day) = civil_from_days(secs / SECS_PER_DAY);
+
+    (
+        year,
+        month,
+        day,
+        secs_of_day / 3600,
+        secs_of_day % 3600 / 60,
+        secs_of_day % 60,
+    )
+}
+
+/// Checks if `year` is a leap year in the proleptic Gregorian calendar.
+fn is_leap_year(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+/// Returns number of days in `month` (1-12) of `year`.
+fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Returns number of days since 1970-01-01 for the given date.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let month = month as i64;
+    let day_of_year =
+        (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * 146097 + day_of_era - 719468
+}
+
+/// Returns date (year, month, day) for the given number of days since 1970-01-01.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECS: u64 = 784111777;
+
+    #[test]
+    fn fn_parse_http_date() {
+        let time = UNIX_EPOCH + Duration::from_secs(SECS);
+
+        assert_eq!(parse_http_date("Sun, 06 Nov 1994 08:49:37 GMT"), Some(time));
+        assert_eq!(
+            parse_http_date("Sunday, 06-Nov-94 08:49:37 GMT"),
+            Some(time)
+        );
+        assert_eq!(parse_http_date("Sun Nov  6 08:49:37 1994"), Some(time));
+        assert_eq!(
+            parse_http_date("Thu, 01 Jan 1970 00:00:00 GMT"),
+            Some(UNIX_EPOCH)
+        );
+        assert_eq!(
+            parse_http_date("Wed, 29 Feb 2040 12:00:00 GMT"),
+            Some(UNIX_EPOCH + Duration::from_secs(2214129600))
+        );
+    }
+
+    #[test]
+    fn fn_parse_http_date_invalid() {
+        const INVALID: [&str; 10] = [
+            "",
+            "Sun, 06 Nov 1994 08:49:37",
+            "Sun, 06 Nov 1994 08:49:37 CET",
+            "Sun, 31 Nov 1994 08:49:37 GMT",
+            "Sun, 06 Foo 1994 08:49:37 GMT",
+            "Sun, 06 Nov 1994 24:49:37 GMT",
+            "Mon, 29 Feb 2100 00:00:00 GMT",
+            "Sun, 06 Nov 9223372036854775807 08:49:37 GMT",
+            "Sun, 06 Nov -9223372036854775808 08:49:37 GMT",
+            "Sun Nov  6 08:49:37 10000",
+        ];
+
+        for date in INVALID {
+            assert_eq!(parse_http_date(date), None, "{}", date);
+        }
+    }
+
+    #[test]
+    fn fn_fmt_http_date() {
+        assert_eq!(
+            fmt_http_date(UNIX_EPOCH + Duration::from_secs(SECS)),
+            "Sun, 06 Nov 1994 08:49:37 GMT"
+        );
+        assert_eq!(fmt_http_date(UNIX_EPOCH), "Thu, 01 Jan 1970 00:00:00 GMT");
+        assert_eq!(
+            fmt_http_date(UNIX_EPOCH + Duration::from_secs(2214129600)),
+            "Wed, 29 Feb 2040 12:00:00 GMT"
+        );
+    }
+}
@@ -0,0 +1,521 @@
+//! capturing details of requests for bug reports
+use crate::{
+    error::Error,
+    response::{find_slice, is_interim, Response, CR_LF_2},
+};
+use std::{
+    fmt,
+    io::{self, Read},
+    net::SocketAddr,
+    sync::{Arc, Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+
+/// Headers, values of which are never captured.
+const REDACTED_HEADERS: [&str; 4] = [
+    "Authorization",
+    "Proxy-Authorization",
+    "Cookie",
+    "Set-Cookie",
+];
+const REDACTED: &str = "<redacted>";
+
+/// Event in the lifetime of a request, recorded by `DebugBundle`.
+#[derive(Debug, PartialEq, Clone)]
+pub enum DebugEvent {
+    /// TCP connection has been opened to the address.
+    Connected(SocketAddr),
+    /// Connection has been upgraded to TLS. Contains subjects of certificates presented
+    /// by the server.
+    TlsEstablished(Vec<String>),
+    /// Head of the request has been sent.
+    RequestSent,
+    /// Head of the response has been received, with the given status code.
+    ResponseHead(u16),
+    /// The request is redirected to the location.
+    Redirect(String),
+    /// The whole response has been received.
+    Completed,
+    /// The request has failed.
+    Failed,
+}
+
+impl fmt::Display for DebugEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use self::DebugEvent::*;
+
+        match self {
+            Connected(addr) => write!(f, "connected to {}", addr),
+            TlsEstablished(certs) => {
+                write!(f, "TLS established, certificates: {}", certs.join("; "))
+            }
+            RequestSent => write!(f, "request sent"),
+            ResponseHead(status) => write!(f, "response head received, status {}", status),
+            Redirect(location) => write!(f, "redirected to {}", location),
+            Completed => write!(f, "completed"),
+            Failed => write!(f, "failed"),
+        }
+    }
+}
+
+/// Details of a request captured by `DebugBundle`.
+///
+/// Its `Display` implementation produces a plain text report, which can be attached
+/// to bug reports. Values of `Authorization`, `Proxy-Authorization`, `Cookie` and
+/// `Set-Cookie` headers are redacted.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct DebugReport {
+    events: Vec<(Duration, DebugEvent)>,
+    request_head: Option<String>,
+    response_head: Option<String>,
+    error: Option<String>,
+}
+
+impl DebugReport {
+    /// Returns recorded events with the time elapsed since the request was started.
+    pub fn events(&self) -> &[(Duration, DebugEvent)] {
+        &self.events
+    }
+
+    /// Returns the head of the last sent request.
+    pub fn request_head(&self) -> Option<&str> {
+        self.request_head.as_deref()
+    }
+
+    /// Returns the head of the last received response.
+    pub fn response_head(&self) -> Option<&str> {
+        self.response_head.as_deref()
+    }
+
+    /// Returns the error, which the request has failed with.
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+}
+
+impl fmt::Display for DebugReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (elapsed, event) in &self.events {
+            writeln!(f, "+{:.3}s {}", elapsed.as_secs_f64(), event)?;
+        }
+
+        if let Some(head) = &self.request_head {
+            write!(f, "\nrequest:\n{}", head)?;
+        }
+
+        if let Some(head) = &self.response_head {
+            write!(f, "\nresponse:\n{}", head)?;
+        }
+
+        if let Some(error) = &self.error {
+            writeln!(f, "\nerror: {}", error)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct State {
+    start: Instant,
+    report: DebugReport,
+}
+
+/// Records resolved addresses, timings, TLS certificates, heads of the request and
+/// the response and an error (if any) of the last request sent with it.
+///
+/// # Examples
+/// ```
+/// use http_req::{debug::DebugBundle, request::Request, uri::Uri};
+/// use std::convert::TryFrom;
+///
+/// let uri = Uri::try_from("https://www.rust-lang.org/learn").unwrap();
+/// let bundle = DebugBundle::new();
+///
+/// let request = Request::new(&uri)
+///     .capture_debug_bundle(&bundle);
+///
+/// // After sending the request
+/// println!("{}", bundle.report());
+/// ```
+#[derive(Debug)]
+pub struct DebugBundle(Mutex<State>);
+
+impl DebugBundle {
+    /// Creates a new empty `DebugBundle`.
+    pub fn new() -> DebugBundle {
+        DebugBundle(Mutex::new(State {
+            start: Instant::now(),
+            report: DebugReport::default(),
+        }))
+    }
+
+    /// Returns the report of the last request.
+    pub fn report(&self) -> DebugReport {
+        self.state().report.clone()
+    }
+
+    /// Clears the report and starts measuring time of a new request.
+    pub(crate) fn start(&self) {
+        let mut state = self.state();
+        state.start = Instant::now();
+        state.report = DebugReport::default();
+    }
+
+    /// Records `event`.
+    pub(crate) fn record(&self, event: DebugEvent) {
+        let mut state = self.state();
+        let elapsed = state.start.elapsed();
+        state.report.events.push((elapsed, event));
+    }
+
+    /// Saves the head of the sent request message.
+    pub(crate) fn set_request_head(&self, msg: &[u8]) {
+        let head = String::from_utf8_lossy(msg);
+        let head = head.split("\r\n\r\n").next().unwrap_or_default();
+        let mut lines = head.split("\r\n");
+        let mut redacted = String::new();
+
+        if let Some(line) = lines.next() {
+            redacted = redacted + line + "\n";
+        }
+
+        for line in lines {
+            let (key, val) = line.split_once(':').unwrap_or((line, ""));
+            redacted = redacted + key + ": " + redact(key, val.trim()) + "\n";
+        }
+
+        self.state().report.request_head = Some(redacted);
+    }
+
+    /// Saves the head of the received response.
+    pub(crate) fn set_response_head(&self, response: &Response) {
+        let mut head = format!(
+            "{} {} {}\n",
+            response.version(),
+            response.status_code(),
+            response.reason()
+        );
+
+        for (key, val) in response.headers().iter() {
+            head = head + key + ": " + redact(key, val) + "\n";
+        }
+
+        self.state().report.response_head = Some(head);
+    }
+
+    /// Records failure of the request with `err`.
+    pub(crate) fn set_error(&self, err: &Error) {
+        self.record(DebugEvent::Failed);
+        self.state().report.error = Some(err.to_string());
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, State> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Default for DebugBundle {
+    fn default() -> Self {
+        DebugBundle::new()
+    }
+}
+
+/// Bytes sent and received over a single connection, captured by `WireCapture`.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct WireExchange {
+    sent: Vec<u8>,
+    received: Vec<u8>,
+}
+
+impl WireExchange {
+    /// Returns bytes written to the connection.
+    pub fn sent(&self) -> &[u8] {
+        &self.sent
+    }
+
+    /// Returns bytes read from the connection.
+    pub fn received(&self) -> &[u8] {
+        &self.received
+    }
+}
+
+/// Captures exact bytes written to and read from connections of requests sent with it,
+/// one `WireExchange` per connection (redirects and retries open new ones).
+///
+/// For HTTPS, bytes are captured before encryption and after decryption. By default,
+/// values of `Authorization`, `Proxy-Authorization`, `Cookie` and `Set-Cookie` headers
+/// are redacted (see `redact`).
+///
+/// # Examples
+/// ```
+/// use http_req::{debug::WireCapture, request::Request, uri::Uri};
+/// use std::convert::TryFrom;
+///
+/// let uri = Uri::try_from("https://www.rust-lang.org/learn").unwrap();
+/// let capture = WireCapture::new();
+///
+/// let request = Request::new(&uri)
+///     .capture_wire(&capture);
+///
+/// // After sending the request
+/// for exchange in capture.exchanges() {
+///     println!("{}", String::from_utf8_lossy(exchange.received()));
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct WireCapture {
+    exchanges: Arc<Mutex<Vec<WireExchange>>>,
+    redact: bool,
+}
+
+impl WireCapture {
+    /// Creates a new, empty `WireCapture`.
+    pub fn new() -> WireCapture {
+        WireCapture {
+            exchanges: Arc::new(Mutex::new(Vec::new())),
+            redact: true,
+        }
+    }
+
+    /// Sets if values of headers containing secrets are redacted in `exchanges`.
+    ///
+    /// # Examples
+    /// ```
+    /// use http_req::debug::WireCapture;
+    ///
+    /// let mut capture = WireCapture::new();
+    /// capture.redact(false);
+    /// ```
+    pub fn redact(&mut self, redact: bool) -> &mut Self {
+        self.redact = redact;
+        self
+    }
+
+    /// Returns exchanges captured so far.
+    pub fn exchanges(&self) -> Vec<WireExchange> {
+        let exchanges = self.state().clone();
+
+        if !self.redact {
+            return exchanges;
+        }
+
+        exchanges
+            .into_iter()
+            .map(|exchange| WireExchange {
+                sent: redact_heads(&exchange.sent),
+                received: redact_heads(&exchange.received),
+            })
+            .collect()
+    }
+
+    /// Removes all captured exchanges.
+    pub fn clear(&self) {
+        self.state().clear();
+    }
+
+    /// Starts capturing a new connection.
+    pub(crate) fn begin(&self) -> WireTap {
+        let mut exchanges = self.state();
+        exchanges.push(WireExchange::default());
+
+        WireTap {
+            exchanges: Arc::clone(&self.exchanges),
+            index: exchanges.len() - 1,
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, Vec<WireExchange>> {
+        self.exchanges.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Default for WireCapture {
+    fn default() -> Self {
+        WireCapture::new()
+    }
+}
+
+/// Handle appending bytes of a single connection to `WireCapture`.
+#[derive(Debug)]
+pub(crate) struct WireTap {
+    exchanges: Arc<Mutex<Vec<WireExchange>>>,
+    index: usize,
+}
+
+impl WireTap {
+    /// Appends `bytes` written to the connection.
+    pub(crate) fn sent(&self, bytes: &[u8]) {
+        self.exchange(|exchange| exchange.sent.extend_from_slice(bytes));
+    }
+
+    /// Appends `bytes` read from the connection.
+    pub(crate) fn received(&self, bytes: &[u8]) {
+        self.exchange(|exchange| exchange.received.extend_from_slice(bytes));
+    }
+
+    fn exchange<F: FnOnce(&mut WireExchange)>(&self, f: F) {
+        let mut exchanges = self.exchanges.lock().unwrap_or_else(|e| e.into_inner());
+
+        // The capture might have been cleared in the meantime.
+        if let Some(exchange) = exchanges.get_mut(self.index) {
+            f(exchange);
+        }
+    }
+}
+
+/// Reader passing bytes read from `inner` to a `WireTap`.
+pub(crate) struct WireReader<R> {
+    inner: R,
+    tap: Option<WireTap>,
+}
+
+impl<R> WireReader<R> {
+    pub(crate) fn new(inner: R, tap: Option<WireTap>) -> WireReader<R> {
+        WireReader { inner, tap }
+    }
+}
+
+impl<R: Read> Read for WireReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = self.inner.read(buf)?;
+
+        if let Some(tap) = &self.tap {
+            tap.received(&buf[..len]);
+        }
+
+        Ok(len)
+    }
+}
+
+/// Checks if values of the header `key` contain secrets.
+fn is_secret(key: &str) -> bool {
+    REDACTED_HEADERS.iter().any(|h| h.eq_ignore_ascii_case(key))
+}
+
+/// Returns `val`, or a placeholder if the header `key` contains secrets.
+fn redact<'v>(key: &str, val: &'v str) -> &'v str {
+    if is_secret(key) {
+        REDACTED
+    } else {
+        val
+    }
+}
+
+/// Redacts values of headers containing secrets in heads at the beginning of `bytes`
+/// (including heads of informational responses). The rest is copied as it is.
+fn redact_heads(bytes: &[u8]) -> Vec<u8> {
+    let mut redacted = Vec::with_capacity(bytes.len());
+    let mut rest = bytes;
+
+    while let Some(end) = find_slice(rest, &CR_LF_2) {
+        let (head, tail) = rest.split_at(end);
+
+        for (i, line) in head.split(|&b| b == b'\n').enumerate() {
+            let text = String::from_utf8_lossy(line);
+
+            match text.split_once(':') {
+                Some((key, _)) if i > 0 && is_secret(key) => {
+                    redacted.extend_from_slice(key.as_bytes());
+                    redacted.extend_from_slice(b": ");
+                    redacted.extend_from_slice(REDACTED.as_bytes());
+                    redacted.extend_from_slice(b"\r");
+                }
+                _ => redacted.extend_from_slice(line),
+            }
+
+            redacted.push(b'\n');
+        }
+
+        // Heads end with an empty line, so there is one newline too many.
+        redacted.pop();
+        rest = tail;
+
+        if !is_interim(head) {
+            break;
+        }
+    }
+
+    redacted.extend_from_slice(rest);
+    redacted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn debug_bundle_heads() {
+        let bundle = DebugBundle::new();
+        bundle.set_request_head(
+            b"GET / HTTP/1.1\r\nHost: example.com\r\nauthorization: Basic Zm9v\r\n\r\nbody",
+        );
+
+        let response = Response::from_head(b"HTTP/1.1 200 OK\r\nSet-Cookie: id=1\r\n\r\n").unwrap();
+        bundle.set_response_head(&response);
+
+        let report = bundle.report();
+        assert_eq!(
+            report.request_head(),
+            Some("GET / HTTP/1.1\nHost: example.com\nauthorization: <redacted>\n")
+        );
+        assert_eq!(
+            report.response_head(),
+            Some("HTTP/1.1 200 OK\nSet-Cookie: <redacted>\n")
+        );
+    }
+
+    #[test]
+    fn debug_bundle_events() {
+        let bundle = DebugBundle::new();
+        bundle.record(DebugEvent::RequestSent);
+        bundle.set_error(&Error::Timeout);
+
+        let report = bundle.report();
+        let events: Vec<_> = report.events().iter().map(|(_, e)| e.clone()).collect();
+        assert_eq!(events, vec![DebugEvent::RequestSent, DebugEvent::Failed]);
+        assert_eq!(report.error(), Some("Error: Timeout error"));
+        assert!(report.to_string().contains("request sent\n"));
+
+        bundle.start();
+        assert_eq!(bundle.report(), DebugReport::default());
+    }
+
+    #[test]
+    fn fn_redact_heads() {
+        assert_eq!(
+            redact_heads(
+                b"HTTP/1.1 103 Early Hints\r\nset-cookie: a\r\n\r\n\
+                HTTP/1.1 200 OK\r\nSet-Cookie: b\r\nX: y\r\n\r\nCookie: c\r\n\r\n"
+            ),
+            b"HTTP/1.1 103 Early Hints\r\nset-cookie: <redacted>\r\n\r\n\
+                HTTP/1.1 200 OK\r\nSet-Cookie: <redacted>\r\nX: y\r\n\r\nCookie: c\r\n\r\n"
+        );
+        assert_eq!(redact_heads(b"partial head"), b"partial head");
+    }
+
+    #[test]
+    fn wire_capture() {
+        let mut capture = WireCapture::new();
+        let tap = capture.begin();
+        tap.sent(b"GET / HTTP/1.1\r\nAuthorization: Basic Zm9v\r\n\r\n");
+        tap.received(b"HTTP/1.1 200 OK\r\n\r\nok");
+
+        let exchanges = capture.exchanges();
+        assert_eq!(exchanges.len(), 1);
+        assert_eq!(
+            exchanges[0].sent(),
+            b"GET / HTTP/1.1\r\nAuthorization: <redacted>\r\n\r\n"
+        );
+        assert_eq!(exchanges[0].received(), b"HTTP/1.1 200 OK\r\n\r\nok");
+
+        capture.redact(false);
+        assert!(capture.exchanges()[0]
+            .sent()
+            .ends_with(b"Basic Zm9v\r\n\r\n"));
+
+        capture.clear();
+        tap.received(b"ignored");
+        assert!(capture.exchanges().is_empty());
+    }
+}
@@ -0,0 +1,283 @@
+//! resolving host names
+use std::{
+    collections::HashMap,
+    io,
+    net::{Ipv6Addr, SocketAddr, SocketAddrV6, ToSocketAddrs},
+    sync::{Mutex, MutexGuard, OnceLock},
+    time::{Duration, Instant},
+};
+
+const DEFAULT_TTL: u64 = 60;
+
+type Entries = HashMap<(String, u16), (Instant, Vec<SocketAddr>)>;
+
+/// Resolves `host` to a list of socket addresses.
+///
+/// IPv6 addresses in brackets (as in URIs) may include a zone ID, e.g. `[fe80::1%25eth0]`,
+/// which is used as the scope ID of the address. Zone ID is either a numeric index
+/// or (on Linux) a name of a network interface.
+pub fn resolve(host: &str, port: u16) -> io::Result<Vec<SocketAddr>> {
+    if let Some(addr) = parse_ip_literal(host, port)? {
+        return Ok(vec![addr]);
+    }
+
+    let addrs: Vec<_> = (host, port).to_socket_addrs()?.collect();
+    check_resolved(host, addrs)
+}
+
+/// Resolves `host` to a list of socket addresses like `resolve`,
+/// but without blocking the executor of Tokio runtime.
+#[cfg(feature = "tokio")]
+pub async fn resolve_async(host: &str, port: u16) -> io::Result<Vec<SocketAddr>> {
+    if let Some(addr) = parse_ip_literal(host, port)? {
+        return Ok(vec![addr]);
+    }
+
+    let addrs: Vec<_> = tokio::net::lookup_host((host, port)).await?.collect();
+    check_resolved(host, addrs)
+}
+
+/// Fails if no address was found for `host`.
+fn check_resolved(host: &str, addrs: Vec<SocketAddr>) -> io::Result<Vec<SocketAddr>> {
+    if addrs.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::AddrNotAvailable,
+            format!("Could not resolve address for {:?}", host),
+        ));
+    }
+
+    Ok(addrs)
+}
+
+/// Parses IPv6 address in brackets, optionally with a zone ID. Returns `None` if `host`
+/// isn't in brackets.
+fn parse_ip_literal(host: &str, port: u16) -> io::Result<Option<SocketAddr>> {
+    let literal = match host.strip_prefix('[').and_then(|h| h.strip_suffix(']')) {
+        Some(literal) => literal,
+        None => return Ok(None),
+    };
+
+    let (addr, zone) = match literal.split_once('%') {
+        Some((addr, zone)) => (addr, Some(zone.strip_prefix("25").unwrap_or(zone))),
+        None => (literal, None),
+    };
+
+    let addr: Ipv6Addr = addr.parse().map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Invalid IPv6 address {:?}", host),
+        )
+    })?;
+    let scope_id = zone.map(scope_id).transpose()?.unwrap_or(0);
+
+    Ok(Some(SocketAddrV6::new(addr, port, 0, scope_id).into()))
+}
+
+/// Returns scope ID for a zone ID, which is either a numeric index
+/// or (on Linux) a name of a network interface.
+fn scope_id(zone: &str) -> io::Result<u32> {
+    if let Ok(id) = zone.parse() {
+        return Ok(id);
+    }
+
+    #[cfg(target_os = "linux")]
+    if !zone.is_empty()
+        && !zone.starts_with('.')
+        && zone
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_.".contains(c))
+    {
+        let path = format!("/sys/class/net/{}/ifindex", zone);
+
+        if let Some(id) = std::fs::read_to_string(path)
+            .ok()
+            .and_then(|id| id.trim().parse().ok())
+        {
+            return Ok(id);
+        }
+    }
+
+    Err(io::Error::new(
+        io::ErrorKind::AddrNotAvailable,
+        format!("Unknown network interface {:?}", zone),
+    ))
+}
+
+/// In-process cache of resolved addresses.
+///
+/// The operating system resolver doesn't expose TTLs of DNS records, therefore entries
+/// are kept for a fixed time (`ttl`), after which a host is resolved again.
+///
+/// # Examples
+/// ```
+/// use http_req::dns::DnsCache;
+/// use std::time::Duration;
+///
+/// let cache = DnsCache::new(Duration::from_secs(30));
+/// let addrs = cache.resolve("localhost", 80).unwrap();
+///
+/// assert!(!addrs.is_empty());
+/// assert_eq!(cache.len(), 1);
+///
+/// cache.flush();
+/// assert_eq!(cache.len(), 0);
+/// ```
+#[derive(Debug)]
+pub struct DnsCache {
+    ttl: Duration,
+    entries: Mutex<Entries>,
+}
+
+impl DnsCache {
+    /// Creates a new empty `DnsCache`, which keeps entries for `ttl`.
+    pub fn new<T>(ttl: T) -> DnsCache
+    where
+        Duration: From<T>,
+    {
+        DnsCache {
+            ttl: Duration::from(ttl),
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the process-wide `DnsCache`, which keeps entries for 60 seconds.
+    ///
+    /// # Examples
+    /// ```
+    /// use http_req::{dns::DnsCache, request::Request, uri::Uri};
+    /// use std::convert::TryFrom;
+    ///
+    /// let uri = Uri::try_from("https://www.rust-lang.org/learn").unwrap();
+    ///
+    /// let request = Request::new(&uri)
+    ///     .dns_cache(DnsCache::global());
+    /// ```
+    pub fn global() -> &'static DnsCache {
+        static GLOBAL: OnceLock<DnsCache> = OnceLock::new();
+        GLOBAL.get_or_init(|| DnsCache::new(Duration::from_secs(DEFAULT_TTL)))
+    }
+
+    /// Returns time for which entries are kept in the cache.
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// Returns addresses of `host` from the cache. If there is no such an entry
+    /// or it has expired, resolves `host` and stores the result.
+    pub fn resolve(&self, host: &str, port: u16) -> io::Result<Vec<SocketAddr>> {
+        let key = (host.to_string(), port);
+
+        if let Some((resolved_at, addrs)) = self.lock().get(&key) {
+            if resolved_at.elapsed() < self.ttl {
+                return Ok(addrs.clone());
+            }
+        }
+
+        // Lock is not held while resolving, so that other hosts can be looked up meanwhile.
+        let addrs = resolve(host, port)?;
+        let mut entries = self.lock();
+
+        entries.retain(|_, (resolved_at, _)| resolved_at.elapsed() < self.ttl);
+        entries.insert(key, (Instant::now(), addrs.clone()));
+
+        Ok(addrs)
+    }
+
+    /// Removes the entry for `host` from the cache, e.g. after connecting to
+    /// all of its addresses has failed.
+    pub fn remove(&self, host: &str, port: u16) {
+        self.lock().remove(&(host.to_string(), port));
+    }
+
+    /// Removes all entries from the cache.
+    pub fn flush(&self) {
+        self.lock().clear();
+    }
+
+    /// Returns number of entries in the cache, including expired ones.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Checks if the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Entries> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fn_resolve() {
+        let addrs = resolve("127.0.0.1", 80).unwrap();
+        assert_eq!(addrs, vec![SocketAddr::from(([127, 0, 0, 1], 80))]);
+
+        assert!(resolve("", 80).is_err());
+    }
+
+    #[test]
+    fn fn_resolve_ip_literal() {
+        let addrs = resolve("[::1]", 80).unwrap();
+        assert_eq!(addrs, vec![SocketAddr::from((Ipv6Addr::LOCALHOST, 80))]);
+
+        let link_local: Ipv6Addr = "fe80::1".parse().unwrap();
+        for host in ["[fe80::1%253]", "[fe80::1%3]"] {
+            let addrs = resolve(host, 443).unwrap();
+            assert_eq!(addrs, vec![SocketAddrV6::new(link_local, 443, 0, 3).into()]);
+        }
+
+        #[cfg(target_os = "linux")]
+        assert!(matches!(
+            resolve("[fe80::1%25lo]", 80).unwrap()[0],
+            SocketAddr::V6(addr) if addr.scope_id() > 0
+        ));
+
+        assert!(resolve("[fe80::1%25no-such-interface]", 80).is_err());
+        assert!(resolve("[::1/]", 80).is_err());
+    }
+
+    #[test]
+    fn dns_cache_resolve() {
+        let cache = DnsCache::new(Duration::from_secs(60));
+
+        assert!(cache.is_empty());
+        assert_eq!(
+            cache.resolve("127.0.0.1", 80).unwrap(),
+            vec![SocketAddr::from(([127, 0, 0, 1], 80))]
+        );
+        assert_eq!(cache.len(), 1);
+
+        cache.resolve("127.0.0.1", 80).unwrap();
+        cache.resolve("127.0.0.1", 443).unwrap();
+        assert_eq!(cache.len(), 2);
+
+        cache.remove("127.0.0.1", 80);
+        assert_eq!(cache.len(), 1);
+
+        cache.flush();
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn dns_cache_ttl() {
+        let cache = DnsCache::new(Duration::ZERO);
+
+        cache.resolve("127.0.0.1", 80).unwrap();
+        cache.resolve("127.0.0.1", 443).unwrap();
+
+        assert_eq!(cache.ttl(), Duration::ZERO);
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn dns_cache_global() {
+        assert!(std::ptr::eq(DnsCache::global(), DnsCache::global()));
+        assert_eq!(DnsCache::global().ttl(), Duration::from_secs(DEFAULT_TTL));
+    }
+}
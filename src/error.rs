@@ -7,9 +7,79 @@ pub enum ParseErr {
     Int(num::ParseIntError),
     StatusErr,
     HeadersErr,
-    UriErr,
+    UriErr {
+        component: UriComponent,
+        position: usize,
+    },
     Invalid,
     Empty,
+    TooManyHeaders {
+        limit: usize,
+    },
+    InvalidHeader {
+        name: String,
+    },
+    AmbiguousFraming,
+    Json {
+        position: usize,
+        message: String,
+    },
+}
+
+/// Component of URI, which was being parsed when an error occurred.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum UriComponent {
+    Scheme,
+    Authority,
+    Host,
+    Port,
+    Path,
+}
+
+impl fmt::Display for UriComponent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use self::UriComponent::*;
+
+        let component = match self {
+            Scheme => "scheme",
+            Authority => "authority",
+            Host => "host",
+            Port => "port",
+            Path => "path",
+        };
+        write!(f, "{}", component)
+    }
+}
+
+/// Reasons for rejecting the certificate of a server during TLS handshake.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum CertificateError {
+    Expired,
+    NotYetValid,
+    WrongHost,
+    UnknownIssuer,
+    SelfSigned,
+    Revoked,
+    NotPinned,
+    Other,
+}
+
+impl fmt::Display for CertificateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use self::CertificateError::*;
+
+        let reason = match self {
+            Expired => "expired",
+            NotYetValid => "not yet valid",
+            WrongHost => "issued for another host",
+            UnknownIssuer => "issued by an unknown authority",
+            SelfSigned => "self-signed",
+            Revoked => "revoked",
+            NotPinned => "not matching any pinned public key",
+            Other => "rejected",
+        };
+        write!(f, "{}", reason)
+    }
 }
 
 impl error::Error for ParseErr {
@@ -19,7 +89,15 @@ impl error::Error for ParseErr {
         match self {
             Utf8(e) => Some(e),
             Int(e) => Some(e),
-            StatusErr | HeadersErr | UriErr | Invalid | Empty => None,
+            StatusErr
+            | HeadersErr
+            | UriErr { .. }
+            | Invalid
+            | Empty
+            | TooManyHeaders { .. }
+            | InvalidHeader { .. }
+            | AmbiguousFraming
+            | Json { .. } => None,
         }
     }
 }
@@ -35,7 +113,34 @@ impl fmt::Display for ParseErr {
             Empty => "Nothing to parse",
             StatusErr => "Status line contains invalid values",
             HeadersErr => "Headers contain invalid values",
-            UriErr => "URI contains invalid characters",
+            AmbiguousFraming => "Conflicting Content-Length or Transfer-Encoding headers",
+            UriErr {
+                component,
+                position,
+            } => {
+                return write!(
+                    f,
+                    "ParseErr: Invalid {} in URI at byte {}",
+                    component, position
+                )
+            }
+            TooManyHeaders { limit } => {
+                return write!(
+                    f,
+                    "ParseErr: Number of headers exceeds the limit of {}",
+                    limit
+                )
+            }
+            InvalidHeader { name } => {
+                return write!(f, "ParseErr: Header {:?} contains illegal characters", name)
+            }
+            Json { position, message } => {
+                return write!(
+                    f,
+                    "ParseErr: Invalid JSON at byte {}: {}",
+                    position, message
+                )
+            }
         };
         write!(f, "ParseErr: {}", err)
     }
@@ -57,9 +162,16 @@ impl From<str::Utf8Error> for ParseErr {
 pub enum Error {
     IO(io::Error),
     Parse(ParseErr),
+    HeadersTooLarge {
+        header: Option<String>,
+        size: usize,
+        limit: usize,
+    },
     Timeout,
     Tls,
+    Certificate(CertificateError),
     Thread,
+    Cancelled,
 }
 
 impl error::Error for Error {
@@ -69,7 +181,7 @@ impl error::Error for Error {
         match self {
             IO(e) => Some(e),
             Parse(e) => Some(e),
-            Timeout | Tls | Thread => None,
+            HeadersTooLarge { .. } | Timeout | Tls | Certificate(_) | Thread | Cancelled => None,
         }
     }
 }
@@ -81,9 +193,31 @@ impl fmt::Display for Error {
         let err = match self {
             IO(_) => "IO error",
             Parse(err) => return err.fmt(f),
+            HeadersTooLarge {
+                header,
+                size,
+                limit,
+            } => {
+                return match header {
+                    Some(header) => write!(
+                        f,
+                        "Error: Value of header {} has {} bytes, exceeding the limit of {}",
+                        header, size, limit
+                    ),
+                    None => write!(
+                        f,
+                        "Error: Request head has {} bytes, exceeding the limit of {}",
+                        size, limit
+                    ),
+                }
+            }
             Timeout => "Timeout error",
             Tls => "TLS error",
+            Certificate(reason) => {
+                return write!(f, "Error: Certificate of the server is {}", reason)
+            }
             Thread => "Thread communication error",
+            Cancelled => "Request cancelled",
         };
         write!(f, "Error: {}", err)
     }
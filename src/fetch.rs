@@ -0,0 +1,341 @@
+//! downloading files and keeping them up to date
+use crate::{
+    error::Error,
+    request::Request,
+    response::{Headers, Response, StatusCode},
+    sink::BodySink,
+    uri::Uri,
+};
+use std::{
+    collections::HashMap,
+    ffi::OsString,
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::{Mutex, MutexGuard},
+};
+
+/// Validators of a downloaded file, which are stored next to it.
+const VALIDATORS: [(&str, &str); 2] = [
+    ("ETag", "If-None-Match"),
+    ("Last-Modified", "If-Modified-Since"),
+];
+
+/// Outcome of `fetch_file`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum FetchStatus {
+    /// The file has been created or its content has changed.
+    Updated,
+    /// The server sent the content again, but it's the same as the content of the file.
+    Unchanged,
+    /// The server confirmed that the file is up to date (`304 Not Modified`).
+    NotModified,
+    /// The server responded with a status other than success or `304 Not Modified`.
+    /// The file has been left untouched.
+    Failed,
+}
+
+/// Downloads `uri` to `path`, revalidating the file if it has been downloaded before.
+///
+/// `ETag` and `Last-Modified` of the response are saved to a file with `.meta` appended
+/// to the name of `path`. They're sent back as `If-None-Match` and `If-Modified-Since`
+/// on subsequent calls, so the server can respond with `304 Not Modified` instead of
+/// the whole content. The file is rewritten (atomically, via a temporary file) only if
+/// its content has changed.
+///
+/// # Examples
+/// ```no_run
+/// use http_req::{fetch::{fetch_file, FetchStatus}, uri::Uri};
+/// use std::convert::TryFrom;
+///
+/// let uri = Uri::try_from("https://www.rust-lang.org/learn").unwrap();
+/// let path = std::env::temp_dir().join("learn.html");
+///
+/// let (response, status) = fetch_file(&uri, &path).unwrap();
+/// ```
+pub fn fetch_file(uri: &Uri, path: &Path) -> Result<(Response, FetchStatus), Error> {
+    let meta_path = with_suffix(path, ".meta");
+    let mut request = Request::new(uri);
+
+    if path.exists() {
+        if let Some(validators) = read_meta(&meta_path)? {
+            for (name, condition) in VALIDATORS {
+                if let Some(value) = validators.get(name) {
+                    request.header(condition, value);
+                }
+            }
+        }
+    }
+
+    let mut body = Vec::new();
+    let response = request.send(&mut body)?;
+
+    if response.status_code() == StatusCode::new(304) {
+        return Ok((response, FetchStatus::NotModified));
+    }
+
+    if !response.status_code().is_success() {
+        return Ok((response, FetchStatus::Failed));
+    }
+
+    let status = match fs::read(path) {
+        Ok(content) if content == body => FetchStatus::Unchanged,
+        Ok(_) => FetchStatus::Updated,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => FetchStatus::Updated,
+        Err(e) => return Err(e.into()),
+    };
+
+    if status == FetchStatus::Updated {
+        write_atomically(path, &body)?;
+    }
+
+    write_meta(&meta_path, response.headers())?;
+    Ok((response, status))
+}
+
+/// Outcome of `ValidatorStore::send`.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Conditional {
+    /// The server sent the content, as it has changed or wasn't requested before.
+    Modified(Response),
+    /// The server confirmed that the copy held by the caller is up to date
+    /// (`304 Not Modified`). Nothing has been written to the sink.
+    NotModified(Response),
+}
+
+impl Conditional {
+    /// Returns the response, regardless of the outcome.
+    pub const fn response(&self) -> &Response {
+        match self {
+            Conditional::Modified(response) | Conditional::NotModified(response) => response,
+        }
+    }
+}
+
+/// Remembers `ETag` and `Last-Modified` of responses per URI and sends them back
+/// as `If-None-Match` and `If-Modified-Since`, so the server can respond with
+/// `304 Not Modified` instead of the whole content.
+///
+/// Unlike `Cache`, it doesn't keep the content, so the caller is responsible for keeping
+/// the copy received before.
+///
+/// # Examples
+/// ```no_run
+/// use http_req::{fetch::{Conditional, ValidatorStore}, request::Request, uri::Uri};
+/// use std::convert::TryFrom;
+///
+/// let uri = Uri::try_from("https://www.rust-lang.org/learn").unwrap();
+/// let store = ValidatorStore::new();
+/// let mut body = Vec::new();
+///
+/// match store.send(&Request::new(&uri), &mut body).unwrap() {
+///     Conditional::Modified(_) => println!("new content: {} bytes", body.len()),
+///     Conditional::NotModified(_) => println!("keeping the previous copy"),
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct ValidatorStore(Mutex<HashMap<String, Headers>>);
+
+impl ValidatorStore {
+    /// Creates a new, empty `ValidatorStore`.
+    pub fn new() -> ValidatorStore {
+        ValidatorStore::default()
+    }
+
+    /// Sends a copy of `request` with validators remembered for its URI and writes body
+    /// of the response to `sink`. Validators of successful responses are remembered
+    /// for the following requests.
+    pub fn send<T>(&self, request: &Request, sink: &mut T) -> Result<Conditional, Error>
+    where
+        T: BodySink,
+    {
+        let key = request.uri().to_string();
+        let mut request = request.clone();
+
+        if let Some(validators) = self.validators().get(&key) {
+            for (name, condition) in VALIDATORS {
+                if let Some(value) = validators.get(name) {
+                    request.header(condition, value);
+                }
+            }
+        }
+
+        let response = request.send(sink)?;
+        let not_modified = response.status_code() == StatusCode::new(304);
+
+        if not_modified || response.status_code().is_success() {
+            let mut validators = self.validators().remove(&key).unwrap_or_default();
+
+            // `304 Not Modified` may omit validators, which haven't changed.
+            if !not_modified {
+                validators = Headers::new();
+            }
+
+            for (name, _) in VALIDATORS {
+                if let Some(value) = response.headers().get(name) {
+                    validators.insert(name, value);
+                }
+            }
+
+            if validators.iter().next().is_some() {
+                self.validators().insert(key, validators);
+            }
+        }
+
+        if not_modified {
+            Ok(Conditional::NotModified(response))
+        } else {
+            Ok(Conditional::Modified(response))
+        }
+    }
+
+    /// Forgets validators remembered for `uri`, so the next request for it is unconditional.
+    pub fn forget(&self, uri: &Uri) {
+        self.validators().remove(&uri.to_string());
+    }
+
+    /// Returns the map of validators, even if another thread panicked while holding the lock.
+    fn validators(&self) -> MutexGuard<'_, HashMap<String, Headers>> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Reads validators saved to `meta_path`. Returns `None` if there are none.
+fn read_meta(meta_path: &Path) -> Result<Option<Headers>, Error> {
+    match fs::read_to_string(meta_path) {
+        Ok(meta) if meta.trim().is_empty() => Ok(None),
+        Ok(meta) => Ok(Some(meta.parse()?)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Saves validators from `headers` to `meta_path`.
+fn write_meta(meta_path: &Path, headers: &Headers) -> Result<(), Error> {
+    let mut meta = String::new();
+
+    for (name, _) in VALIDATORS {
+        if let Some(value) = headers.get(name) {
+            meta = meta + name + ": " + value + "\n";
+        }
+    }
+
+    Ok(write_atomically(meta_path, meta.as_bytes())?)
+}
+
+/// Writes `content` to a temporary file and moves it to `path`, so readers of `path`
+/// never see a partially written file.
+fn write_atomically(path: &Path, content: &[u8]) -> io::Result<()> {
+    let tmp_path = with_suffix(path, ".tmp");
+    let mut file = fs::File::create(&tmp_path)?;
+
+    file.write_all(content)?;
+    file.sync_all()?;
+    fs::rename(tmp_path, path)
+}
+
+/// Appends `suffix` to the file name of `path`.
+pub(crate) fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = OsString::from(path.as_os_str());
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TestServer;
+    use std::convert::TryFrom;
+
+    /// Returns `name` header of the `n`-th request received by `server`.
+    fn header(server: &TestServer, n: usize, name: &str) -> Option<String> {
+        server.requests()[n].head().headers().get(name).cloned()
+    }
+
+    #[test]
+    fn validator_store_send() {
+        let server = TestServer::start([
+            "HTTP/1.1 200 OK\r\nETag: \"v1\"\r\nContent-Length: 2\r\n\r\nv1",
+            "HTTP/1.1 304 Not Modified\r\n\r\n",
+            "HTTP/1.1 200 OK\r\nLast-Modified: Sun, 06 Nov 1994 08:49:37 GMT\r\n\
+             Content-Length: 2\r\n\r\nv2",
+            "HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nv3",
+        ])
+        .unwrap();
+        let uri = server.uri("/file.txt");
+        let uri = Uri::try_from(uri.as_str()).unwrap();
+        let request = Request::new(&uri);
+        let store = ValidatorStore::new();
+
+        let mut body = Vec::new();
+        let res = store.send(&request, &mut body).unwrap();
+        assert!(matches!(res, Conditional::Modified(_)));
+        assert_eq!(header(&server, 0, "If-None-Match"), None);
+        assert_eq!(body, b"v1");
+
+        let res = store.send(&request, &mut body).unwrap();
+        assert!(matches!(res, Conditional::NotModified(_)));
+        assert_eq!(header(&server, 1, "If-None-Match"), Some("\"v1\"".into()));
+
+        store.send(&request, &mut body).unwrap();
+        assert_eq!(header(&server, 2, "If-None-Match"), Some("\"v1\"".into()));
+
+        store.forget(&uri);
+        let res = store.send(&request, &mut body).unwrap();
+        assert_eq!(res.response().status_code(), StatusCode::new(200));
+        assert_eq!(header(&server, 3, "If-None-Match"), None);
+        assert_eq!(header(&server, 3, "If-Modified-Since"), None);
+    }
+
+    #[test]
+    fn fn_fetch_file() {
+        let path = std::env::temp_dir().join(format!("http_req-fetch-{}.txt", std::process::id()));
+        let meta_path = with_suffix(&path, ".meta");
+        let _ = fs::remove_file(&path);
+        let _ = fs::remove_file(&meta_path);
+
+        let server = TestServer::start([
+            "HTTP/1.1 200 OK\r\nETag: \"v1\"\r\nContent-Length: 2\r\n\r\nv1",
+            "HTTP/1.1 304 Not Modified\r\nETag: \"v1\"\r\n\r\n",
+            "HTTP/1.1 200 OK\r\nETag: \"v1b\"\r\nContent-Length: 2\r\n\r\nv1",
+            "HTTP/1.1 200 OK\r\nETag: \"v2\"\r\nContent-Length: 2\r\n\r\nv2",
+            "HTTP/1.1 500 Internal Server Error\r\nContent-Length: 0\r\n\r\n",
+        ])
+        .unwrap();
+        let uri = server.uri("/file.txt");
+        let uri = Uri::try_from(uri.as_str()).unwrap();
+
+        let (_, status) = fetch_file(&uri, &path).unwrap();
+        assert_eq!(status, FetchStatus::Updated);
+        assert_eq!(header(&server, 0, "If-None-Match"), None);
+        assert_eq!(fs::read(&path).unwrap(), b"v1");
+
+        let (_, status) = fetch_file(&uri, &path).unwrap();
+        assert_eq!(status, FetchStatus::NotModified);
+        assert_eq!(header(&server, 1, "If-None-Match"), Some("\"v1\"".into()));
+
+        let (_, status) = fetch_file(&uri, &path).unwrap();
+        assert_eq!(status, FetchStatus::Unchanged);
+        assert_eq!(fs::read_to_string(&meta_path).unwrap(), "ETag: \"v1b\"\n");
+
+        let (_, status) = fetch_file(&uri, &path).unwrap();
+        assert_eq!(status, FetchStatus::Updated);
+        assert_eq!(header(&server, 3, "If-None-Match"), Some("\"v1b\"".into()));
+        assert_eq!(fs::read(&path).unwrap(), b"v2");
+
+        let (_, status) = fetch_file(&uri, &path).unwrap();
+        assert_eq!(status, FetchStatus::Failed);
+        assert_eq!(fs::read(&path).unwrap(), b"v2");
+
+        fs::remove_file(&path).unwrap();
+        fs::remove_file(&meta_path).unwrap();
+    }
+
+    #[test]
+    fn fn_with_suffix() {
+        assert_eq!(
+            with_suffix(Path::new("dir/file.txt"), ".meta"),
+            PathBuf::from("dir/file.txt.meta")
+        );
+    }
+}
@@ -0,0 +1,393 @@
+//! exporting requests and responses to HTTP Archive (HAR) format
+use crate::{
+    date::to_civil,
+    request::RequestMessage,
+    response::{Headers, Response},
+    uri::parse_query,
+};
+use base64::engine::{general_purpose::STANDARD, Engine};
+use serde::Serialize;
+use std::{
+    fmt, io,
+    sync::{Mutex, MutexGuard},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// Records requests sent with it, with their responses and timings, and exports them
+/// in HTTP Archive 1.2 format, which can be imported into browser devtools and proxies.
+///
+/// Bodies of responses are included: as text if they are valid UTF-8, otherwise
+/// encoded with base64. Requests which failed are not recorded.
+///
+/// # Examples
+/// ```no_run
+/// use http_req::{har::HarRecorder, request::Request, uri::Uri};
+/// use std::convert::TryFrom;
+///
+/// let uri = Uri::try_from("https://www.rust-lang.org/learn").unwrap();
+/// let recorder = HarRecorder::new();
+///
+/// let request = Request::new(&uri)
+///     .record_har(&recorder);
+///
+/// // After sending the request
+/// std::fs::write("requests.har", recorder.to_json()).unwrap();
+/// ```
+#[derive(Debug, Default)]
+pub struct HarRecorder(Mutex<Vec<Entry>>);
+
+impl HarRecorder {
+    /// Creates a new, empty `HarRecorder`.
+    pub fn new() -> HarRecorder {
+        HarRecorder::default()
+    }
+
+    /// Returns number of recorded entries.
+    pub fn len(&self) -> usize {
+        self.entries().len()
+    }
+
+    /// Checks if no entries have been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.entries().is_empty()
+    }
+
+    /// Removes all recorded entries.
+    pub fn clear(&self) {
+        self.entries().clear();
+    }
+
+    /// Returns the archive with all recorded entries as JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(&self.archive()).unwrap_or_default()
+    }
+
+    /// Writes the archive with all recorded entries as JSON to `writer`.
+    pub fn write_to<W: io::Write>(&self, writer: W) -> io::Result<()> {
+        serde_json::to_writer_pretty(writer, &self.archive()).map_err(io::Error::from)
+    }
+
+    /// Records `msg` sent at `started` and `response` with `body` received after `time`.
+    pub(crate) fn record(
+        &self,
+        msg: &RequestMessage,
+        response: &Response,
+        body: &[u8],
+        started: SystemTime,
+        time: Duration,
+    ) {
+        let time = time.as_secs_f64() * 1000.0;
+        let request_body = msg.body.unwrap_or_default();
+        let url = msg.url();
+        let without_fragment = url.split('#').next().unwrap_or_default();
+
+        let request = HarRequest {
+            method: msg.method.to_string(),
+            url: url.to_string(),
+            http_version: msg.version.to_string(),
+            cookies: Vec::new(),
+            headers: pairs(&msg.headers),
+            query_string: without_fragment
+                .split_once('?')
+                .map(|(_, query)| parse_query(query))
+                .unwrap_or_default()
+                .iter()
+                .map(|(name, value)| Pair::new(name, value))
+                .collect(),
+            post_data: msg.body.map(|body| PostData {
+                mime_type: msg.headers.get("Content-Type").cloned().unwrap_or_default(),
+                text: String::from_utf8_lossy(body).into_owned(),
+            }),
+            headers_size: -1,
+            body_size: request_body.len() as i64,
+        };
+
+        let (text, encoding) = match std::str::from_utf8(body) {
+            Ok(text) => (text.to_string(), None),
+            Err(_) => (STANDARD.encode(body), Some("base64")),
+        };
+
+        let response = HarResponse {
+            status: response.status_code().into(),
+            status_text: response.reason().to_string(),
+            http_version: response.version().to_string(),
+            cookies: Vec::new(),
+            headers: pairs(response.headers()),
+            content: Content {
+                size: body.len() as i64,
+                mime_type: response
+                    .headers()
+                    .get("Content-Type")
+                    .cloned()
+                    .unwrap_or_default(),
+                text,
+                encoding,
+            },
+            redirect_url: response
+                .headers()
+                .location()
+                .unwrap_or_default()
+                .to_string(),
+            headers_size: -1,
+            body_size: body.len() as i64,
+        };
+
+        self.entries().push(Entry {
+            started_date_time: fmt_iso_date(started),
+            time,
+            request,
+            response,
+            cache: Cache {},
+            timings: Timings {
+                send: 0.0,
+                wait: time,
+                receive: 0.0,
+            },
+        });
+    }
+
+    /// Returns the archive with clones of recorded entries.
+    fn archive(&self) -> Archive {
+        Archive {
+            log: Log {
+                version: "1.2",
+                creator: Creator {
+                    name: "http_req",
+                    version: env!("CARGO_PKG_VERSION"),
+                },
+                entries: self.entries().clone(),
+            },
+        }
+    }
+
+    fn entries(&self) -> MutexGuard<'_, Vec<Entry>> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Writer passing bytes to `inner` and keeping a copy of them.
+pub(crate) struct CopyWriter<'w, W: ?Sized> {
+    inner: &'w mut W,
+    copy: Vec<u8>,
+}
+
+impl<'w, W: io::Write + ?Sized> CopyWriter<'w, W> {
+    pub(crate) fn new(inner: &'w mut W) -> Self {
+        CopyWriter {
+            inner,
+            copy: Vec::new(),
+        }
+    }
+
+    /// Returns the copy of written bytes.
+    pub(crate) fn copy(&self) -> &[u8] {
+        &self.copy
+    }
+}
+
+impl<W: io::Write + ?Sized> io::Write for CopyWriter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let len = self.inner.write(buf)?;
+        self.copy.extend_from_slice(&buf[..len]);
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[derive(Serialize)]
+struct Archive {
+    log: Log,
+}
+
+#[derive(Serialize)]
+struct Log {
+    version: &'static str,
+    creator: Creator,
+    entries: Vec<Entry>,
+}
+
+#[derive(Serialize)]
+struct Creator {
+    name: &'static str,
+    version: &'static str,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct Entry {
+    started_date_time: String,
+    time: f64,
+    request: HarRequest,
+    response: HarResponse,
+    cache: Cache,
+    timings: Timings,
+}
+
+impl fmt::Debug for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {}", self.request.method, self.request.url)
+    }
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct HarRequest {
+    method: String,
+    url: String,
+    http_version: String,
+    cookies: Vec<Pair>,
+    headers: Vec<Pair>,
+    query_string: Vec<Pair>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    post_data: Option<PostData>,
+    headers_size: i64,
+    body_size: i64,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct HarResponse {
+    status: u16,
+    status_text: String,
+    http_version: String,
+    cookies: Vec<Pair>,
+    headers: Vec<Pair>,
+    content: Content,
+    #[serde(rename = "redirectURL")]
+    redirect_url: String,
+    headers_size: i64,
+    body_size: i64,
+}
+
+#[derive(Serialize, Clone)]
+struct Pair {
+    name: String,
+    value: String,
+}
+
+impl Pair {
+    fn new(name: &str, value: &str) -> Pair {
+        Pair {
+            name: name.to_string(),
+            value: value.to_string(),
+        }
+    }
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct PostData {
+    mime_type: String,
+    text: String,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct Content {
+    size: i64,
+    mime_type: String,
+    text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    encoding: Option<&'static str>,
+}
+
+#[derive(Serialize, Clone)]
+struct Cache {}
+
+#[derive(Serialize, Clone)]
+struct Timings {
+    send: f64,
+    wait: f64,
+    receive: f64,
+}
+
+/// Returns `headers` as name-value pairs, sorted by name.
+fn pairs(headers: &Headers) -> Vec<Pair> {
+    let mut pairs: Vec<_> = headers
+        .iter()
+        .map(|(name, value)| Pair::new(name, value))
+        .collect();
+
+    pairs.sort_by(|a, b| a.name.cmp(&b.name));
+    pairs
+}
+
+/// Formats `time` as ISO 8601 date in UTC, with milliseconds.
+fn fmt_iso_date(time: SystemTime) -> String {
+    let (year, month, day, hour, min, sec) = to_civil(time);
+    let millis = time
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .subsec_millis();
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year, month, day, hour, min, sec, millis
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::uri::Uri;
+    use std::{convert::TryFrom, io::Write};
+
+    #[test]
+    fn fn_fmt_iso_date() {
+        let time = UNIX_EPOCH + Duration::from_millis(784_111_777_250);
+        assert_eq!(fmt_iso_date(time), "1994-11-06T08:49:37.250Z");
+    }
+
+    #[test]
+    fn copy_writer() {
+        let mut inner = Vec::new();
+        let mut writer = CopyWriter::new(&mut inner);
+        writer.write_all(b"body").unwrap();
+
+        assert_eq!(writer.copy(), b"body");
+        assert_eq!(inner, b"body");
+    }
+
+    #[test]
+    fn har_recorder_to_json() {
+        let uri = Uri::try_from("http://example.com/a?b=1&c").unwrap();
+        let mut msg = RequestMessage::new(&uri);
+        msg.body(b"data");
+
+        let response = Response::from_head(
+            b"HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nContent-Length: 2\r\n\r\n",
+        )
+        .unwrap();
+
+        let recorder = HarRecorder::new();
+        let started = UNIX_EPOCH + Duration::from_secs(784111777);
+        recorder.record(&msg, &response, b"ok", started, Duration::from_millis(5));
+        recorder.record(&msg, &response, b"\xff", started, Duration::from_millis(5));
+        assert_eq!(recorder.len(), 2);
+
+        let har: serde_json::Value = serde_json::from_str(&recorder.to_json()).unwrap();
+        let entry = &har["log"]["entries"][0];
+
+        assert_eq!(har["log"]["version"], "1.2");
+        assert_eq!(entry["startedDateTime"], "1994-11-06T08:49:37.000Z");
+        assert_eq!(entry["time"], 5.0);
+        assert_eq!(entry["request"]["method"], "GET");
+        assert_eq!(entry["request"]["url"], "http://example.com/a?b=1&c");
+        assert_eq!(entry["request"]["queryString"][1]["name"], "c");
+        assert_eq!(entry["request"]["postData"]["text"], "data");
+        assert_eq!(entry["request"]["headers"][0]["name"], "Content-Length");
+        assert_eq!(entry["response"]["status"], 200);
+        assert_eq!(entry["response"]["content"]["mimeType"], "text/plain");
+        assert_eq!(entry["response"]["content"]["text"], "ok");
+        assert_eq!(
+            har["log"]["entries"][1]["response"]["content"]["encoding"],
+            "base64"
+        );
+
+        recorder.clear();
+        assert!(recorder.is_empty());
+    }
+}
@@ -0,0 +1,431 @@
+//! conversions between http_req types and types of the `http` crate
+//!
+//! | http_req                      | `http`                       |
+//! |-------------------------------|------------------------------|
+//! | `Method`                      | `http::Method`               |
+//! | `HttpVersion`                 | `http::Version`              |
+//! | `StatusCode`                  | `http::StatusCode`           |
+//! | `Headers`                     | `http::HeaderMap`            |
+//! | `Uri`                         | `http::Uri`                  |
+//! | `RequestMessage`              | `http::Request<Vec<u8>>`     |
+//! | `Response` / `FullResponse`   | `http::Response<_>`          |
+//!
+//! Since `Uri` and `RequestMessage` borrow the data they refer to, they are created from
+//! `http` types in two steps: `http::Uri` is first converted to a string, and then
+//! `RequestMessage::from_http` borrows the rest of `http::Request`. `Response` has an inherent
+//! `try_from` method, so its conversion is called with `<Response as TryFrom<_>>::try_from`.
+//!
+//! # Examples
+//! ```
+//! use http_req::{request::RequestMessage, uri::Uri};
+//! use std::convert::TryFrom;
+//!
+//! let request = http::Request::post("https://www.rust-lang.org/learn")
+//!     .header("Content-Type", "text/plain")
+//!     .body(b"hello".to_vec())
+//!     .unwrap();
+//!
+//! let uri = request.uri().to_string();
+//! let uri = Uri::try_from(uri.as_str()).unwrap();
+//! let msg = RequestMessage::from_http(&uri, &request).unwrap();
+//!
+//! let request = http::Request::try_from(&msg).unwrap();
+//! assert_eq!(request.body(), b"hello");
+//! ```
+use crate::{
+    error::{Error, ParseErr},
+    request::{HttpVersion, Method, RequestMessage},
+    response::{FullResponse, Headers, Response, StatusCode},
+    uri::Uri,
+};
+use std::{convert::TryFrom, str};
+
+impl TryFrom<&Method> for http::Method {
+    type Error = ParseErr;
+
+    /// Fails for extension methods which aren't valid tokens.
+    fn try_from(method: &Method) -> Result<http::Method, ParseErr> {
+        Ok(match method {
+            Method::GET => http::Method::GET,
+            Method::HEAD => http::Method::HEAD,
+            Method::POST => http::Method::POST,
+            Method::PUT => http::Method::PUT,
+            Method::DELETE => http::Method::DELETE,
+            Method::CONNECT => http::Method::CONNECT,
+            Method::OPTIONS => http::Method::OPTIONS,
+            Method::TRACE => http::Method::TRACE,
+            Method::PATCH => http::Method::PATCH,
+            Method::Custom(method) => {
+                http::Method::from_bytes(method.as_bytes()).map_err(|_| ParseErr::Invalid)?
+            }
+        })
+    }
+}
+
+impl TryFrom<&http::Method> for Method {
+    type Error = ParseErr;
+
+    /// Extension methods are converted to `Method::Custom`.
+    fn try_from(method: &http::Method) -> Result<Method, ParseErr> {
+        method.as_str().parse()
+    }
+}
+
+impl From<HttpVersion> for http::Version {
+    fn from(version: HttpVersion) -> http::Version {
+        match version {
+            HttpVersion::Http10 => http::Version::HTTP_10,
+            HttpVersion::Http11 => http::Version::HTTP_11,
+            HttpVersion::Http20 => http::Version::HTTP_2,
+        }
+    }
+}
+
+impl TryFrom<http::Version> for HttpVersion {
+    type Error = ParseErr;
+
+    /// Fails for HTTP/0.9 and HTTP/3, which aren't supported.
+    fn try_from(version: http::Version) -> Result<HttpVersion, ParseErr> {
+        match version {
+            http::Version::HTTP_10 => Ok(HttpVersion::Http10),
+            http::Version::HTTP_11 => Ok(HttpVersion::Http11),
+            http::Version::HTTP_2 => Ok(HttpVersion::Http20),
+            _ => Err(ParseErr::Invalid),
+        }
+    }
+}
+
+impl From<http::StatusCode> for StatusCode {
+    fn from(code: http::StatusCode) -> StatusCode {
+        StatusCode::from(code.as_u16())
+    }
+}
+
+impl TryFrom<StatusCode> for http::StatusCode {
+    type Error = ParseErr;
+
+    /// Fails for codes outside of 100..=999.
+    fn try_from(code: StatusCode) -> Result<http::StatusCode, ParseErr> {
+        http::StatusCode::from_u16(code.into()).map_err(|_| ParseErr::StatusErr)
+    }
+}
+
+impl TryFrom<&Headers> for http::HeaderMap {
+    type Error = ParseErr;
+
+    /// Fails with `ParseErr::InvalidHeader` if any of the headers is invalid.
+    fn try_from(headers: &Headers) -> Result<http::HeaderMap, ParseErr> {
+        let mut map = http::HeaderMap::with_capacity(headers.iter().len());
+
+        for (key, val) in headers.iter() {
+            let invalid = || ParseErr::InvalidHeader {
+                name: key.to_string(),
+            };
+
+            let name = http::HeaderName::from_bytes(key.as_bytes()).map_err(|_| invalid())?;
+            let value = http::HeaderValue::from_str(val).map_err(|_| invalid())?;
+            map.append(name, value);
+        }
+
+        Ok(map)
+    }
+}
+
+impl TryFrom<&http::HeaderMap> for Headers {
+    type Error = ParseErr;
+
+    /// Repeated headers are joined into a comma-separated list.
+    /// Fails with `ParseErr::InvalidHeader` if any of the values isn't valid UTF-8.
+    fn try_from(map: &http::HeaderMap) -> Result<Headers, ParseErr> {
+        let mut headers = Headers::with_capacity(map.keys_len());
+
+        for (name, value) in map.iter() {
+            let value = str::from_utf8(value.as_bytes()).map_err(|_| ParseErr::InvalidHeader {
+                name: name.to_string(),
+            })?;
+
+            let value = match headers.get(name.as_str()) {
+                Some(prev) => format!("{}, {}", prev, value),
+                None => value.to_string(),
+            };
+
+            headers.insert_raw(name.to_string(), value);
+        }
+
+        Ok(headers)
+    }
+}
+
+impl TryFrom<&Uri<'_>> for http::Uri {
+    type Error = ParseErr;
+
+    /// Fragment of the URI is dropped. Fails if `http::Uri` rejects the URI.
+    fn try_from(uri: &Uri) -> Result<http::Uri, ParseErr> {
+        uri.get_ref().parse().map_err(|_| ParseErr::Invalid)
+    }
+}
+
+impl<'a> RequestMessage<'a> {
+    /// Creates a new `RequestMessage` with `uri` and method, version, headers and body
+    /// of `request`. Uri of `request` itself is ignored, so that `uri` (usually parsed
+    /// from it) can be borrowed for the lifetime of the message.
+    ///
+    /// # Examples
+    /// ```
+    /// use http_req::{request::{Method, RequestMessage}, uri::Uri};
+    /// use std::convert::TryFrom;
+    ///
+    /// let request = http::Request::head("https://www.rust-lang.org/learn")
+    ///     .body(Vec::new())
+    ///     .unwrap();
+    ///
+    /// let uri = request.uri().to_string();
+    /// let uri = Uri::try_from(uri.as_str()).unwrap();
+    /// let msg = RequestMessage::from_http(&uri, &request).unwrap();
+    /// ```
+    pub fn from_http<B>(
+        uri: &'a Uri<'a>,
+        request: &'a http::Request<B>,
+    ) -> Result<RequestMessage<'a>, ParseErr>
+    where
+        B: AsRef<[u8]>,
+    {
+        let mut msg = RequestMessage::new(uri);
+        msg.method(Method::try_from(request.method())?)
+            .version(HttpVersion::try_from(request.version())?);
+
+        for (key, val) in Headers::try_from(request.headers())?.iter() {
+            msg.headers.insert_raw(key.to_string(), val.to_string());
+        }
+
+        let body = request.body().as_ref();
+        if !body.is_empty() {
+            msg.body(body);
+        }
+
+        Ok(msg)
+    }
+}
+
+impl TryFrom<&RequestMessage<'_>> for http::Request<Vec<u8>> {
+    type Error = ParseErr;
+
+    fn try_from(msg: &RequestMessage) -> Result<http::Request<Vec<u8>>, ParseErr> {
+        let mut request = http::Request::new(msg.body.unwrap_or_default().to_vec());
+
+        *request.method_mut() = http::Method::try_from(&msg.method)?;
+        *request.version_mut() = msg.version.into();
+        *request.uri_mut() = http::Uri::try_from(msg.uri)?;
+        *request.headers_mut() = http::HeaderMap::try_from(&msg.headers)?;
+
+        Ok(request)
+    }
+}
+
+impl TryFrom<&Response> for http::Response<()> {
+    type Error = ParseErr;
+
+    fn try_from(response: &Response) -> Result<http::Response<()>, ParseErr> {
+        let mut res = http::Response::new(());
+
+        *res.status_mut() = http::StatusCode::try_from(response.status_code())?;
+        *res.version_mut() = response.version().into();
+        *res.headers_mut() = http::HeaderMap::try_from(response.headers())?;
+
+        Ok(res)
+    }
+}
+
+impl TryFrom<FullResponse> for http::Response<Vec<u8>> {
+    type Error = ParseErr;
+
+    fn try_from(full: FullResponse) -> Result<http::Response<Vec<u8>>, ParseErr> {
+        let (response, body) = full.into_parts();
+        let res = http::Response::try_from(&response)?;
+
+        Ok(res.map(|_| body))
+    }
+}
+
+impl<B> TryFrom<&http::Response<B>> for Response {
+    type Error = Error;
+
+    /// Parses the head of `response` as if it was received from a server, so that
+    /// e.g. `Response::cookies` are available. The body is ignored.
+    fn try_from(response: &http::Response<B>) -> Result<Response, Error> {
+        let status = response.status();
+        let mut head = format!(
+            "{} {} {}\r\n",
+            HttpVersion::try_from(response.version())?,
+            status.as_u16(),
+            status.canonical_reason().unwrap_or_default()
+        )
+        .into_bytes();
+
+        for (name, value) in response.headers() {
+            head.extend_from_slice(name.as_str().as_bytes());
+            head.extend_from_slice(b": ");
+            head.extend_from_slice(value.as_bytes());
+            head.extend_from_slice(b"\r\n");
+        }
+
+        head.extend_from_slice(b"\r\n");
+        Response::from_head(&head)
+    }
+}
+
+impl<B> TryFrom<http::Response<B>> for FullResponse
+where
+    B: Into<Vec<u8>>,
+{
+    type Error = Error;
+
+    fn try_from(response: http::Response<B>) -> Result<FullResponse, Error> {
+        let head = <Response as TryFrom<_>>::try_from(&response)?;
+
+        Ok(head.with_body(response.into_body().into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const URI: &str = "https://www.rust-lang.org/learn?q=1";
+
+    #[test]
+    fn method_conversions() {
+        assert_eq!(
+            http::Method::try_from(&Method::PATCH),
+            Ok(http::Method::PATCH)
+        );
+        assert_eq!(
+            http::Method::try_from(&Method::Custom("PURGE".to_string())),
+            Ok(http::Method::from_bytes(b"PURGE").unwrap())
+        );
+        assert!(http::Method::try_from(&Method::Custom("NOT VALID".to_string())).is_err());
+        assert_eq!(Method::try_from(&http::Method::PUT), Ok(Method::PUT));
+        assert_eq!(
+            Method::try_from(&http::Method::from_bytes(b"PURGE").unwrap()),
+            Ok(Method::Custom("PURGE".to_string()))
+        );
+    }
+
+    #[test]
+    fn version_conversions() {
+        assert_eq!(
+            http::Version::from(HttpVersion::Http20),
+            http::Version::HTTP_2
+        );
+        assert_eq!(
+            HttpVersion::try_from(http::Version::HTTP_10),
+            Ok(HttpVersion::Http10)
+        );
+        assert!(HttpVersion::try_from(http::Version::HTTP_3).is_err());
+    }
+
+    #[test]
+    fn status_code_conversions() {
+        assert_eq!(
+            StatusCode::from(http::StatusCode::NOT_FOUND),
+            StatusCode::new(404)
+        );
+        assert_eq!(
+            http::StatusCode::try_from(StatusCode::new(201)),
+            Ok(http::StatusCode::CREATED)
+        );
+        assert!(http::StatusCode::try_from(StatusCode::new(1000)).is_err());
+    }
+
+    #[test]
+    fn headers_conversions() {
+        let mut map = http::HeaderMap::new();
+        map.append("accept", "text/html".parse().unwrap());
+        map.append("accept", "text/plain".parse().unwrap());
+        map.append("x-bin", http::HeaderValue::from_bytes(b"\xff").unwrap());
+
+        assert_eq!(
+            Headers::try_from(&map),
+            Err(ParseErr::InvalidHeader {
+                name: "x-bin".to_string()
+            })
+        );
+
+        map.remove("x-bin");
+        let headers = Headers::try_from(&map).unwrap();
+        assert_eq!(
+            headers.get("Accept"),
+            Some(&"text/html, text/plain".to_string())
+        );
+
+        let map = http::HeaderMap::try_from(&headers).unwrap();
+        assert_eq!(map["accept"], "text/html, text/plain");
+
+        let mut headers = Headers::new();
+        headers.insert("Bad Name", "value");
+        assert!(http::HeaderMap::try_from(&headers).is_err());
+    }
+
+    #[test]
+    fn uri_conversions() {
+        let uri = Uri::try_from(URI).unwrap();
+        assert_eq!(http::Uri::try_from(&uri).unwrap(), URI);
+
+        let uri = Uri::try_from("https://www.rust-lang.org/learn#fragment").unwrap();
+        assert_eq!(
+            http::Uri::try_from(&uri).unwrap(),
+            "https://www.rust-lang.org/learn"
+        );
+    }
+
+    #[test]
+    fn request_conversions() {
+        let request = http::Request::post(URI)
+            .header("Content-Type", "text/plain")
+            .body(b"hello".to_vec())
+            .unwrap();
+
+        let uri = request.uri().to_string();
+        let uri = Uri::try_from(uri.as_str()).unwrap();
+        let msg = RequestMessage::from_http(&uri, &request).unwrap();
+
+        assert_eq!(msg.method, Method::POST);
+        assert_eq!(msg.body, Some(&b"hello"[..]));
+        assert_eq!(
+            msg.headers.get("Host"),
+            Some(&"www.rust-lang.org".to_string())
+        );
+        assert_eq!(
+            msg.headers.get("Content-Type"),
+            Some(&"text/plain".to_string())
+        );
+
+        let request = http::Request::try_from(&msg).unwrap();
+        assert_eq!(request.method(), http::Method::POST);
+        assert_eq!(request.uri(), URI);
+        assert_eq!(request.headers()["content-type"], "text/plain");
+        assert_eq!(request.body(), b"hello");
+    }
+
+    #[test]
+    fn response_conversions() {
+        let response = http::Response::builder()
+            .status(404)
+            .header("Set-Cookie", "a=1")
+            .header("Set-Cookie", "b=2")
+            .header("Content-Length", "2")
+            .body(b"no".to_vec())
+            .unwrap();
+
+        let full = FullResponse::try_from(response).unwrap();
+        assert_eq!(full.status_code(), StatusCode::new(404));
+        assert_eq!(full.response().reason(), "Not Found");
+        assert_eq!(full.response().cookies().len(), 2);
+        assert_eq!(full.body(), b"no");
+
+        let response = http::Response::try_from(full).unwrap();
+        assert_eq!(response.status(), http::StatusCode::NOT_FOUND);
+        assert_eq!(response.headers()["content-length"], "2");
+        assert_eq!(response.body(), b"no");
+    }
+}
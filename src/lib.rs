@@ -4,9 +4,29 @@
 //! which relies on TLS framework provided by OS on Windows and macOS, and OpenSSL
 //! on all other platforms. But it also supports [rus-tls](https://crates.io/crates/rustls).
 //!
+//! Optional features:
+//! - `rust-tls-native-roots` - rustls trusts root certificates installed in the OS
+//!   in addition to the bundled ones.
+//! - `aws-sign` - requests can be signed with AWS Signature Version 4 (see `aws` module).
+//! - `serde` - `Headers`, `Status`, `Uri` and `Authority` implement `Serialize`
+//!   and `Deserialize`, and `Response::json` deserializes bodies.
+//! - `charset` - `Response::text` decodes bodies in charsets other than UTF-8.
+//! - `har` - requests and responses can be exported in HTTP Archive format
+//!   (see `har` module).
+//! - `http` - types can be converted to and from types of the
+//!   [http](https://crates.io/crates/http) crate (see `interop` module).
+//! - `tokio` - requests can be sent asynchronously in Tokio runtime
+//!   (see `async_request` module).
+//! - `tokio-native-tls` - asynchronous requests can be sent over TLS with rust-native-tls.
+//! - `tokio-rust-tls` - asynchronous requests can be sent over TLS with rustls.
+//! - `test-util` - `test_util` module with a local server serving scripted responses.
+//!
+//! Without TLS features (e.g. on WASI targets), only plain HTTP is supported. On WebAssembly
+//! targets, requests are sent in single-threaded mode by default.
+//!
 //! ## Example
 //! Basic GET request
-//! ```
+//! ```no_run
 //! use http_req::request;
 //!
 //! fn main() {
@@ -17,13 +37,128 @@
 //!     println!("Status: {} {}", res.status_code(), res.reason());
 //! }
 //! ```
+#[cfg(feature = "tokio")]
+pub mod async_request;
+#[cfg(feature = "tokio")]
+pub mod async_stream;
+pub mod auth;
+#[cfg(feature = "aws-sign")]
+pub mod aws;
+pub mod broadcast;
+pub mod cache;
+pub mod cancel;
 pub mod chunked;
+pub mod cookie;
+pub mod date;
+pub mod debug;
+pub mod dns;
 pub mod error;
+pub mod fetch;
+#[cfg(feature = "har")]
+pub mod har;
+#[cfg(feature = "http")]
+pub mod interop;
+pub mod middleware;
 pub mod request;
 pub mod response;
+pub mod sink;
+pub mod sse;
 pub mod stream;
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_util;
 pub mod tls;
 pub mod uri;
+pub mod vcr;
+
+use std::fmt;
 
 pub(crate) const CR_LF: &[u8; 2] = b"\r\n";
 pub(crate) const LF: u8 = 0xA;
+
+/// Information about the build of this crate: its version, TLS backend and enabled features.
+#[derive(Debug, PartialEq, Clone)]
+pub struct BuildInfo {
+    version: &'static str,
+    tls_backend: &'static str,
+    features: Vec<&'static str>,
+}
+
+impl BuildInfo {
+    /// Returns version of the crate.
+    pub const fn version(&self) -> &'static str {
+        self.version
+    }
+
+    /// Returns name of the TLS backend (`native-tls` or `rust-tls`), or `none` if neither is enabled.
+    pub const fn tls_backend(&self) -> &'static str {
+        self.tls_backend
+    }
+
+    /// Returns names of enabled features.
+    pub fn features(&self) -> &[&'static str] {
+        &self.features
+    }
+}
+
+impl fmt::Display for BuildInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "http_req {} (TLS: {}, features: {})",
+            self.version,
+            self.tls_backend,
+            self.features.join(", ")
+        )
+    }
+}
+
+/// Returns information about the build of this crate, e.g. for logging
+/// configuration of the HTTP stack.
+///
+/// # Examples
+/// ```
+/// let info = http_req::build_info();
+///
+/// assert_eq!(info.version(), env!("CARGO_PKG_VERSION"));
+///
+/// if info.tls_backend() != "none" {
+///     assert!(info.features().contains(&info.tls_backend()));
+/// }
+///
+/// println!("{}", info);
+/// ```
+pub fn build_info() -> BuildInfo {
+    let features = [
+        ("native-tls", cfg!(feature = "native-tls")),
+        ("rust-tls", cfg!(feature = "rust-tls")),
+        (
+            "rust-tls-native-roots",
+            cfg!(feature = "rust-tls-native-roots"),
+        ),
+        ("aws-sign", cfg!(feature = "aws-sign")),
+        ("serde", cfg!(feature = "serde")),
+        ("charset", cfg!(feature = "charset")),
+        ("har", cfg!(feature = "har")),
+        ("http", cfg!(feature = "http")),
+        ("test-util", cfg!(feature = "test-util")),
+        ("tokio", cfg!(feature = "tokio")),
+        ("tokio-native-tls", cfg!(feature = "tokio-native-tls")),
+        ("tokio-rust-tls", cfg!(feature = "tokio-rust-tls")),
+    ];
+
+    BuildInfo {
+        version: env!("CARGO_PKG_VERSION"),
+        tls_backend: if cfg!(feature = "native-tls") {
+            "native-tls"
+        } else if cfg!(feature = "rust-tls") {
+            "rust-tls"
+        } else {
+            "none"
+        },
+        features: features
+            .into_iter()
+            .filter(|(_, enabled)| *enabled)
+            .map(|(name, _)| name)
+            .collect(),
+    }
+}
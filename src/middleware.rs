@@ -0,0 +1,82 @@
+//! wrapping execution of requests with middleware
+use crate::{error::Error, request::Request, response::Response};
+use std::io::Write;
+
+/// Middleware wrapping execution of a request, e.g. to inject headers, log requests,
+/// collect metrics or retry failed requests. It's added with `Request::middleware`.
+///
+/// Middleware calls `next.run` to pass the request to the next middleware (or to send it,
+/// if it's the last one), and may inspect or modify the request before that and
+/// the response after that. It may also return a response without calling `next` at all.
+///
+/// Closures with a matching signature implement `Middleware` too.
+///
+/// # Examples
+/// ```
+/// use http_req::{
+///     error::Error,
+///     middleware::{Middleware, Next},
+///     request::Request,
+///     response::Response,
+/// };
+///
+/// struct Logger;
+///
+/// impl Middleware for Logger {
+///     fn handle(&self, request: &mut Request, next: &mut Next) -> Result<Response, Error> {
+///         let res = next.run(request);
+///
+///         match &res {
+///             Ok(response) => println!("{}", response.status_code()),
+///             Err(err) => println!("{}", err),
+///         }
+///
+///         res
+///     }
+/// }
+/// ```
+pub trait Middleware {
+    /// Handles `request`, calling `next` to continue its execution.
+    fn handle(&self, request: &mut Request, next: &mut Next) -> Result<Response, Error>;
+}
+
+impl<F> Middleware for F
+where
+    F: Fn(&mut Request, &mut Next) -> Result<Response, Error>,
+{
+    fn handle(&self, request: &mut Request, next: &mut Next) -> Result<Response, Error> {
+        self(request, next)
+    }
+}
+
+/// The rest of the middleware chain, which ends with sending the request.
+pub struct Next<'n, 'w> {
+    chain: &'n [&'n dyn Middleware],
+    writer: &'n mut (dyn Write + 'w),
+}
+
+impl<'n, 'w> Next<'n, 'w> {
+    /// Creates a new `Next` running `chain` and then sending the request.
+    pub(crate) fn new(chain: &'n [&'n dyn Middleware], writer: &'n mut (dyn Write + 'w)) -> Self {
+        Next { chain, writer }
+    }
+
+    /// Passes `request` to the rest of the chain and returns its response. Body of
+    /// the response is written to the sink given to `Request::send`.
+    ///
+    /// It can be called more than once (e.g. to retry the request), but then bodies
+    /// of all responses are written to the sink.
+    pub fn run(&mut self, request: &mut Request) -> Result<Response, Error> {
+        match self.chain.split_first() {
+            Some((middleware, rest)) => {
+                let mut next = Next {
+                    chain: rest,
+                    writer: &mut *self.writer,
+                };
+
+                middleware.handle(request, &mut next)
+            }
+            None => request.send_with_cache(&mut self.writer),
+        }
+    }
+}
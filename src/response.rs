@@ -1,7 +1,10 @@
 //! parsing server response
 use crate::{
+    cookie::Cookie,
+    date::parse_http_date,
     error::{Error, ParseErr},
-    request::Method,
+    request::{HttpVersion, Method},
+    tls::Certificate,
     uri::Uri,
 };
 use std::{
@@ -9,11 +12,40 @@ use std::{
     fmt,
     io::Write,
     str,
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 use unicase::Ascii;
 
 pub(crate) const CR_LF_2: [u8; 4] = [13, 10, 13, 10];
 
+/// Default maximum number of headers parsed from a single response head.
+pub const MAX_HEADERS: usize = 128;
+
+/// Fields which mustn't be sent in trailers, as they're needed before the body is processed
+/// (RFC 9110, section 6.5.1): framing, routing, authentication, controls and content metadata.
+const FORBIDDEN_TRAILERS: [&str; 20] = [
+    "Age",
+    "Authorization",
+    "Cache-Control",
+    "Content-Encoding",
+    "Content-Length",
+    "Content-Range",
+    "Content-Type",
+    "Date",
+    "Expires",
+    "Host",
+    "Location",
+    "Proxy-Authenticate",
+    "Proxy-Authorization",
+    "Retry-After",
+    "Set-Cookie",
+    "Trailer",
+    "Transfer-Encoding",
+    "Vary",
+    "Warning",
+    "WWW-Authenticate",
+];
+
 /// Represents an HTTP response.
 ///
 /// It contains `Headers` and `Status` parsed from response.
@@ -21,11 +53,17 @@ pub(crate) const CR_LF_2: [u8; 4] = [13, 10, 13, 10];
 pub struct Response {
     status: Status,
     headers: Headers,
+    interim: Vec<Response>,
+    cookies: Vec<Cookie>,
+    peer_certificates: Vec<Certificate>,
 }
 
 impl Response {
     /// Creates new `Response` with head - status and headers - parsed from a slice of bytes
     ///
+    /// If `head` starts with heads of informational (1xx) responses, they are skipped
+    /// and available via `interim`. `101 Switching Protocols` is treated as a final response.
+    ///
     /// # Examples
     /// ```
     /// use http_req::response::Response;
@@ -38,12 +76,79 @@ impl Response {
     /// let response = Response::from_head(HEAD).unwrap();
     /// ```
     pub fn from_head(head: &[u8]) -> Result<Response, Error> {
+        Self::from_head_limited(head, Some(MAX_HEADERS))
+    }
+
+    /// Creates new `Response` with head parsed from a slice of bytes, like `from_head`,
+    /// allowing at most `max_headers` headers in each response head. If `None` is provided,
+    /// the number of headers is not limited.
+    ///
+    /// # Examples
+    /// ```
+    /// use http_req::{error::{Error, ParseErr}, response::Response};
+    ///
+    /// const HEAD: &[u8] = b"HTTP/1.1 200 OK\r\n\
+    ///                       Content-Type: text/html\r\n\
+    ///                       Content-Length: 100\r\n\r\n";
+    ///
+    /// let err = Response::from_head_limited(HEAD, Some(1)).unwrap_err();
+    /// assert!(matches!(err, Error::Parse(ParseErr::TooManyHeaders { limit: 1 })));
+    /// ```
+    pub fn from_head_limited(head: &[u8], max_headers: Option<usize>) -> Result<Response, Error> {
+        Self::from_head_with(head, max_headers, false)
+    }
+
+    /// Creates new `Response` like `from_head_limited`. If `lenient` is set, headers are
+    /// parsed with `Headers::parse_lenient`.
+    pub(crate) fn from_head_with(
+        head: &[u8],
+        max_headers: Option<usize>,
+        lenient: bool,
+    ) -> Result<Response, Error> {
+        let mut interim = Vec::new();
+        let mut head = head;
+
+        loop {
+            let pos = find_slice(head, &CR_LF_2).unwrap_or(head.len());
+            let (current, rest) = head.split_at(pos);
+
+            if rest.is_empty() || !is_interim(current) {
+                let mut response = Self::from_single_head(current, max_headers, lenient)?;
+                response.interim = interim;
+
+                return Ok(response);
+            }
+
+            interim.push(Self::from_single_head(current, max_headers, lenient)?);
+            head = rest;
+        }
+    }
+
+    /// Parses status and headers of a single response head.
+    fn from_single_head(
+        head: &[u8],
+        max_headers: Option<usize>,
+        lenient: bool,
+    ) -> Result<Response, Error> {
         let mut head = str::from_utf8(head)?.splitn(2, '\n');
 
         let status = head.next().ok_or(ParseErr::StatusErr)?.parse()?;
-        let headers = head.next().ok_or(ParseErr::HeadersErr)?.parse()?;
+        let headers = head.next().ok_or(ParseErr::HeadersErr)?;
+        let cookies = headers
+            .lines()
+            .filter_map(|line| line.split_once(':'))
+            .filter(|(key, _)| key.trim().eq_ignore_ascii_case("Set-Cookie"))
+            .filter_map(|(_, value)| value.parse().ok())
+            .collect();
+        let headers = Headers::parse_with(headers, max_headers, lenient)?;
 
-        Ok(Response { status, headers })
+        Ok(Response {
+            status,
+            headers,
+            interim: Vec::new(),
+            cookies,
+            peer_certificates: Vec::new(),
+        })
     }
 
     /// Parses `Response` from slice of bytes. Writes it's body to `writer`.
@@ -68,9 +173,15 @@ impl Response {
         if res.is_empty() {
             Err(Error::Parse(ParseErr::Empty))
         } else {
-            let pos = match find_slice(res, &CR_LF_2) {
-                Some(v) => v,
-                None => res.len(),
+            let mut start = 0;
+            let pos = loop {
+                let end = start + find_slice(&res[start..], &CR_LF_2).unwrap_or(res.len() - start);
+
+                if end == res.len() || !is_interim(&res[start..end]) {
+                    break end;
+                }
+
+                start = end;
             };
 
             let response = Self::from_head(&res[..pos])?;
@@ -100,12 +211,27 @@ impl Response {
         self.status.code
     }
 
-    /// Returns HTTP version of this `Response`.
+    /// Returns the whole status line of this `Response`.
     ///
     /// # Examples
     /// ```
     /// use http_req::response::Response;
     ///
+    /// const HEAD: &[u8] = b"HTTP/1.1 404 Not Found\r\n\r\n";
+    ///
+    /// let response = Response::from_head(HEAD).unwrap();
+    /// assert_eq!(response.status().to_string(), "HTTP/1.1 404 Not Found");
+    /// ```
+    pub const fn status(&self) -> &Status {
+        &self.status
+    }
+
+    /// Returns HTTP version of this `Response`.
+    ///
+    /// # Examples
+    /// ```
+    /// use http_req::{request::HttpVersion, response::Response};
+    ///
     /// const RESPONSE: &[u8; 129] = b"HTTP/1.1 200 OK\r\n\
     ///                              Date: Sat, 11 Jan 2003 02:44:04 GMT\r\n\
     ///                              Content-Type: text/html\r\n\
@@ -114,10 +240,10 @@ impl Response {
     /// let mut body = Vec::new();
     ///
     /// let response = Response::try_from(RESPONSE, &mut body).unwrap();
-    /// assert_eq!(response.version(), "HTTP/1.1");
+    /// assert_eq!(response.version(), HttpVersion::Http11);
     /// ```
-    pub fn version(&self) -> &str {
-        &self.status.version
+    pub const fn version(&self) -> HttpVersion {
+        self.status.version
     }
 
     /// Returns reason of this `Response`.
@@ -140,6 +266,65 @@ impl Response {
         &self.status.reason
     }
 
+    /// Combines this `Response` with its `body` into a `FullResponse`.
+    ///
+    /// # Examples
+    /// ```
+    /// use http_req::response::Response;
+    ///
+    /// const HEAD: &[u8] = b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\n";
+    ///
+    /// let response = Response::from_head(HEAD).unwrap().with_body(b"ok".to_vec());
+    /// assert_eq!(response.text(), "ok");
+    /// ```
+    pub fn with_body(self, body: Vec<u8>) -> FullResponse {
+        FullResponse {
+            response: self,
+            body,
+        }
+    }
+
+    /// Returns informational (1xx) responses received before this `Response`.
+    ///
+    /// # Examples
+    /// ```
+    /// use http_req::response::{Response, StatusCode};
+    ///
+    /// const HEAD: &[u8] = b"HTTP/1.1 103 Early Hints\r\n\
+    ///                       Link: </style.css>; rel=preload\r\n\r\n\
+    ///                       HTTP/1.1 200 OK\r\n\
+    ///                       Content-Length: 0\r\n\r\n";
+    ///
+    /// let response = Response::from_head(HEAD).unwrap();
+    /// assert_eq!(response.status_code(), StatusCode::new(200));
+    /// assert_eq!(response.interim()[0].status_code(), StatusCode::new(103));
+    /// ```
+    pub fn interim(&self) -> &[Response] {
+        &self.interim
+    }
+
+    /// Returns cookies parsed from every `Set-Cookie` header of this `Response`.
+    /// Headers which are not valid cookies are skipped.
+    ///
+    /// # Examples
+    /// ```
+    /// use http_req::response::Response;
+    ///
+    /// const HEAD: &[u8] = b"HTTP/1.1 200 OK\r\n\
+    ///                       Set-Cookie: id=a3fWa; Secure\r\n\
+    ///                       Set-Cookie: lang=en; Path=/\r\n\r\n";
+    ///
+    /// let response = Response::from_head(HEAD).unwrap();
+    /// let cookies = response.cookies();
+    ///
+    /// assert_eq!(cookies.len(), 2);
+    /// assert_eq!(cookies[0].name(), "id");
+    /// assert_eq!(cookies[1].value(), "en");
+    /// ```
+    pub fn cookies(&self) -> &[Cookie] {
+        &self.cookies
+    }
+
     /// Returns headers of this `Response`.
     ///
     /// # Examples
@@ -160,6 +345,11 @@ impl Response {
         &self.headers
     }
 
+    /// Returns mutable headers of this `Response`.
+    pub(crate) fn headers_mut(&mut self) -> &mut Headers {
+        &mut self.headers
+    }
+
     /// Returns length of the content of this `Response` as a `Option`, according to information
     /// included in headers. If there is no such an information, returns `None`.
     ///
@@ -179,8 +369,105 @@ impl Response {
     /// ```
     pub fn content_len(&self) -> Option<usize> {
         self.headers()
-            .get("Content-Length")
-            .and_then(|len| len.parse().ok())
+            .content_length()
+            .and_then(|len| usize::try_from(len).ok())
+    }
+
+    /// Returns media type from `Content-Type` header of this `Response`, with its parameters
+    /// (e.g. `charset` or `boundary`). Returns `None` if the header is missing or invalid.
+    ///
+    /// # Examples
+    /// ```
+    /// use http_req::response::Response;
+    ///
+    /// const HEAD: &[u8] = b"HTTP/1.1 200 OK\r\n\
+    ///                       Content-Type: text/html; charset=ISO-8859-1\r\n\r\n";
+    ///
+    /// let response = Response::from_head(HEAD).unwrap();
+    /// let content_type = response.content_type().unwrap();
+    ///
+    /// assert_eq!(content_type.essence(), "text/html");
+    /// assert_eq!(content_type.charset(), Some("ISO-8859-1"));
+    /// ```
+    pub fn content_type(&self) -> Option<MediaType> {
+        self.headers().get("Content-Type")?.parse().ok()
+    }
+
+    /// Decodes `body` of this `Response` to a `String`, according to `charset` parameter
+    /// of `Content-Type` header (UTF-8 if it's missing). Invalid sequences are replaced
+    /// with `U+FFFD`.
+    ///
+    /// Charsets other than UTF-8 are decoded only with `charset` feature. Without it, the body
+    /// is always decoded as UTF-8.
+    ///
+    /// # Examples
+    /// ```
+    /// use http_req::response::Response;
+    ///
+    /// const HEAD: &[u8] = b"HTTP/1.1 200 OK\r\n\
+    ///                       Content-Type: text/plain; charset=utf-8\r\n\r\n";
+    ///
+    /// let response = Response::from_head(HEAD).unwrap();
+    /// assert_eq!(response.text("zażółć".as_bytes()), "zażółć");
+    /// ```
+    pub fn text(&self, body: &[u8]) -> String {
+        #[cfg(feature = "charset")]
+        {
+            let encoding = self
+                .content_type()
+                .and_then(|media_type| {
+                    encoding_rs::Encoding::for_label(media_type.charset()?.as_bytes())
+                })
+                .unwrap_or(encoding_rs::UTF_8);
+
+            encoding.decode(body).0.into_owned()
+        }
+
+        #[cfg(not(feature = "charset"))]
+        String::from_utf8_lossy(body).into_owned()
+    }
+
+    /// Deserializes JSON `body` of this `Response` into `T`. If it fails, the error
+    /// reports the byte offset in `body` where the problem was found.
+    ///
+    /// # Examples
+    /// ```
+    /// use http_req::{error::ParseErr, response::Response};
+    /// use std::collections::HashMap;
+    ///
+    /// const HEAD: &[u8] = b"HTTP/1.1 200 OK\r\n\
+    ///                       Content-Type: application/json\r\n\r\n";
+    ///
+    /// let response = Response::from_head(HEAD).unwrap();
+    ///
+    /// let value: HashMap<String, u32> = response.json(br#"{"a": 1}"#).unwrap();
+    /// assert_eq!(value["a"], 1);
+    ///
+    /// let err = response.json::<HashMap<String, u32>>(br#"{"a": x}"#).unwrap_err();
+    /// assert!(matches!(err, ParseErr::Json { position: 6, .. }));
+    /// ```
+    #[cfg(feature = "serde")]
+    pub fn json<T>(&self, body: &[u8]) -> Result<T, ParseErr>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        serde_json::from_slice(body).map_err(|e| {
+            // serde_json reports 1-based line and column (counted in bytes).
+            let line_start = match e.line() {
+                0 | 1 => 0,
+                line => body
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, &b)| b == b'\n')
+                    .nth(line - 2)
+                    .map_or(body.len(), |(i, _)| i + 1),
+            };
+
+            ParseErr::Json {
+                position: (line_start + e.column().saturating_sub(1)).min(body.len()),
+                message: e.to_string(),
+            }
+        })
     }
 
     /// Checks if Transfer-Encoding includes "chunked".
@@ -190,48 +477,410 @@ impl Response {
             .is_some_and(|encodings| encodings.contains("chunked"))
     }
 
+    /// Returns certificates presented by the server over TLS, starting with its own
+    /// certificate. Empty for responses received without TLS.
+    ///
+    /// With native-tls only the certificate of the server is available, without the rest
+    /// of the chain.
+    ///
+    /// # Examples
+    /// ```
+    /// use http_req::response::Response;
+    ///
+    /// const HEAD: &[u8] = b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n";
+    ///
+    /// let response = Response::from_head(HEAD).unwrap();
+    /// assert!(response.peer_certificates().is_empty());
+    /// ```
+    pub fn peer_certificates(&self) -> &[Certificate] {
+        &self.peer_certificates
+    }
+
+    /// Sets certificates presented by the server.
+    pub(crate) fn set_peer_certificates(&mut self, certs: Vec<Certificate>) {
+        self.peer_certificates = certs;
+    }
+
+    /// Adds trailer headers, received after a chunked body, to headers of this `Response`.
+    /// Fields forbidden in trailers are ignored, so they can't override e.g. framing
+    /// of the body.
+    pub(crate) fn add_trailers(&mut self, trailers: Headers) {
+        for (key, val) in trailers.0 {
+            if !FORBIDDEN_TRAILERS
+                .iter()
+                .any(|name| key == Ascii::new(*name))
+            {
+                self.headers.insert_raw(key.into_inner(), val);
+            }
+        }
+    }
+
     /// Returns basic information about the response as an array, including:
     /// - chunked -> Transfer-Encoding includes "chunked"
-    /// - non-empty -> Content-Length is greater than 0 (or unknown) and method is not HEAD
+    /// - non-empty -> response to `method` has a body (see `framing`)
     pub fn basic_info<'a>(&self, method: &Method) -> [&'a str; 2] {
         let mut params = [""; 2];
-        let content_len = self.content_len().unwrap_or(1);
+        let framing = self.framing(method);
 
-        if self.is_chunked() {
+        if framing.is_chunked() {
             params[0] = "chunked";
         }
 
-        if content_len > 0 && method != &Method::HEAD {
+        if framing.has_body() {
             params[1] = "non-empty";
         }
 
         params
     }
+
+    /// Returns information on how the body of this `Response` to a request with `method`
+    /// is delimited and what length it's expected to have.
+    ///
+    /// Responses to HEAD requests, as well as 1xx, 204 and 304 responses, never have a body,
+    /// even though `Content-Length` may describe the (possibly compressed) representation
+    /// which would be sent in response to GET.
+    ///
+    /// # Examples
+    /// ```
+    /// use http_req::{request::Method, response::Response};
+    ///
+    /// const HEAD: &[u8] = b"HTTP/1.1 200 OK\r\n\
+    ///                       Content-Encoding: gzip\r\n\
+    ///                       Content-Length: 100\r\n\r\n";
+    ///
+    /// let response = Response::from_head(HEAD).unwrap();
+    /// let framing = response.framing(&Method::HEAD);
+    ///
+    /// assert!(!framing.has_body());
+    /// assert_eq!(framing.body_len(), Some(0));
+    /// assert_eq!(framing.encoded_len(), Some(100));
+    /// assert_eq!(framing.decoded_len(), None);
+    /// ```
+    pub fn framing(&self, method: &Method) -> Framing {
+        let code = u16::from(self.status_code());
+        let chunked = self.is_chunked();
+
+        // Content-Length is ignored if Transfer-Encoding is present.
+        let encoded_len = if chunked { None } else { self.content_len() };
+        let decoded_len = match self.headers().get("Content-Encoding") {
+            Some(encoding) if !encoding.trim().eq_ignore_ascii_case("identity") => None,
+            _ => encoded_len,
+        };
+
+        let has_body = method != &Method::HEAD
+            && !(100..200).contains(&code)
+            && code != 204
+            && code != 304
+            && !(method == &Method::CONNECT && self.status_code().is_success())
+            && (chunked || encoded_len != Some(0));
+
+        Framing {
+            has_body,
+            chunked,
+            encoded_len,
+            decoded_len,
+        }
+    }
+
+    /// Checks that framing of the body is unambiguous, i.e. the response doesn't have both
+    /// `Content-Length` and chunked `Transfer-Encoding` and its `Content-Length` is valid
+    /// (which also means it doesn't list different lengths). Such responses may be used
+    /// to smuggle responses through intermediaries.
+    ///
+    /// # Examples
+    /// ```
+    /// use http_req::response::Response;
+    ///
+    /// const HEAD: &[u8] = b"HTTP/1.1 200 OK\r\n\
+    ///                       Content-Length: 10\r\n\
+    ///                       Transfer-Encoding: chunked\r\n\r\n";
+    ///
+    /// let response = Response::from_head(HEAD).unwrap();
+    /// assert!(response.check_framing().is_err());
+    /// ```
+    pub fn check_framing(&self) -> Result<(), ParseErr> {
+        let has_len = self.headers().get("Content-Length").is_some();
+
+        if has_len && (self.is_chunked() || self.headers().content_length().is_none()) {
+            Err(ParseErr::AmbiguousFraming)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Returns information about deprecation of the requested resource, parsed from
+    /// `Deprecation` header. If there is no such a header or it's invalid, returns `None`.
+    ///
+    /// # Examples
+    /// ```
+    /// use http_req::response::{Deprecation, Response};
+    /// use std::time::{Duration, UNIX_EPOCH};
+    ///
+    /// const HEAD: &[u8] = b"HTTP/1.1 200 OK\r\n\
+    ///                       Deprecation: @1688169599\r\n\r\n";
+    ///
+    /// let response = Response::from_head(HEAD).unwrap();
+    /// assert_eq!(
+    ///     response.deprecation(),
+    ///     Some(Deprecation::Since(UNIX_EPOCH + Duration::from_secs(1688169599)))
+    /// );
+    /// ```
+    pub fn deprecation(&self) -> Option<Deprecation> {
+        self.headers().get("Deprecation")?.parse().ok()
+    }
+
+    /// Returns the time at which the requested resource is expected to become unavailable,
+    /// parsed from `Sunset` header. If there is no such a header or it's invalid, returns `None`.
+    ///
+    /// # Examples
+    /// ```
+    /// use http_req::response::Response;
+    /// use std::time::{Duration, UNIX_EPOCH};
+    ///
+    /// const HEAD: &[u8] = b"HTTP/1.1 200 OK\r\n\
+    ///                       Sunset: Sat, 31 Dec 2044 23:59:59 GMT\r\n\r\n";
+    ///
+    /// let response = Response::from_head(HEAD).unwrap();
+    /// assert_eq!(response.sunset(), Some(UNIX_EPOCH + Duration::from_secs(2366841599)));
+    /// ```
+    pub fn sunset(&self) -> Option<SystemTime> {
+        parse_http_date(self.headers().get("Sunset")?)
+    }
+
+    /// Returns warnings parsed from `Warning` header. Invalid entries are skipped.
+    ///
+    /// # Examples
+    /// ```
+    /// use http_req::response::Response;
+    ///
+    /// const HEAD: &[u8] = b"HTTP/1.1 200 OK\r\n\
+    ///                       Warning: 299 api.example.com \"Deprecated API\"\r\n\r\n";
+    ///
+    /// let response = Response::from_head(HEAD).unwrap();
+    /// let warnings = response.warnings();
+    ///
+    /// assert_eq!(warnings[0].code(), 299);
+    /// assert_eq!(warnings[0].text(), "Deprecated API");
+    /// ```
+    pub fn warnings(&self) -> Vec<Warning> {
+        match self.headers().get("Warning") {
+            Some(value) => split_list(value)
+                .into_iter()
+                .filter_map(|warning| warning.parse().ok())
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+    /// Returns links parsed from `Link` header. Invalid entries are skipped.
+    ///
+    /// # Examples
+    /// ```
+    /// use http_req::response::Response;
+    ///
+    /// const HEAD: &[u8] = b"HTTP/1.1 200 OK\r\n\
+    ///                       Link: <https://api.github.com/user/repos?page=3>; rel=\"next\", \
+    ///                       <https://api.github.com/user/repos?page=50>; rel=\"last\"\r\n\r\n";
+    ///
+    /// let response = Response::from_head(HEAD).unwrap();
+    /// let links = response.links();
+    ///
+    /// assert_eq!(links.len(), 2);
+    /// assert_eq!(links[1].uri(), "https://api.github.com/user/repos?page=50");
+    /// ```
+    pub fn links(&self) -> Vec<Link> {
+        match self.headers().get("Link") {
+            Some(value) => split_list(value)
+                .into_iter()
+                .filter_map(|link| link.parse().ok())
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Returns URI of the first link from `Link` header with relation type `rel`
+    /// (e.g. `next`, `prev` or `last`, used for pagination).
+    ///
+    /// # Examples
+    /// ```
+    /// use http_req::response::Response;
+    ///
+    /// const HEAD: &[u8] = b"HTTP/1.1 200 OK\r\n\
+    ///                       Link: </items?page=1>; rel=prev, </items?page=3>; rel=next\r\n\r\n";
+    ///
+    /// let response = Response::from_head(HEAD).unwrap();
+    ///
+    /// assert_eq!(response.link("next").as_deref(), Some("/items?page=3"));
+    /// assert_eq!(response.link("last"), None);
+    /// ```
+    pub fn link(&self, rel: &str) -> Option<String> {
+        self.links()
+            .into_iter()
+            .find(|link| link.has_rel(rel))
+            .map(|link| link.uri)
+    }
+    /// Returns name of the file suggested by `Content-Disposition` header. `filename*`
+    /// parameter (RFC 5987, in UTF-8 or ISO-8859-1) takes precedence over `filename`.
+    ///
+    /// Only the last component of the name is returned, so it can't point outside of
+    /// the directory it's saved to. Returns `None` if there is no valid name.
+    ///
+    /// # Examples
+    /// ```
+    /// use http_req::response::Response;
+    ///
+    /// const HEAD: &[u8] = b"HTTP/1.1 200 OK\r\n\
+    ///                       Content-Disposition: attachment; filename=\"EURO rates.txt\"; \
+    ///                       filename*=UTF-8''%e2%82%ac%20rates.txt\r\n\r\n";
+    ///
+    /// let response = Response::from_head(HEAD).unwrap();
+    /// assert_eq!(response.filename().as_deref(), Some("€ rates.txt"));
+    /// ```
+    pub fn filename(&self) -> Option<String> {
+        let value = self.headers().get("Content-Disposition")?;
+        let (kind, params) = value.split_once(';').unwrap_or((value, ""));
+
+        if !is_token(kind.trim()) {
+            return None;
+        }
+
+        let params = parse_params(params).ok()?;
+        let param = |name: &str| {
+            params
+                .iter()
+                .find(|(key, _)| key == name)
+                .map(|(_, val)| val.as_str())
+        };
+
+        let filename = param("filename*")
+            .and_then(decode_ext_value)
+            .or_else(|| param("filename").map(str::to_string))?;
+        let filename = filename.rsplit(['/', '\\']).next()?.trim();
+
+        match filename {
+            "" | "." | ".." => None,
+            _ => Some(filename.to_string()),
+        }
+    }
+}
+
+/// Response together with its body, which owns both of them. Unlike `Response`, it doesn't
+/// require a separate writer for the body, so it can be easily stored or sent to another thread.
+///
+/// # Examples
+/// ```
+/// use http_req::response::{Response, StatusCode};
+///
+/// const RESPONSE: &[u8] = b"HTTP/1.1 200 OK\r\n\
+///                           Content-Length: 5\r\n\r\n\
+///                           hello";
+/// let mut body = Vec::new();
+///
+/// let response = Response::try_from(RESPONSE, &mut body).unwrap().with_body(body);
+/// assert_eq!(response.status_code(), StatusCode::new(200));
+/// assert_eq!(response.body(), b"hello");
+///
+/// let (response, body) = response.into_parts();
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+pub struct FullResponse {
+    response: Response,
+    body: Vec<u8>,
+}
+
+impl FullResponse {
+    /// Returns status code of the response.
+    pub const fn status_code(&self) -> StatusCode {
+        self.response.status_code()
+    }
+
+    /// Returns headers of the response.
+    pub const fn headers(&self) -> &Headers {
+        self.response.headers()
+    }
+
+    /// Returns the response, without its body.
+    pub const fn response(&self) -> &Response {
+        &self.response
+    }
+
+    /// Returns body of the response.
+    pub fn body(&self) -> &[u8] {
+        &self.body
+    }
+
+    /// Decodes body of the response to a `String` (see `Response::text`).
+    pub fn text(&self) -> String {
+        self.response.text(&self.body)
+    }
+
+    /// Deserializes JSON body of the response into `T` (see `Response::json`).
+    #[cfg(feature = "serde")]
+    pub fn json<T>(&self) -> Result<T, ParseErr>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        self.response.json(&self.body)
+    }
+
+    /// Splits this `FullResponse` into the response and its body.
+    pub fn into_parts(self) -> (Response, Vec<u8>) {
+        (self.response, self.body)
+    }
 }
 
 /// Status of HTTP response
 #[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Status {
-    version: String,
+    version: HttpVersion,
     code: StatusCode,
     reason: String,
 }
 
 impl Status {
-    pub fn new(version: &str, code: StatusCode, reason: &str) -> Status {
+    pub fn new(version: HttpVersion, code: StatusCode, reason: &str) -> Status {
         Status::from((version, code, reason))
     }
+
+    /// Returns HTTP version from the status line.
+    ///
+    /// # Examples
+    /// ```
+    /// use http_req::{request::HttpVersion, response::Status};
+    ///
+    /// let status: Status = "HTTP/1.0 404 Not Found".parse().unwrap();
+    /// assert_eq!(status.version(), HttpVersion::Http10);
+    /// ```
+    pub const fn version(&self) -> HttpVersion {
+        self.version
+    }
+
+    /// Returns status code from the status line.
+    pub const fn code(&self) -> StatusCode {
+        self.code
+    }
+
+    /// Returns reason phrase from the status line.
+    pub fn reason(&self) -> &str {
+        &self.reason
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {} {}", self.version, self.code, self.reason)
+    }
 }
 
 impl<T, U, V> From<(T, U, V)> for Status
 where
-    T: ToString,
+    HttpVersion: From<T>,
     V: ToString,
     StatusCode: From<U>,
 {
     fn from(status: (T, U, V)) -> Status {
         Status {
-            version: status.0.to_string(),
+            version: HttpVersion::from(status.0),
             code: StatusCode::from(status.1),
             reason: status.2.to_string(),
         }
@@ -244,7 +893,11 @@ impl str::FromStr for Status {
     fn from_str(status_line: &str) -> Result<Status, Self::Err> {
         let mut status_line = status_line.trim().splitn(3, ' ');
 
-        let version = status_line.next().ok_or(ParseErr::StatusErr)?;
+        let version: HttpVersion = status_line
+            .next()
+            .ok_or(ParseErr::StatusErr)?
+            .parse()
+            .map_err(|_| ParseErr::StatusErr)?;
         let code: StatusCode = status_line.next().ok_or(ParseErr::StatusErr)?.parse()?;
         let reason = match status_line.next() {
             Some(reason) => reason,
@@ -269,149 +922,408 @@ impl str::FromStr for Status {
 #[derive(Debug, PartialEq, Clone, Default)]
 pub struct Headers(HashMap<Ascii<String>, String>);
 
-impl Headers {
-    /// Creates an empty `Headers`.
-    ///
-    /// The headers are initially created with a capacity of 0, so they will not allocate until
-    /// it is first inserted into.
+impl Headers {
+    /// Creates an empty `Headers`.
+    ///
+    /// The headers are initially created with a capacity of 0, so they will not allocate until
+    /// it is first inserted into.
+    ///
+    /// # Examples
+    /// ```
+    /// use http_req::response::Headers;
+    ///
+    /// let mut headers = Headers::new();
+    /// ```
+    pub fn new() -> Headers {
+        Headers(HashMap::new())
+    }
+
+    /// Creates empty `Headers` with the specified capacity.
+    ///
+    /// The headers will be able to hold at least capacity elements without reallocating.
+    /// If capacity is 0, the headers will not allocate.
+    ///
+    /// # Examples
+    /// ```
+    /// use http_req::response::Headers;
+    ///
+    /// let mut headers = Headers::with_capacity(200);
+    /// ```
+    pub fn with_capacity(capacity: usize) -> Headers {
+        Headers(HashMap::with_capacity(capacity))
+    }
+
+    /// An iterator visiting all key-value pairs in arbitrary order.
+    /// The iterator's element type is `(&Ascii<String>, &String)`.
+    ///
+    /// # Examples
+    /// ```
+    /// use http_req::response::Headers;
+    ///
+    /// let mut headers = Headers::new();
+    /// headers.insert("Accept-Charset", "utf-8");
+    /// headers.insert("Accept-Language", "en-US");
+    /// headers.insert("Connection", "Close");
+    ///
+    /// let mut iterator = headers.iter();
+    /// ```
+    pub fn iter(&self) -> hash_map::Iter<'_, Ascii<String>, String> {
+        self.0.iter()
+    }
+
+    /// Returns a reference to the value corresponding to the key.
+    ///
+    /// # Examples
+    /// ```
+    /// use http_req::response::Headers;
+    ///
+    /// let mut headers = Headers::new();
+    /// headers.insert("Accept-Charset", "utf-8");
+    ///
+    /// assert_eq!(headers.get("Accept-Charset"), Some(&"utf-8".to_string()))
+    /// ```
+    pub fn get<T: ToString + ?Sized>(&self, k: &T) -> Option<&std::string::String> {
+        self.0.get(&Ascii::new(k.to_string()))
+    }
+
+    /// Inserts a key-value pair into the headers.
+    ///
+    /// If the headers did not have this key present, None is returned.
+    ///
+    /// If the headers did have this key present, the value is updated, and the old value is returned.
+    /// The key is not updated, though; this matters for types that can be == without being identical.
+    ///
+    /// # Examples
+    /// ```
+    /// use http_req::response::Headers;
+    ///
+    /// let mut headers = Headers::new();
+    /// headers.insert("Accept-Language", "en-US");
+    /// ```
+    pub fn insert<T, U>(&mut self, key: &T, val: &U) -> Option<String>
+    where
+        T: ToString + ?Sized,
+        U: ToString + ?Sized,
+    {
+        self.0.insert(Ascii::new(key.to_string()), val.to_string())
+    }
+
+    /// Inserts a key-value pair into the headers, if both of them are valid. Names must be
+    /// tokens and values mustn't contain control characters other than horizontal tab,
+    /// so that e.g. `\r\n` can't be used to inject additional headers.
+    ///
+    /// `insert` doesn't validate headers, but requests containing invalid ones fail
+    /// to be sent.
+    ///
+    /// # Examples
+    /// ```
+    /// use http_req::{error::ParseErr, response::Headers};
+    ///
+    /// let mut headers = Headers::new();
+    ///
+    /// assert!(headers.try_insert("Accept-Language", "en-US").is_ok());
+    /// assert_eq!(
+    ///     headers.try_insert("Accept-Language", "en-US\r\nCookie: a=b"),
+    ///     Err(ParseErr::InvalidHeader { name: "Accept-Language".to_string() })
+    /// );
+    /// ```
+    pub fn try_insert<T, U>(&mut self, key: &T, val: &U) -> Result<Option<String>, ParseErr>
+    where
+        T: ToString + ?Sized,
+        U: ToString + ?Sized,
+    {
+        let (key, val) = (key.to_string(), val.to_string());
+        check_header(&key, &val)?;
+
+        Ok(self.insert_raw(key, val))
+    }
+
+    /// Checks that names and values of all headers are valid (see `try_insert`).
+    ///
+    /// # Examples
+    /// ```
+    /// use http_req::response::Headers;
+    ///
+    /// let mut headers = Headers::new();
+    /// headers.insert("Accept-Language", "en-US");
+    /// assert!(headers.validate().is_ok());
+    ///
+    /// headers.insert("Accept Language", "en-US");
+    /// assert!(headers.validate().is_err());
+    /// ```
+    pub fn validate(&self) -> Result<(), ParseErr> {
+        self.iter()
+            .try_for_each(|(key, val)| check_header(key, val))
+    }
+
+    /// Inserts key-value pair into the headers and takes ownership over them.
+    ///
+    /// If the headers did not have this key present, None is returned.
+    ///
+    /// If the headers did have this key present, the value is updated, and the old value is returned.
+    /// The key is not updated, though; this matters for types that can be == without being identical.
+    ///
+    /// # Examples
+    /// ```
+    /// use http_req::response::Headers;
+    ///
+    /// let mut headers = Headers::new();
+    /// headers.insert_raw("Accept-Language".to_string(), "en-US".to_string());
+    /// ```
+    pub fn insert_raw(&mut self, key: String, val: String) -> Option<String> {
+        self.0.insert(Ascii::new(key), val)
+    }
+
+    /// Removes a key from the headers, returning its value if the key was present.
+    ///
+    /// # Examples
+    /// ```
+    /// use http_req::response::Headers;
+    ///
+    /// let mut headers = Headers::new();
+    /// headers.insert("Cookie", "a=b");
+    ///
+    /// assert_eq!(headers.remove("Cookie"), Some("a=b".to_string()));
+    /// assert_eq!(headers.get("Cookie"), None);
+    /// ```
+    pub fn remove<T: ToString + ?Sized>(&mut self, k: &T) -> Option<String> {
+        self.0.remove(&Ascii::new(k.to_string()))
+    }
+
+    /// Creates default headers for a HTTP request
+    ///
+    /// # Examples
+    /// ```
+    /// use http_req::{response::Headers, uri::Uri};
+    /// use std::convert::TryFrom;
+    ///
+    /// let uri: Uri = Uri::try_from("https://www.rust-lang.org/learn").unwrap();
+    /// let headers = Headers::default_http(&uri);
+    /// ```
+    pub fn default_http(uri: &Uri) -> Headers {
+        let mut headers = Headers::with_capacity(10);
+        headers.insert("Host", &uri.host_header().unwrap_or_default());
+        headers.insert(
+            "User-Agent",
+            concat!("http_req/", env!("CARGO_PKG_VERSION")),
+        );
+
+        headers
+    }
+
+    /// Parses headers from a string, allowing at most `max_headers` of them.
+    /// If `None` is provided, the number of headers is not limited.
     ///
     /// # Examples
     /// ```
-    /// use http_req::response::Headers;
+    /// use http_req::{error::ParseErr, response::Headers};
     ///
-    /// let mut headers = Headers::new();
+    /// const HEADERS: &str = "Content-Type: text/html\r\nContent-Length: 100\r\n";
+    ///
+    /// assert!(Headers::parse_limited(HEADERS, Some(2)).is_ok());
+    /// assert_eq!(
+    ///     Headers::parse_limited(HEADERS, Some(1)),
+    ///     Err(ParseErr::TooManyHeaders { limit: 1 })
+    /// );
     /// ```
-    pub fn new() -> Headers {
-        Headers(HashMap::new())
+    pub fn parse_limited(s: &str, max_headers: Option<usize>) -> Result<Headers, ParseErr> {
+        Headers::parse_with(s, max_headers, false)
     }
 
-    /// Creates empty `Headers` with the specified capacity.
-    ///
-    /// The headers will be able to hold at least capacity elements without reallocating.
-    /// If capacity is 0, the headers will not allocate.
+    /// Parses headers from a string like `parse_limited`, but tolerates mistakes made
+    /// by some legacy servers:
+    /// - obsolete line folding: continuation lines starting with a space or a tab are
+    ///   appended to the value of the previous header,
+    /// - whitespace between the name of a header and the colon,
+    /// - lines without a colon or with an invalid name, which are skipped.
     ///
     /// # Examples
     /// ```
     /// use http_req::response::Headers;
     ///
-    /// let mut headers = Headers::with_capacity(200);
+    /// const HEADERS: &str = "Content-Type :text/html\r\n\
+    ///                        X-Folded: first\r\n  second\r\n\
+    ///                        garbage\r\n";
+    ///
+    /// let headers = Headers::parse_lenient(HEADERS, None).unwrap();
+    ///
+    /// assert_eq!(headers.get("Content-Type"), Some(&"text/html".to_string()));
+    /// assert_eq!(headers.get("X-Folded"), Some(&"first second".to_string()));
+    /// assert!(HEADERS.parse::<Headers>().is_err());
     /// ```
-    pub fn with_capacity(capacity: usize) -> Headers {
-        Headers(HashMap::with_capacity(capacity))
+    pub fn parse_lenient(s: &str, max_headers: Option<usize>) -> Result<Headers, ParseErr> {
+        Headers::parse_with(s, max_headers, true)
+    }
+
+    /// Parses headers, strictly or leniently (see `parse_lenient`).
+    pub(crate) fn parse_with(
+        s: &str,
+        max_headers: Option<usize>,
+        lenient: bool,
+    ) -> Result<Headers, ParseErr> {
+        let mut fields: Vec<(&str, String)> = Vec::new();
+
+        for line in s.trim_end().lines() {
+            if line.starts_with([' ', '\t']) {
+                match fields.last_mut() {
+                    Some((_, value)) if lenient => {
+                        let line = line.trim();
+
+                        if !line.is_empty() {
+                            value.push(' ');
+                            value.push_str(line);
+                        }
+                    }
+                    _ if lenient => (),
+                    _ => return Err(ParseErr::HeadersErr),
+                }
+
+                continue;
+            }
+
+            let (key, value) = match line.split_once(':') {
+                Some((key, value)) if lenient => (key.trim(), value),
+                Some((key, value)) => (key, value),
+                None if lenient => continue,
+                None => return Err(ParseErr::HeadersErr),
+            };
+
+            if !is_token(key) {
+                if lenient {
+                    continue;
+                }
+
+                return Err(ParseErr::HeadersErr);
+            }
+
+            if let Some(limit) = max_headers.filter(|limit| fields.len() >= *limit) {
+                return Err(ParseErr::TooManyHeaders { limit });
+            }
+
+            fields.push((key, value.trim().to_string()));
+        }
+
+        let mut headers = Headers::with_capacity(fields.len());
+        for (key, value) in fields {
+            // Repeated lengths are kept, so that conflicting ones can be detected.
+            let value = match headers.get(key) {
+                Some(prev) if key.eq_ignore_ascii_case("Content-Length") => {
+                    format!("{}, {}", prev, value)
+                }
+                _ => value,
+            };
+
+            headers.insert_raw(key.to_string(), value);
+        }
+
+        Ok(headers)
     }
 
-    /// An iterator visiting all key-value pairs in arbitrary order.
-    /// The iterator's element type is `(&Ascii<String>, &String)`.
+    /// Returns value of `Content-Length` header. Returns `None` if it's missing or invalid,
+    /// i.e. isn't a number or lists different lengths.
     ///
     /// # Examples
     /// ```
     /// use http_req::response::Headers;
     ///
     /// let mut headers = Headers::new();
-    /// headers.insert("Accept-Charset", "utf-8");
-    /// headers.insert("Accept-Language", "en-US");
-    /// headers.insert("Connection", "Close");
+    /// headers.insert("Content-Length", "100");
     ///
-    /// let mut iterator = headers.iter();
+    /// assert_eq!(headers.content_length(), Some(100));
     /// ```
-    pub fn iter(&self) -> hash_map::Iter<Ascii<String>, String> {
-        self.0.iter()
+    pub fn content_length(&self) -> Option<u64> {
+        let mut lengths = self.get("Content-Length")?.split(',').map(|len| {
+            let len = len.trim();
+
+            if !len.is_empty() && len.bytes().all(|b| b.is_ascii_digit()) {
+                len.parse().ok()
+            } else {
+                None
+            }
+        });
+
+        let first = lengths.next()??;
+        lengths.try_fold(first, |first, len| (len? == first).then_some(first))
     }
 
-    /// Returns a reference to the value corresponding to the key.
+    /// Returns media type from `Content-Type` header (e.g. `text/html`), without
+    /// parameters. Returns `None` if it's missing or isn't in format `type/subtype`.
     ///
     /// # Examples
     /// ```
     /// use http_req::response::Headers;
     ///
     /// let mut headers = Headers::new();
-    /// headers.insert("Accept-Charset", "utf-8");
+    /// headers.insert("Content-Type", "text/html; charset=utf-8");
     ///
-    /// assert_eq!(headers.get("Accept-Charset"), Some(&"utf-8".to_string()))
+    /// assert_eq!(headers.content_type(), Some("text/html"));
     /// ```
-    pub fn get<T: ToString + ?Sized>(&self, k: &T) -> Option<&std::string::String> {
-        self.0.get(&Ascii::new(k.to_string()))
+    pub fn content_type(&self) -> Option<&str> {
+        let media_type = self.get("Content-Type")?.split(';').next()?.trim();
+        let (kind, subtype) = media_type.split_once('/')?;
+
+        (is_token(kind) && is_token(subtype)).then_some(media_type)
     }
 
-    /// Inserts a key-value pair into the headers.
-    ///
-    /// If the headers did not have this key present, None is returned.
-    ///
-    /// If the headers did have this key present, the value is updated, and the old value is returned.
-    /// The key is not updated, though; this matters for types that can be == without being identical.
+    /// Returns value of `Location` header. Returns `None` if it's missing or empty.
     ///
     /// # Examples
     /// ```
     /// use http_req::response::Headers;
     ///
     /// let mut headers = Headers::new();
-    /// headers.insert("Accept-Language", "en-US");
+    /// headers.insert("Location", "/learn");
+    ///
+    /// assert_eq!(headers.location(), Some("/learn"));
     /// ```
-    pub fn insert<T, U>(&mut self, key: &T, val: &U) -> Option<String>
-    where
-        T: ToString + ?Sized,
-        U: ToString + ?Sized,
-    {
-        self.0.insert(Ascii::new(key.to_string()), val.to_string())
+    pub fn location(&self) -> Option<&str> {
+        let location = self.get("Location")?.trim();
+        let valid = !location.is_empty() && !location.bytes().any(|b| b.is_ascii_whitespace());
+
+        valid.then_some(location)
     }
 
-    /// Inserts key-value pair into the headers and takes ownership over them.
-    ///
-    /// If the headers did not have this key present, None is returned.
-    ///
-    /// If the headers did have this key present, the value is updated, and the old value is returned.
-    /// The key is not updated, though; this matters for types that can be == without being identical.
+    /// Returns entity tag parsed from `ETag` header. Returns `None` if it's missing or invalid.
     ///
     /// # Examples
     /// ```
     /// use http_req::response::Headers;
     ///
     /// let mut headers = Headers::new();
-    /// headers.insert_raw("Accept-Language".to_string(), "en-US".to_string());
+    /// headers.insert("ETag", "W/\"xyzzy\"");
+    ///
+    /// let etag = headers.etag().unwrap();
+    /// assert!(etag.is_weak());
+    /// assert_eq!(etag.tag(), "xyzzy");
     /// ```
-    pub fn insert_raw(&mut self, key: String, val: String) -> Option<String> {
-        self.0.insert(Ascii::new(key), val)
+    pub fn etag(&self) -> Option<EntityTag> {
+        self.get("ETag")?.parse().ok()
     }
 
-    /// Creates default headers for a HTTP request
+    /// Returns time parsed from `Last-Modified` header. Returns `None` if it's missing
+    /// or invalid.
     ///
     /// # Examples
     /// ```
-    /// use http_req::{response::Headers, uri::Uri};
-    /// use std::convert::TryFrom;
+    /// use http_req::response::Headers;
+    /// use std::time::{Duration, UNIX_EPOCH};
     ///
-    /// let uri: Uri = Uri::try_from("https://www.rust-lang.org/learn").unwrap();
-    /// let headers = Headers::default_http(&uri);
+    /// let mut headers = Headers::new();
+    /// headers.insert("Last-Modified", "Sun, 06 Nov 1994 08:49:37 GMT");
+    ///
+    /// assert_eq!(headers.last_modified(), Some(UNIX_EPOCH + Duration::from_secs(784111777)));
     /// ```
-    pub fn default_http(uri: &Uri) -> Headers {
-        let mut headers = Headers::with_capacity(10);
-        headers.insert("Host", &uri.host_header().unwrap_or_default());
-        headers.insert("User-Agent", "http_req/0.13.0");
-
-        headers
+    pub fn last_modified(&self) -> Option<SystemTime> {
+        parse_http_date(self.get("Last-Modified")?)
     }
 }
 
 impl str::FromStr for Headers {
     type Err = ParseErr;
 
+    /// Parses headers, allowing at most `MAX_HEADERS` of them.
     fn from_str(s: &str) -> Result<Headers, ParseErr> {
-        let headers = s.trim();
-
-        if headers.lines().all(|e| e.contains(':')) {
-            let headers = headers
-                .lines()
-                .map(|elem| {
-                    let idx = elem.find(':').unwrap();
-                    let (key, value) = elem.split_at(idx);
-                    (Ascii::new(key.to_string()), value[1..].trim().to_string())
-                })
-                .collect();
-
-            Ok(Headers(headers))
-        } else {
-            Err(ParseErr::HeadersErr)
-        }
+        Headers::parse_limited(s, Some(MAX_HEADERS))
     }
 }
 
@@ -427,6 +1339,31 @@ impl From<Headers> for HashMap<Ascii<String>, String> {
     }
 }
 
+/// Serializes headers as a map from names to values, sorted by names.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Headers {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut headers: Vec<_> = self.iter().collect();
+        headers.sort_by_key(|(key, _)| *key);
+
+        serializer.collect_map(headers.into_iter().map(|(key, val)| (key.as_ref(), val)))
+    }
+}
+
+/// Deserializes headers from a map from names to values.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Headers {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Headers, D::Error> {
+        let map = HashMap::<String, String>::deserialize(deserializer)?;
+
+        Ok(Headers(
+            map.into_iter()
+                .map(|(key, val)| (Ascii::new(key), val))
+                .collect(),
+        ))
+    }
+}
+
 impl fmt::Display for Headers {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let headers: String = self
@@ -448,6 +1385,11 @@ impl fmt::Display for Headers {
 /// assert!(code.is_success())
 /// ```
 #[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
 pub struct StatusCode(u16);
 
 impl StatusCode {
@@ -615,38 +1557,614 @@ impl StatusCode {
             _ => "",
         };
 
-        if !reason.is_empty() {
-            Some(reason)
-        } else {
-            None
+        if !reason.is_empty() {
+            Some(reason)
+        } else {
+            None
+        }
+    }
+}
+
+impl From<StatusCode> for u16 {
+    fn from(code: StatusCode) -> Self {
+        code.0
+    }
+}
+
+impl From<u16> for StatusCode {
+    fn from(code: u16) -> Self {
+        StatusCode(code)
+    }
+}
+
+impl fmt::Display for StatusCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl str::FromStr for StatusCode {
+    type Err = ParseErr;
+
+    fn from_str(s: &str) -> Result<StatusCode, ParseErr> {
+        Ok(StatusCode::new(s.parse()?))
+    }
+}
+
+/// Framing of the body of a message (usually a response), as described by its status
+/// and headers.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Framing {
+    has_body: bool,
+    chunked: bool,
+    encoded_len: Option<usize>,
+    decoded_len: Option<usize>,
+}
+
+impl Framing {
+    /// Returns framing of the body of a request with `headers` (RFC 9112, section 6.3).
+    /// Requests without `Content-Length` or `Transfer-Encoding` have no body.
+    /// Fails if the framing is ambiguous, like `Response::check_framing`.
+    pub(crate) fn of_request(headers: &Headers) -> Result<Framing, ParseErr> {
+        let chunked = headers
+            .get("Transfer-Encoding")
+            .is_some_and(|encodings| encodings.contains("chunked"));
+
+        let encoded_len = match headers.get("Content-Length") {
+            Some(_) if chunked => return Err(ParseErr::AmbiguousFraming),
+            Some(_) => {
+                let len = headers.content_length().ok_or(ParseErr::AmbiguousFraming)?;
+                Some(usize::try_from(len).map_err(|_| ParseErr::Invalid)?)
+            }
+            None if chunked => None,
+            None => Some(0),
+        };
+
+        let decoded_len = match headers.get("Content-Encoding") {
+            Some(encoding) if !encoding.trim().eq_ignore_ascii_case("identity") => None,
+            _ => encoded_len,
+        };
+
+        Ok(Framing {
+            has_body: chunked || encoded_len != Some(0),
+            chunked,
+            encoded_len,
+            decoded_len,
+        })
+    }
+
+    /// Checks if any body follows the head of the response.
+    pub const fn has_body(&self) -> bool {
+        self.has_body
+    }
+
+    /// Checks if the body is sent using chunked transfer coding.
+    pub const fn is_chunked(&self) -> bool {
+        self.chunked
+    }
+
+    /// Returns number of bytes of the body expected on the wire.
+    /// Returns `None` if the body is delimited by chunked transfer coding or by closing the connection.
+    pub const fn body_len(&self) -> Option<usize> {
+        match (self.has_body, self.chunked) {
+            (false, _) => Some(0),
+            (true, true) => None,
+            (true, false) => self.encoded_len,
+        }
+    }
+
+    /// Returns length of the representation with content codings (e.g. gzip) applied,
+    /// as declared by `Content-Length`.
+    pub const fn encoded_len(&self) -> Option<usize> {
+        self.encoded_len
+    }
+
+    /// Returns length of the representation after removing content codings.
+    /// Known only if no content coding (other than identity) is applied.
+    pub const fn decoded_len(&self) -> Option<usize> {
+        self.decoded_len
+    }
+}
+
+/// Deprecation of a resource, as announced by `Deprecation` header.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Deprecation {
+    /// Resource is deprecated, but the date of deprecation is unknown.
+    Deprecated,
+    /// Resource is (or will be) deprecated since the given time.
+    Since(SystemTime),
+}
+
+impl str::FromStr for Deprecation {
+    type Err = ParseErr;
+
+    /// Parses `Deprecation` header value in format defined by RFC 9745 (`@<unix time>`).
+    /// Values used by earlier drafts of the specification (`true` and HTTP-date)
+    /// are accepted as well.
+    fn from_str(s: &str) -> Result<Deprecation, ParseErr> {
+        let s = s.trim();
+
+        if let Some(secs) = s.strip_prefix('@') {
+            let secs: i64 = secs.parse()?;
+            let time = if secs >= 0 {
+                UNIX_EPOCH.checked_add(Duration::from_secs(secs as u64))
+            } else {
+                UNIX_EPOCH.checked_sub(Duration::from_secs(secs.unsigned_abs()))
+            };
+
+            time.map(Deprecation::Since).ok_or(ParseErr::Invalid)
+        } else if s == "true" {
+            Ok(Deprecation::Deprecated)
+        } else {
+            parse_http_date(s)
+                .map(Deprecation::Since)
+                .ok_or(ParseErr::Invalid)
+        }
+    }
+}
+
+/// Single warning from `Warning` header.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Warning {
+    code: u16,
+    agent: String,
+    text: String,
+    date: Option<SystemTime>,
+}
+
+impl Warning {
+    /// Returns the three-digit warning code.
+    pub fn code(&self) -> u16 {
+        self.code
+    }
+
+    /// Returns the name or pseudonym of the server adding the warning.
+    pub fn agent(&self) -> &str {
+        &self.agent
+    }
+
+    /// Returns the warning text.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Returns the date of the warning, if included.
+    pub fn date(&self) -> Option<SystemTime> {
+        self.date
+    }
+}
+
+impl str::FromStr for Warning {
+    type Err = ParseErr;
+
+    /// Parses single warning in format `<code> <agent> "<text>" ["<date>"]`.
+    fn from_str(s: &str) -> Result<Warning, ParseErr> {
+        let s = s.trim();
+        let (code, rest) = s.split_once(' ').ok_or(ParseErr::Invalid)?;
+        let (agent, rest) = rest.trim_start().split_once(' ').ok_or(ParseErr::Invalid)?;
+
+        if code.len() != 3 {
+            return Err(ParseErr::Invalid);
+        }
+
+        let (text, rest) = parse_quoted(rest.trim_start()).ok_or(ParseErr::Invalid)?;
+        let rest = rest.trim();
+
+        let date = if rest.is_empty() {
+            None
+        } else {
+            match parse_quoted(rest) {
+                Some((date, "")) => Some(parse_http_date(&date).ok_or(ParseErr::Invalid)?),
+                _ => return Err(ParseErr::Invalid),
+            }
+        };
+
+        Ok(Warning {
+            code: code.parse()?,
+            agent: agent.to_string(),
+            text,
+            date,
+        })
+    }
+}
+
+/// Entity tag, as sent in `ETag` header.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct EntityTag {
+    weak: bool,
+    tag: String,
+}
+
+impl EntityTag {
+    /// Creates a new `EntityTag`. Returns `None` if `tag` contains characters not allowed
+    /// in entity tags, e.g. `"`.
+    ///
+    /// # Examples
+    /// ```
+    /// use http_req::response::EntityTag;
+    ///
+    /// let etag = EntityTag::new(false, "xyzzy").unwrap();
+    /// assert_eq!(etag.to_string(), "\"xyzzy\"");
+    /// ```
+    pub fn new(weak: bool, tag: &str) -> Option<EntityTag> {
+        let valid = tag
+            .bytes()
+            .all(|b| b == 0x21 || (0x23..0x7f).contains(&b) || b >= 0x80);
+
+        valid.then(|| EntityTag {
+            weak,
+            tag: tag.to_string(),
+        })
+    }
+
+    /// Checks if the tag is weak (`W/` prefix).
+    pub const fn is_weak(&self) -> bool {
+        self.weak
+    }
+
+    /// Returns the opaque tag, without quotes.
+    pub fn tag(&self) -> &str {
+        &self.tag
+    }
+}
+
+impl str::FromStr for EntityTag {
+    type Err = ParseErr;
+
+    /// Parses entity tag in format `"<tag>"` or `W/"<tag>"`.
+    fn from_str(s: &str) -> Result<EntityTag, ParseErr> {
+        let s = s.trim();
+        let (weak, quoted) = match s.strip_prefix("W/") {
+            Some(quoted) => (true, quoted),
+            None => (false, s),
+        };
+
+        let tag = quoted
+            .strip_prefix('"')
+            .and_then(|tag| tag.strip_suffix('"'))
+            .ok_or(ParseErr::Invalid)?;
+
+        EntityTag::new(weak, tag).ok_or(ParseErr::Invalid)
+    }
+}
+
+impl fmt::Display for EntityTag {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.weak {
+            f.write_str("W/")?;
+        }
+
+        write!(f, "\"{}\"", self.tag)
+    }
+}
+
+/// Media type, as sent in `Content-Type` header (e.g. `text/html; charset=utf-8`).
+///
+/// Type, subtype and names of parameters are case-insensitive, so they are stored
+/// in lowercase.
+///
+/// # Examples
+/// ```
+/// use http_req::response::MediaType;
+///
+/// let media_type: MediaType = "Multipart/Form-Data; boundary=\"a;b\"".parse().unwrap();
+///
+/// assert_eq!(media_type.essence(), "multipart/form-data");
+/// assert_eq!(media_type.boundary(), Some("a;b"));
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct MediaType {
+    essence: String,
+    params: Vec<(String, String)>,
+}
+
+impl MediaType {
+    /// Returns type and subtype, separated by `/` (e.g. `text/html`).
+    pub fn essence(&self) -> &str {
+        &self.essence
+    }
+
+    /// Returns top-level type (e.g. `text`).
+    pub fn kind(&self) -> &str {
+        self.essence.split('/').next().unwrap_or_default()
+    }
+
+    /// Returns subtype (e.g. `html`).
+    pub fn subtype(&self) -> &str {
+        self.essence.split('/').nth(1).unwrap_or_default()
+    }
+
+    /// Returns all parameters, in the order they were sent.
+    pub fn params(&self) -> &[(String, String)] {
+        &self.params
+    }
+
+    /// Returns value of the first parameter called `name` (case-insensitive).
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, val)| val.as_str())
+    }
+
+    /// Returns value of `charset` parameter.
+    pub fn charset(&self) -> Option<&str> {
+        self.param("charset")
+    }
+
+    /// Returns value of `boundary` parameter.
+    pub fn boundary(&self) -> Option<&str> {
+        self.param("boundary")
+    }
+}
+
+impl str::FromStr for MediaType {
+    type Err = ParseErr;
+
+    /// Parses media type in format `type/subtype` followed by optional parameters.
+    /// Parameters without a value are ignored.
+    fn from_str(s: &str) -> Result<MediaType, ParseErr> {
+        let (essence, rest) = s.split_once(';').unwrap_or((s, ""));
+        let (kind, subtype) = essence.trim().split_once('/').ok_or(ParseErr::Invalid)?;
+
+        if !is_token(kind) || !is_token(subtype) {
+            return Err(ParseErr::Invalid);
+        }
+
+        let params = parse_params(rest)?;
+
+        Ok(MediaType {
+            essence: essence.trim().to_ascii_lowercase(),
+            params,
+        })
+    }
+}
+
+impl fmt::Display for MediaType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.essence)?;
+
+        for (name, value) in &self.params {
+            if is_token(value) {
+                write!(f, "; {}={}", name, value)?;
+            } else {
+                let value = value.replace('\\', "\\\\").replace('"', "\\\"");
+                write!(f, "; {}=\"{}\"", name, value)?;
+            }
         }
+
+        Ok(())
     }
 }
 
-impl From<StatusCode> for u16 {
-    fn from(code: StatusCode) -> Self {
-        code.0
+/// Single link from `Link` header (RFC 8288), e.g. `</items?page=3>; rel="next"`.
+///
+/// # Examples
+/// ```
+/// use http_req::response::Link;
+///
+/// let link: Link = "</page/3>; rel=\"next last\"".parse().unwrap();
+///
+/// assert_eq!(link.uri(), "/page/3");
+/// assert!(link.has_rel("next"));
+/// assert!(link.has_rel("Last"));
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Link {
+    uri: String,
+    params: Vec<(String, String)>,
+}
+
+impl Link {
+    /// Returns target URI of the link, as it was sent (it may be relative).
+    pub fn uri(&self) -> &str {
+        &self.uri
+    }
+
+    /// Returns all parameters of the link, in the order they were sent.
+    pub fn params(&self) -> &[(String, String)] {
+        &self.params
+    }
+
+    /// Returns value of the first parameter called `name` (case-insensitive).
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, val)| val.as_str())
+    }
+
+    /// Returns relation types of the link (`rel` parameter may contain several of them).
+    pub fn rels(&self) -> impl Iterator<Item = &str> {
+        self.param("rel").unwrap_or_default().split_whitespace()
+    }
+
+    /// Checks if the link has relation type `rel` (case-insensitive).
+    pub fn has_rel(&self, rel: &str) -> bool {
+        self.rels().any(|r| r.eq_ignore_ascii_case(rel))
     }
 }
 
-impl From<u16> for StatusCode {
-    fn from(code: u16) -> Self {
-        StatusCode(code)
+impl str::FromStr for Link {
+    type Err = ParseErr;
+
+    /// Parses single link in format `<uri>` followed by optional parameters.
+    fn from_str(s: &str) -> Result<Link, ParseErr> {
+        let (uri, rest) = s
+            .trim()
+            .strip_prefix('<')
+            .and_then(|s| s.split_once('>'))
+            .ok_or(ParseErr::Invalid)?;
+
+        let rest = rest.trim_start();
+        if !rest.is_empty() && !rest.starts_with(';') {
+            return Err(ParseErr::Invalid);
+        }
+
+        Ok(Link {
+            uri: uri.trim().to_string(),
+            params: parse_params(rest.strip_prefix(';').unwrap_or(rest))?,
+        })
     }
 }
 
-impl fmt::Display for StatusCode {
+impl fmt::Display for Link {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.0)
+        write!(f, "<{}>", self.uri)?;
+
+        for (name, value) in &self.params {
+            let value = value.replace('\\', "\\\\").replace('"', "\\\"");
+            write!(f, "; {}=\"{}\"", name, value)?;
+        }
+
+        Ok(())
     }
 }
 
-impl str::FromStr for StatusCode {
-    type Err = ParseErr;
+/// Checks that `key` is a token and `val` doesn't contain control characters other than
+/// horizontal tab.
+fn check_header(key: &str, val: &str) -> Result<(), ParseErr> {
+    if is_token(key) && !val.bytes().any(|b| (b < 0x20 && b != b'\t') || b == 0x7f) {
+        Ok(())
+    } else {
+        Err(ParseErr::InvalidHeader {
+            name: key.to_string(),
+        })
+    }
+}
 
-    fn from_str(s: &str) -> Result<StatusCode, ParseErr> {
-        Ok(StatusCode::new(s.parse()?))
+/// Checks if `s` is a non-empty token (RFC 9110, section 5.6.2).
+pub(crate) fn is_token(s: &str) -> bool {
+    !s.is_empty()
+        && s.bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b))
+}
+
+/// Parses parameters in format `; name=value` (values may be quoted strings), which follow
+/// the main value of a header. Names are converted to lowercase. Parameters without
+/// a value are ignored.
+fn parse_params(mut rest: &str) -> Result<Vec<(String, String)>, ParseErr> {
+    let mut params = Vec::new();
+
+    while !rest.is_empty() {
+        let end = rest.find([';', '=']).unwrap_or(rest.len());
+        let (name, after) = rest.split_at(end);
+        let name = name.trim();
+
+        rest = match after.strip_prefix('=') {
+            Some(after) => {
+                let (value, after) = match parse_quoted(after) {
+                    Some((value, after)) => (value, after),
+                    None => {
+                        let end = after.find(';').unwrap_or(after.len());
+                        (after[..end].trim().to_string(), &after[end..])
+                    }
+                };
+
+                if !is_token(name) {
+                    return Err(ParseErr::Invalid);
+                }
+
+                params.push((name.to_ascii_lowercase(), value));
+                after.trim_start()
+            }
+            None => after,
+        };
+
+        rest = match rest.strip_prefix(';') {
+            Some(rest) => rest,
+            None if rest.is_empty() => rest,
+            None => return Err(ParseErr::Invalid),
+        };
+    }
+
+    Ok(params)
+}
+
+/// Decodes extended parameter value in format `charset'language'value` (RFC 5987),
+/// where `value` is percent-encoded. Supports UTF-8 and ISO-8859-1 charsets.
+fn decode_ext_value(s: &str) -> Option<String> {
+    let mut parts = s.splitn(3, '\'');
+    let (charset, _, value) = (parts.next()?, parts.next()?, parts.next()?);
+
+    let mut bytes = Vec::with_capacity(value.len());
+    let mut rest = value.as_bytes();
+
+    while let Some((&b, tail)) = rest.split_first() {
+        if b == b'%' {
+            let hex = str::from_utf8(tail.get(..2)?).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(b);
+            rest = tail;
+        }
+    }
+
+    if charset.eq_ignore_ascii_case("UTF-8") {
+        String::from_utf8(bytes).ok()
+    } else if charset.eq_ignore_ascii_case("ISO-8859-1") {
+        Some(bytes.into_iter().map(char::from).collect())
+    } else {
+        None
+    }
+}
+
+/// Splits comma-separated header value into elements, ignoring commas inside quoted strings
+/// and URIs enclosed in `<>`.
+pub(crate) fn split_list(value: &str) -> Vec<&str> {
+    let mut elements = Vec::new();
+    let mut quoted = false;
+    let mut escaped = false;
+    let mut enclosed = false;
+    let mut start = 0;
+
+    for (i, c) in value.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' if !enclosed => quoted = !quoted,
+            '<' if !quoted => enclosed = true,
+            '>' if !quoted => enclosed = false,
+            ',' if !quoted && !enclosed => {
+                elements.push(value[start..i].trim());
+                start = i + 1;
+            }
+            _ => (),
+        }
+    }
+
+    elements.push(value[start..].trim());
+    elements.retain(|e| !e.is_empty());
+    elements
+}
+
+/// Parses quoted string at the beginning of `s`. Returns unescaped content of the string
+/// and the remaining part of `s`.
+fn parse_quoted(s: &str) -> Option<(String, &str)> {
+    let mut content = String::new();
+    let mut chars = s.strip_prefix('"')?.char_indices();
+
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' => content.push(chars.next()?.1),
+            '"' => return Some((content, &s[i + 2..])),
+            _ => content.push(c),
+        }
     }
+
+    None
+}
+
+/// Checks if `head` belongs to an informational (1xx) response, which is followed
+/// by another response. `101 Switching Protocols` is final.
+pub(crate) fn is_interim(head: &[u8]) -> bool {
+    let status_line = head.split(|&c| c == b'\n').next().unwrap_or_default();
+
+    str::from_utf8(status_line)
+        .ok()
+        .and_then(|line| line.parse::<Status>().ok())
+        .is_some_and(|status| status.code.is_info() && u16::from(status.code) != 101)
 }
 
 /// Finds elements slice `e` inside slice `data`. Returns position of the end of first match.
@@ -682,7 +2200,7 @@ mod tests {
     const BODY: &[u8; 27] = b"<html>hello</html>\r\n\r\nhello";
 
     const STATUS_LINE: &str = "HTTP/1.1 200 OK";
-    const VERSION: &str = "HTTP/1.1";
+    const VERSION: HttpVersion = HttpVersion::Http11;
     const CODE: u16 = 200;
     const REASON: &str = "OK";
 
@@ -703,7 +2221,7 @@ mod tests {
             assert!(StatusCode::new(i).is_info())
         }
 
-        for i in (0..1000).filter(|&i| i < 100 || i >= 200) {
+        for i in (0..1000).filter(|&i| !(100..200).contains(&i)) {
             assert!(!StatusCode::new(i).is_info())
         }
     }
@@ -714,7 +2232,7 @@ mod tests {
             assert!(StatusCode::new(i).is_success())
         }
 
-        for i in (0..1000).filter(|&i| i < 200 || i >= 300) {
+        for i in (0..1000).filter(|&i| !(200..300).contains(&i)) {
             assert!(!StatusCode::new(i).is_success())
         }
     }
@@ -725,7 +2243,7 @@ mod tests {
             assert!(StatusCode::new(i).is_redirect())
         }
 
-        for i in (0..1000).filter(|&i| i < 300 || i >= 400) {
+        for i in (0..1000).filter(|&i| !(300..400).contains(&i)) {
             assert!(!StatusCode::new(i).is_redirect())
         }
     }
@@ -736,7 +2254,7 @@ mod tests {
             assert!(StatusCode::new(i).is_client_err())
         }
 
-        for i in (0..1000).filter(|&i| i < 400 || i >= 500) {
+        for i in (0..1000).filter(|&i| !(400..500).contains(&i)) {
             assert!(!StatusCode::new(i).is_client_err())
         }
     }
@@ -747,7 +2265,7 @@ mod tests {
             assert!(StatusCode::new(i).is_server_err())
         }
 
-        for i in (0..1000).filter(|&i| i < 500 || i >= 600) {
+        for i in (0..1000).filter(|&i| !(500..600).contains(&i)) {
             assert!(!StatusCode::new(i).is_server_err())
         }
     }
@@ -809,6 +2327,12 @@ mod tests {
         assert_eq!(status.version, VERSION);
         assert_eq!(status.code, CODE_S);
         assert_eq!(status.reason, REASON);
+
+        assert_eq!(
+            "HTTP/1.0 200".parse::<Status>().unwrap().version(),
+            HttpVersion::Http10
+        );
+        assert_eq!("ICY 200 OK".parse::<Status>(), Err(ParseErr::StatusErr));
     }
 
     #[test]
@@ -845,7 +2369,10 @@ mod tests {
 
         let mut headers = Headers::with_capacity(4);
         headers.insert("Host", "doc.rust-lang.org");
-        headers.insert("User-Agent", "http_req/0.13.0");
+        headers.insert(
+            "User-Agent",
+            concat!("http_req/", env!("CARGO_PKG_VERSION")),
+        );
 
         assert_eq!(Headers::default_http(&uri), headers);
     }
@@ -897,6 +2424,318 @@ mod tests {
         }
     }
 
+    #[test]
+    fn headers_parse_limited() {
+        let many = (0..=MAX_HEADERS)
+            .map(|i| format!("X-Header-{}: {}\r\n", i, i))
+            .collect::<String>();
+
+        assert_eq!(
+            many.parse::<Headers>(),
+            Err(ParseErr::TooManyHeaders { limit: MAX_HEADERS })
+        );
+        assert_eq!(
+            Headers::parse_limited(&many, None).unwrap().iter().count(),
+            MAX_HEADERS + 1
+        );
+        assert_eq!(
+            Headers::parse_limited("A: 1\r\nB: 2\r\n", Some(2))
+                .unwrap()
+                .iter()
+                .count(),
+            2
+        );
+        assert_eq!(
+            Headers::parse_limited("A: 1\r\nB\r\n", Some(2)),
+            Err(ParseErr::HeadersErr)
+        );
+    }
+
+    #[test]
+    fn headers_typed() {
+        let mut headers = Headers::new();
+        assert_eq!(headers.content_length(), None);
+        assert_eq!(headers.content_type(), None);
+        assert_eq!(headers.location(), None);
+        assert_eq!(headers.etag(), None);
+        assert_eq!(headers.last_modified(), None);
+
+        for (len, expected) in [
+            ("42", Some(42)),
+            ("42, 42", Some(42)),
+            ("42, 43", None),
+            ("+42", None),
+            ("-1", None),
+            ("", None),
+        ] {
+            headers.insert("Content-Length", len);
+            assert_eq!(headers.content_length(), expected, "{:?}", len);
+        }
+
+        headers.insert("Content-Type", "Text/HTML ; charset=utf-8");
+        assert_eq!(headers.content_type(), Some("Text/HTML"));
+        headers.insert("Content-Type", "text");
+        assert_eq!(headers.content_type(), None);
+
+        headers.insert("Location", " https://example.com/a ");
+        assert_eq!(headers.location(), Some("https://example.com/a"));
+        headers.insert("Location", "/a b");
+        assert_eq!(headers.location(), None);
+
+        headers.insert("Last-Modified", "yesterday");
+        assert_eq!(headers.last_modified(), None);
+    }
+
+    #[test]
+    fn headers_try_insert() {
+        let mut headers = Headers::new();
+        assert_eq!(headers.try_insert("X-Tab", "a\tb"), Ok(None));
+
+        for (key, val) in [
+            ("X-Value", "a\r\nX-Injected: 1"),
+            ("X-Value", "a\nb"),
+            ("X-Value", "a\0b"),
+            ("X Name", "a"),
+            ("X-Name:", "a"),
+            ("", "a"),
+        ] {
+            assert_eq!(
+                headers.try_insert(key, val),
+                Err(ParseErr::InvalidHeader {
+                    name: key.to_string()
+                })
+            );
+        }
+
+        assert_eq!(headers.iter().count(), 1);
+        assert!(headers.validate().is_ok());
+
+        headers.insert("X-Value", "a\rb");
+        assert!(headers.validate().is_err());
+    }
+
+    #[test]
+    fn entity_tag_from_str() {
+        assert_eq!(
+            "\"xyzzy\"".parse(),
+            Ok(EntityTag {
+                weak: false,
+                tag: "xyzzy".to_string()
+            })
+        );
+        assert_eq!("W/\"\"".parse::<EntityTag>().unwrap().to_string(), "W/\"\"");
+        assert!("xyzzy".parse::<EntityTag>().is_err());
+        assert!("\"a\"b\"".parse::<EntityTag>().is_err());
+        assert!("w/\"a\"".parse::<EntityTag>().is_err());
+    }
+
+    #[test]
+    fn media_type_from_str() {
+        let media_type: MediaType = "Text/HTML ; Charset=\"utf-8\"; format; q=\"a\\\"b\""
+            .parse()
+            .unwrap();
+
+        assert_eq!(media_type.essence(), "text/html");
+        assert_eq!(media_type.kind(), "text");
+        assert_eq!(media_type.subtype(), "html");
+        assert_eq!(media_type.charset(), Some("utf-8"));
+        assert_eq!(media_type.param("Q"), Some("a\"b"));
+        assert_eq!(media_type.params().len(), 2);
+        assert_eq!(
+            media_type.to_string(),
+            "text/html; charset=utf-8; q=\"a\\\"b\""
+        );
+
+        assert!("text".parse::<MediaType>().is_err());
+        assert!("text/html; a=\"b\" c".parse::<MediaType>().is_err());
+        assert!("text/html; a b=c".parse::<MediaType>().is_err());
+    }
+
+    #[test]
+    fn res_content_type() {
+        const HEAD: &[u8] = b"HTTP/1.1 200 OK\r\n\
+                              Content-Type: multipart/mixed; boundary=xyz\r\n\r\n";
+
+        let res = Response::from_head(HEAD).unwrap();
+        assert_eq!(res.content_type().unwrap().boundary(), Some("xyz"));
+        assert_eq!(
+            Response::from_head(b"HTTP/1.1 200 OK\r\n\r\n")
+                .unwrap()
+                .content_type(),
+            None
+        );
+    }
+
+    #[test]
+    fn res_text() {
+        const HEAD: &[u8] = b"HTTP/1.1 200 OK\r\n\
+                              Content-Type: text/html; charset=ISO-8859-2\r\n\r\n";
+
+        let res = Response::from_head(HEAD).unwrap();
+        const BODY: &[u8] = b"\xbfa\xbf\xf3\xb3\xe6";
+
+        if cfg!(feature = "charset") {
+            assert_eq!(res.text(BODY), "żażółć");
+        } else {
+            assert_eq!(res.text(BODY), String::from_utf8_lossy(BODY));
+        }
+
+        let res = Response::from_head(RESPONSE_H).unwrap();
+        assert_eq!(res.text("zażółć".as_bytes()), "zażółć");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn res_json() {
+        let res = Response::from_head(RESPONSE_H).unwrap();
+
+        let value: Vec<u8> = res.json(b"[1,\n 2]").unwrap();
+        assert_eq!(value, [1, 2]);
+
+        let err = res.json::<Vec<u8>>(b"[1,\n 2,\n  300]").unwrap_err();
+        assert!(matches!(err, ParseErr::Json { position: 12, .. }));
+
+        let err = res.json::<Vec<u8>>(b"[1,\n").unwrap_err();
+        assert!(matches!(err, ParseErr::Json { position: 4, .. }));
+    }
+
+    #[test]
+    fn link_from_str() {
+        let link: Link = " <http://a/b?c=1,2> ; rel=\"next prev\"; title=x; crossorigin"
+            .parse()
+            .unwrap();
+
+        assert_eq!(link.uri(), "http://a/b?c=1,2");
+        assert_eq!(link.rels().collect::<Vec<_>>(), ["next", "prev"]);
+        assert_eq!(link.param("Title"), Some("x"));
+        assert_eq!(
+            link.to_string(),
+            "<http://a/b?c=1,2>; rel=\"next prev\"; title=\"x\""
+        );
+
+        assert!("http://a; rel=next".parse::<Link>().is_err());
+        assert!("<http://a> rel=next".parse::<Link>().is_err());
+    }
+
+    #[test]
+    fn res_links() {
+        const HEAD: &[u8] = b"HTTP/1.1 200 OK\r\n\
+                              Link: <http://a/?x=1,2>; rel=\"a,b\", invalid, </c>; rel=last\r\n\r\n";
+
+        let res = Response::from_head(HEAD).unwrap();
+        let uris: Vec<_> = res.links().into_iter().map(|link| link.uri).collect();
+
+        assert_eq!(uris, ["http://a/?x=1,2", "/c"]);
+        assert_eq!(res.link("LAST").as_deref(), Some("/c"));
+        assert!(Response::from_head(RESPONSE_H).unwrap().links().is_empty());
+    }
+
+    #[test]
+    fn res_filename() {
+        let filename = |value: &str| {
+            let head = format!("HTTP/1.1 200 OK\r\nContent-Disposition: {}\r\n\r\n", value);
+            Response::from_head(head.as_bytes()).unwrap().filename()
+        };
+
+        assert_eq!(
+            filename("attachment; filename=a.txt").as_deref(),
+            Some("a.txt")
+        );
+        assert_eq!(
+            filename("inline; FILENAME=\"a; b.txt\"").as_deref(),
+            Some("a; b.txt")
+        );
+        assert_eq!(
+            filename("attachment; filename*=iso-8859-1'en'%A3%20rates").as_deref(),
+            Some("£ rates")
+        );
+        assert_eq!(
+            filename("attachment; filename*=koi8-r''x; filename=y").as_deref(),
+            Some("y")
+        );
+        assert_eq!(
+            filename("attachment; filename=\"../../etc/passwd\"").as_deref(),
+            Some("passwd")
+        );
+        assert_eq!(filename("attachment; filename=\"..\""), None);
+        assert_eq!(filename("attachment"), None);
+        assert_eq!(filename("attachment; filename*=UTF-8''%ff"), None);
+        assert_eq!(Response::from_head(RESPONSE_H).unwrap().filename(), None);
+    }
+
+    #[test]
+    fn full_response_into_parts() {
+        let res = Response::from_head(RESPONSE_H).unwrap();
+        let full = res.clone().with_body(b"body".to_vec());
+
+        assert_eq!(full.status_code(), StatusCode(200));
+        assert_eq!(full.headers(), res.headers());
+        assert_eq!(full.text(), "body");
+        assert_eq!(full.into_parts(), (res, b"body".to_vec()));
+    }
+
+    #[test]
+    fn headers_parse_strict() {
+        let headers: Headers = "A:1\r\nB:  2 \r\nC: x:y\r\n".parse().unwrap();
+        assert_eq!(headers.get("A"), Some(&"1".to_string()));
+        assert_eq!(headers.get("B"), Some(&"2".to_string()));
+        assert_eq!(headers.get("C"), Some(&"x:y".to_string()));
+
+        for invalid in ["A : 1\r\n", "A: 1\r\n folded\r\n", ": 1\r\n", "A 1\r\n"] {
+            assert_eq!(invalid.parse::<Headers>(), Err(ParseErr::HeadersErr));
+        }
+    }
+
+    #[test]
+    fn headers_parse_lenient() {
+        let headers = Headers::parse_lenient(
+            " folded at start\r\n\
+            A : 1\r\n\
+            \tcontinued\r\n  \r\n\
+            no colon\r\n\
+            Bad Name: x\r\n\
+            B:2\n",
+            Some(2),
+        )
+        .unwrap();
+
+        assert_eq!(headers.iter().count(), 2);
+        assert_eq!(headers.get("A"), Some(&"1 continued".to_string()));
+        assert_eq!(headers.get("B"), Some(&"2".to_string()));
+        assert_eq!(
+            Headers::parse_lenient("A: 1\r\nB: 2\r\n", Some(1)),
+            Err(ParseErr::TooManyHeaders { limit: 1 })
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn headers_serde() {
+        let mut headers = Headers::new();
+        headers.insert("Content-Type", "text/html");
+        headers.insert("Accept", "*/*");
+
+        let json = serde_json::to_string(&headers).unwrap();
+        assert_eq!(json, r#"{"Accept":"*/*","Content-Type":"text/html"}"#);
+
+        let parsed: Headers = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, headers);
+        assert_eq!(parsed.get("content-type"), Some(&"text/html".to_string()));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn status_serde() {
+        let status = Status::from((HttpVersion::Http11, StatusCode::new(404), "Not Found"));
+
+        let json = serde_json::to_string(&status).unwrap();
+        assert_eq!(
+            json,
+            r#"{"version":"HTTP/1.1","code":404,"reason":"Not Found"}"#
+        );
+        assert_eq!(serde_json::from_str::<Status>(&json).unwrap(), status);
+    }
+
     #[test]
     fn hash_map_from_headers() {
         let mut headers = Headers::with_capacity(4);
@@ -943,6 +2782,240 @@ mod tests {
         Response::from_head(RESPONSE_H).unwrap();
     }
 
+    #[test]
+    fn res_cookies() {
+        const HEAD: &[u8] = b"HTTP/1.1 200 OK\r\n\
+                              Set-Cookie: a=1; Expires=Sun, 06 Nov 1994 08:49:37 GMT\r\n\
+                              set-cookie: invalid\r\n\
+                              SET-COOKIE: b=2; HttpOnly\r\n\r\n";
+
+        let res = Response::from_head(HEAD).unwrap();
+        let names: Vec<_> = res.cookies().iter().map(|c| c.name()).collect();
+
+        assert_eq!(names, ["a", "b"]);
+        assert!(res.cookies()[0].expires().is_some());
+        assert!(res.cookies()[1].http_only());
+        assert!(Response::from_head(RESPONSE_H)
+            .unwrap()
+            .cookies()
+            .is_empty());
+    }
+
+    #[test]
+    fn res_from_head_interim() {
+        let head = [b"HTTP/1.1 100 Continue\r\n\r\n".as_slice(), RESPONSE_H].concat();
+        let res = Response::from_head(&head).unwrap();
+
+        assert_eq!(res.status_code(), CODE_S);
+        assert_eq!(res.content_len(), Some(100));
+        assert_eq!(res.interim().len(), 1);
+        assert_eq!(res.interim()[0].status_code(), StatusCode(100));
+
+        let head = b"HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\n\r\n";
+        let res = Response::from_head(head).unwrap();
+
+        assert_eq!(res.status_code(), StatusCode(101));
+        assert!(res.interim().is_empty());
+    }
+
+    #[test]
+    fn res_try_from_interim() {
+        let response = [
+            b"HTTP/1.1 100 Continue\r\n\r\n".as_slice(),
+            b"HTTP/1.1 102 Processing\r\n\r\n",
+            RESPONSE,
+        ]
+        .concat();
+        let mut writer = Vec::new();
+        let res = Response::try_from(&response, &mut writer).unwrap();
+
+        assert_eq!(res.status_code(), CODE_S);
+        assert_eq!(res.interim().len(), 2);
+        assert_eq!(writer, BODY);
+    }
+
+    #[test]
+    fn fn_is_interim() {
+        assert!(is_interim(b"HTTP/1.1 100 Continue\r\n\r\n"));
+        assert!(is_interim(
+            b"HTTP/1.1 103 Early Hints\r\nLink: </a.css>\r\n\r\n"
+        ));
+        assert!(!is_interim(b"HTTP/1.1 101 Switching Protocols\r\n\r\n"));
+        assert!(!is_interim(RESPONSE_H));
+        assert!(!is_interim(b"GET / HTTP/1.1\r\n\r\n"));
+    }
+
+    #[test]
+    fn res_framing() {
+        let res = Response::from_head(RESPONSE_H).unwrap();
+        let framing = res.framing(&Method::GET);
+
+        assert!(framing.has_body());
+        assert!(!framing.is_chunked());
+        assert_eq!(framing.body_len(), Some(100));
+        assert_eq!(framing.encoded_len(), Some(100));
+        assert_eq!(framing.decoded_len(), Some(100));
+
+        let framing = res.framing(&Method::HEAD);
+        assert!(!framing.has_body());
+        assert_eq!(framing.body_len(), Some(0));
+        assert_eq!(framing.encoded_len(), Some(100));
+
+        let res = Response::from_head(
+            b"HTTP/1.1 200 OK\r\n\
+              Content-Encoding: gzip\r\n\
+              Transfer-Encoding: chunked\r\n\
+              Content-Length: 100\r\n\r\n",
+        )
+        .unwrap();
+        let framing = res.framing(&Method::GET);
+
+        assert!(framing.has_body());
+        assert!(framing.is_chunked());
+        assert_eq!(framing.body_len(), None);
+        assert_eq!(framing.encoded_len(), None);
+        assert_eq!(framing.decoded_len(), None);
+
+        let res = Response::from_head(
+            b"HTTP/1.1 304 Not Modified\r\n\
+              Content-Encoding: gzip\r\n\
+              Content-Length: 100\r\n\r\n",
+        )
+        .unwrap();
+        let framing = res.framing(&Method::GET);
+
+        assert!(!framing.has_body());
+        assert_eq!(framing.encoded_len(), Some(100));
+        assert_eq!(framing.decoded_len(), None);
+
+        let res = Response::from_head(b"HTTP/1.1 204 No Content\r\n\r\n").unwrap();
+        assert!(!res.framing(&Method::GET).has_body());
+
+        let res = Response::from_head(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n").unwrap();
+        assert!(!res.framing(&Method::GET).has_body());
+
+        let res = Response::from_head(b"HTTP/1.1 200 OK\r\n\r\n").unwrap();
+        assert!(res.framing(&Method::GET).has_body());
+        assert_eq!(res.framing(&Method::GET).body_len(), None);
+    }
+
+    #[test]
+    fn res_check_framing() {
+        for (head, valid) in [
+            (&b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\n"[..], true),
+            (
+                b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n",
+                true,
+            ),
+            (
+                b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\nContent-Length: 5\r\n\r\n",
+                true,
+            ),
+            (
+                b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\nContent-Length: 6\r\n\r\n",
+                false,
+            ),
+            (b"HTTP/1.1 200 OK\r\nContent-Length: 5, 6\r\n\r\n", false),
+            (b"HTTP/1.1 200 OK\r\nContent-Length: five\r\n\r\n", false),
+            (
+                b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\nTransfer-Encoding: chunked\r\n\r\n",
+                false,
+            ),
+        ] {
+            let response = Response::from_head(head).unwrap();
+            assert_eq!(response.check_framing().is_ok(), valid, "{:?}", response);
+        }
+    }
+
+    #[test]
+    fn res_basic_info() {
+        let res = Response::from_head(RESPONSE_H).unwrap();
+
+        assert_eq!(res.basic_info(&Method::GET), ["", "non-empty"]);
+        assert_eq!(res.basic_info(&Method::HEAD), ["", ""]);
+    }
+
+    #[test]
+    fn res_deprecation() {
+        let res = Response::from_head(RESPONSE_H).unwrap();
+        assert_eq!(res.deprecation(), None);
+
+        let res = Response::from_head(b"HTTP/1.1 200 OK\r\nDeprecation: true\r\n\r\n").unwrap();
+        assert_eq!(res.deprecation(), Some(Deprecation::Deprecated));
+    }
+
+    #[test]
+    fn res_sunset() {
+        let res = Response::from_head(RESPONSE_H).unwrap();
+        assert_eq!(res.sunset(), None);
+
+        let res = Response::from_head(
+            b"HTTP/1.1 200 OK\r\nSunset: Sat, 11 Jan 2003 02:44:04 GMT\r\n\r\n",
+        )
+        .unwrap();
+        assert_eq!(
+            res.sunset(),
+            Some(UNIX_EPOCH + Duration::from_secs(1042253044))
+        );
+    }
+
+    #[test]
+    fn res_warnings() {
+        let res = Response::from_head(RESPONSE_H).unwrap();
+        assert!(res.warnings().is_empty());
+
+        let res = Response::from_head(
+            b"HTTP/1.1 200 OK\r\n\
+              Warning: 110 - \"Response is stale\", invalid, \
+              299 api.example.com \"Removed, use \\\"v2\\\"\" \"Sat, 11 Jan 2003 02:44:04 GMT\"\r\n\r\n",
+        )
+        .unwrap();
+        let warnings = res.warnings();
+
+        assert_eq!(warnings.len(), 2);
+        assert_eq!(warnings[0].code(), 110);
+        assert_eq!(warnings[0].agent(), "-");
+        assert_eq!(warnings[0].text(), "Response is stale");
+        assert_eq!(warnings[0].date(), None);
+        assert_eq!(warnings[1].code(), 299);
+        assert_eq!(warnings[1].agent(), "api.example.com");
+        assert_eq!(warnings[1].text(), "Removed, use \"v2\"");
+        assert_eq!(
+            warnings[1].date(),
+            Some(UNIX_EPOCH + Duration::from_secs(1042253044))
+        );
+    }
+
+    #[test]
+    fn deprecation_from_str() {
+        assert_eq!(
+            "@1688169599".parse::<Deprecation>(),
+            Ok(Deprecation::Since(
+                UNIX_EPOCH + Duration::from_secs(1688169599)
+            ))
+        );
+        assert_eq!(
+            "Sat, 11 Jan 2003 02:44:04 GMT".parse::<Deprecation>(),
+            Ok(Deprecation::Since(
+                UNIX_EPOCH + Duration::from_secs(1042253044)
+            ))
+        );
+        assert_eq!("true".parse::<Deprecation>(), Ok(Deprecation::Deprecated));
+        assert_eq!("false".parse::<Deprecation>(), Err(ParseErr::Invalid));
+    }
+
+    #[test]
+    fn warning_from_str() {
+        assert!("299 - \"Deprecated\" \"Sat, 11 Jan 2003 02:44:04 GMT\""
+            .parse::<Warning>()
+            .is_ok());
+        assert!("299 - \"Deprecated".parse::<Warning>().is_err());
+        assert!("29 - \"Deprecated\"".parse::<Warning>().is_err());
+        assert!("299 - \"Deprecated\" \"yesterday\""
+            .parse::<Warning>()
+            .is_err());
+    }
+
     #[test]
     fn res_try_from() {
         let mut writer = Vec::new();
@@ -971,7 +3044,7 @@ mod tests {
         let mut writer = Vec::new();
         let res = Response::try_from(RESPONSE, &mut writer).unwrap();
 
-        assert_eq!(res.version(), "HTTP/1.1");
+        assert_eq!(res.version(), HttpVersion::Http11);
     }
 
     #[test]
@@ -992,7 +3065,7 @@ mod tests {
         headers.insert("Content-Type", "text/html");
         headers.insert("Content-Length", "100");
 
-        assert_eq!(res.headers(), &Headers::from(headers));
+        assert_eq!(res.headers(), &headers);
     }
 
     #[test]
@@ -1003,6 +3076,21 @@ mod tests {
         assert_eq!(res.content_len(), Some(100));
     }
 
+    #[test]
+    fn res_add_trailers() {
+        let mut res = Response::from_head(RESPONSE_H).unwrap();
+        let trailers = Headers::parse_limited(
+            "Grpc-Status: 0\r\ncontent-length: 5\r\nSet-Cookie: a=b\r\n",
+            None,
+        )
+        .unwrap();
+        res.add_trailers(trailers);
+
+        assert_eq!(res.headers().get("Grpc-Status"), Some(&"0".to_string()));
+        assert_eq!(res.content_len(), Some(100));
+        assert_eq!(res.headers().get("Set-Cookie"), None);
+    }
+
     #[test]
     fn res_body() {
         {
@@ -1015,7 +3103,7 @@ mod tests {
             let mut writer = Vec::new();
             Response::try_from(RESPONSE_H, &mut writer).unwrap();
 
-            assert_eq!(writer, &[]);
+            assert_eq!(writer, b"");
         }
     }
 }
@@ -0,0 +1,301 @@
+//! destinations for bodies of responses
+use crate::{error::Error, fetch::with_suffix};
+use std::{
+    fs::{self, File},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+/// Destination for the body of a response, which is notified when the transfer ends.
+///
+/// `Request::send` writes the body chunk by chunk with `write_chunk` and then calls
+/// exactly one of `finish` (on success) or `abort` (on failure). This allows sinks to
+/// finalize resources, e.g. sync and rename a temporary file, only after the whole
+/// body has been received, and to clean up otherwise.
+///
+/// Every `Write` is a `BodySink`, which flushes on `finish` and ignores `abort`.
+///
+/// # Examples
+/// ```
+/// use http_req::{error::Error, sink::BodySink};
+/// use std::io;
+///
+/// #[derive(Default)]
+/// struct Counter {
+///     len: u64,
+///     done: bool,
+/// }
+///
+/// impl BodySink for Counter {
+///     fn write_chunk(&mut self, chunk: &[u8]) -> io::Result<()> {
+///         self.len += chunk.len() as u64;
+///         Ok(())
+///     }
+///
+///     fn finish(&mut self, _total_len: u64) -> io::Result<()> {
+///         self.done = true;
+///         Ok(())
+///     }
+///
+///     fn abort(&mut self, _err: &Error) {}
+/// }
+/// ```
+pub trait BodySink {
+    /// Writes a chunk of the body.
+    fn write_chunk(&mut self, chunk: &[u8]) -> io::Result<()>;
+
+    /// Called once after the whole body has been written. `total_len` is the number
+    /// of bytes passed to `write_chunk`. `abort` is not called if this fails.
+    fn finish(&mut self, total_len: u64) -> io::Result<()>;
+
+    /// Called once if the request fails, before or while receiving the body.
+    fn abort(&mut self, err: &Error);
+}
+
+impl<W: Write> BodySink for W {
+    fn write_chunk(&mut self, chunk: &[u8]) -> io::Result<()> {
+        self.write_all(chunk)
+    }
+
+    fn finish(&mut self, _total_len: u64) -> io::Result<()> {
+        self.flush()
+    }
+
+    fn abort(&mut self, _err: &Error) {}
+}
+
+/// Sink writing the body to a file atomically.
+///
+/// The body is written to a temporary file with `.part` appended to the name of the target
+/// path, which is moved to the target path once the whole body has been received. If the
+/// request fails, or the sink is dropped before completion, the temporary file is removed,
+/// so the target path never contains a partially downloaded file.
+///
+/// # Examples
+/// ```no_run
+/// use http_req::{request::Request, sink::FileSink, uri::Uri};
+/// use std::convert::TryFrom;
+///
+/// let uri = Uri::try_from("https://www.rust-lang.org/learn").unwrap();
+/// let mut sink = FileSink::create("learn.html").unwrap();
+///
+/// let response = Request::new(&uri).send(&mut sink).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct FileSink {
+    path: PathBuf,
+    part_path: PathBuf,
+    file: Option<File>,
+}
+
+impl FileSink {
+    /// Creates the temporary file for a download to `path`.
+    pub fn create<P: Into<PathBuf>>(path: P) -> io::Result<FileSink> {
+        let path = path.into();
+        let part_path = with_suffix(&path, ".part");
+        let file = File::create(&part_path)?;
+
+        Ok(FileSink {
+            path,
+            part_path,
+            file: Some(file),
+        })
+    }
+
+    /// Returns the target path.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns path of the temporary file.
+    pub fn part_path(&self) -> &Path {
+        &self.part_path
+    }
+
+    /// Returns the open temporary file or an error if the sink has completed already.
+    fn file(&mut self) -> io::Result<&mut File> {
+        self.file
+            .as_mut()
+            .ok_or_else(|| io::Error::other("FileSink has already completed"))
+    }
+
+    /// Closes and removes the temporary file, if it's still open.
+    fn discard(&mut self) {
+        if self.file.take().is_some() {
+            let _ = fs::remove_file(&self.part_path);
+        }
+    }
+}
+
+impl BodySink for FileSink {
+    fn write_chunk(&mut self, chunk: &[u8]) -> io::Result<()> {
+        self.file()?.write_all(chunk)
+    }
+
+    fn finish(&mut self, _total_len: u64) -> io::Result<()> {
+        let res = self
+            .file()
+            .and_then(|file| file.sync_all())
+            .and_then(|_| fs::rename(&self.part_path, &self.path));
+
+        if res.is_err() {
+            self.discard();
+        }
+
+        self.file = None;
+        res
+    }
+
+    fn abort(&mut self, _err: &Error) {
+        self.discard();
+    }
+}
+
+impl Drop for FileSink {
+    fn drop(&mut self) {
+        self.discard();
+    }
+}
+
+/// Adapter passing data written to it to a `BodySink` and counting its length.
+pub(crate) struct SinkWriter<'s, T: ?Sized> {
+    sink: &'s mut T,
+    len: u64,
+}
+
+impl<'s, T: BodySink + ?Sized> SinkWriter<'s, T> {
+    /// Creates a new `SinkWriter` writing to `sink`.
+    pub(crate) fn new(sink: &'s mut T) -> SinkWriter<'s, T> {
+        SinkWriter { sink, len: 0 }
+    }
+
+    /// Notifies the sink about the outcome of the transfer.
+    pub(crate) fn complete<R>(self, res: Result<R, Error>) -> Result<R, Error> {
+        match res {
+            Ok(res) => {
+                self.sink.finish(self.len)?;
+                Ok(res)
+            }
+            Err(err) => {
+                self.sink.abort(&err);
+                Err(err)
+            }
+        }
+    }
+}
+
+impl<T: BodySink + ?Sized> Write for SinkWriter<'_, T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.sink.write_chunk(buf)?;
+        self.len += buf.len() as u64;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct Recorder {
+        data: Vec<u8>,
+        finished: Option<u64>,
+        aborted: usize,
+    }
+
+    impl BodySink for Recorder {
+        fn write_chunk(&mut self, chunk: &[u8]) -> io::Result<()> {
+            self.data.extend(chunk);
+            Ok(())
+        }
+
+        fn finish(&mut self, total_len: u64) -> io::Result<()> {
+            self.finished = Some(total_len);
+            Ok(())
+        }
+
+        fn abort(&mut self, _err: &Error) {
+            self.aborted += 1;
+        }
+    }
+
+    #[test]
+    fn sink_writer_finish() {
+        let mut sink = Recorder::default();
+        let mut writer = SinkWriter::new(&mut sink);
+
+        writer.write_all(b"hello ").unwrap();
+        writer.write_all(b"world").unwrap();
+        assert_eq!(writer.complete(Ok(1)).unwrap(), 1);
+
+        assert_eq!(sink.data, b"hello world");
+        assert_eq!(sink.finished, Some(11));
+        assert_eq!(sink.aborted, 0);
+    }
+
+    #[test]
+    fn sink_writer_abort() {
+        let mut sink = Recorder::default();
+        let mut writer = SinkWriter::new(&mut sink);
+
+        writer.write_all(b"hello").unwrap();
+        assert!(writer.complete::<()>(Err(Error::Timeout)).is_err());
+
+        assert_eq!(sink.finished, None);
+        assert_eq!(sink.aborted, 1);
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("http_req-{}-{}", name, std::process::id()))
+    }
+
+    #[test]
+    fn file_sink_finish() {
+        let path = temp_path("sink-finish");
+        let mut sink = FileSink::create(&path).unwrap();
+
+        sink.write_chunk(b"hello").unwrap();
+        assert!(sink.part_path().exists());
+        assert!(!path.exists());
+
+        sink.finish(5).unwrap();
+        assert!(!sink.part_path().exists());
+        assert_eq!(fs::read(&path).unwrap(), b"hello");
+        assert!(sink.write_chunk(b"more").is_err());
+
+        drop(sink);
+        assert!(path.exists());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn file_sink_abort() {
+        let path = temp_path("sink-abort");
+        let mut sink = FileSink::create(&path).unwrap();
+        let part_path = sink.part_path().to_path_buf();
+
+        sink.write_chunk(b"hel").unwrap();
+        sink.abort(&Error::Timeout);
+        assert!(!part_path.exists());
+        assert!(!path.exists());
+
+        let mut sink = FileSink::create(&path).unwrap();
+        sink.write_chunk(b"hel").unwrap();
+        drop(sink);
+        assert!(!part_path.exists());
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn write_body_sink() {
+        let mut sink = Vec::new();
+
+        sink.write_chunk(b"abc").unwrap();
+        sink.finish(3).unwrap();
+        assert_eq!(sink, b"abc");
+    }
+}
@@ -0,0 +1,435 @@
+//! reading Server-Sent Events streams
+use crate::{
+    chunked::ChunkReader,
+    error::Error,
+    request::RequestMessage,
+    response::{Headers, Response},
+    stream::{self, Stream},
+    uri::Uri,
+    LF,
+};
+use std::{
+    convert::TryFrom,
+    io::{self, BufRead, BufReader, Write},
+    path::PathBuf,
+    thread,
+    time::Duration,
+};
+
+const DEFAULT_RETRY: u64 = 3000;
+const DEFAULT_CONNECT_TIMEOUT: u64 = 60;
+
+/// Single event received from a `text/event-stream` response.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Event {
+    id: Option<String>,
+    event: String,
+    data: String,
+    retry: Option<Duration>,
+}
+
+impl Event {
+    /// Returns the last event ID seen on the stream when this event was dispatched.
+    pub fn id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
+    /// Returns the type of this event. Defaults to `message`.
+    pub fn event(&self) -> &str {
+        &self.event
+    }
+
+    /// Returns the data of this event. Multiple `data` lines are joined with `\n`.
+    pub fn data(&self) -> &str {
+        &self.data
+    }
+
+    /// Returns the reconnection time sent by the server along with this event, if any.
+    pub fn retry(&self) -> Option<Duration> {
+        self.retry
+    }
+}
+
+/// Client for Server-Sent Events.
+///
+/// Keeps the connection open and yields events as they arrive. When the connection
+/// is closed, it reconnects after the reconnection time (set by the server via `retry`)
+/// and sends the last received event ID in the `Last-Event-ID` header.
+///
+/// Iteration ends when the server responds with `204 No Content`, with a status other
+/// than `200 OK` or with a content type other than `text/event-stream`.
+/// The last response head is available via [`EventSource::response`].
+///
+/// # Examples
+/// ```no_run
+/// use http_req::sse::EventSource;
+///
+/// let events = EventSource::new("https://example.com/stream").unwrap();
+///
+/// for event in events {
+///     let event = event.unwrap();
+///     println!("{}: {}", event.event(), event.data());
+/// }
+/// ```
+pub struct EventSource {
+    uri: String,
+    headers: Headers,
+    last_event_id: Option<String>,
+    retry: Duration,
+    connect_timeout: Option<Duration>,
+    read_timeout: Option<Duration>,
+    root_cert_file_pem: Option<PathBuf>,
+    reader: Option<Box<dyn BufRead>>,
+    response: Option<Response>,
+    reconnecting: bool,
+    closed: bool,
+}
+
+impl EventSource {
+    /// Creates a new `EventSource` for `uri`. The connection is opened on the first call to `next`.
+    ///
+    /// # Examples
+    /// ```
+    /// use http_req::sse::EventSource;
+    ///
+    /// let events = EventSource::new("https://example.com/stream").unwrap();
+    /// ```
+    pub fn new<T: AsRef<str>>(uri: T) -> Result<EventSource, Error> {
+        let uri = uri.as_ref().to_string();
+        Uri::try_from(uri.as_str())?;
+
+        Ok(EventSource {
+            uri,
+            headers: Headers::new(),
+            last_event_id: None,
+            retry: Duration::from_millis(DEFAULT_RETRY),
+            connect_timeout: Some(Duration::from_secs(DEFAULT_CONNECT_TIMEOUT)),
+            read_timeout: None,
+            root_cert_file_pem: None,
+            reader: None,
+            response: None,
+            reconnecting: false,
+            closed: false,
+        })
+    }
+
+    /// Adds a header sent with every (re)connection request.
+    ///
+    /// # Examples
+    /// ```
+    /// use http_req::sse::EventSource;
+    ///
+    /// let mut events = EventSource::new("https://example.com/stream").unwrap();
+    /// events.header("Authorization", "Bearer secret456token123");
+    /// ```
+    pub fn header<T, U>(&mut self, key: &T, val: &U) -> &mut Self
+    where
+        T: ToString + ?Sized,
+        U: ToString + ?Sized,
+    {
+        self.headers.insert(key, val);
+        self
+    }
+
+    /// Sets the ID sent in the `Last-Event-ID` header, e.g. to resume a stream
+    /// consumed by an earlier `EventSource`.
+    pub fn last_event_id<T: ToString + ?Sized>(&mut self, id: &T) -> &mut Self {
+        self.last_event_id = Some(id.to_string());
+        self
+    }
+
+    /// Sets the initial reconnection time. The server may change it with a `retry` field.
+    pub fn retry<T>(&mut self, retry: T) -> &mut Self
+    where
+        Duration: From<T>,
+    {
+        self.retry = Duration::from(retry);
+        self
+    }
+
+    /// Sets the connect timeout used for every (re)connection.
+    pub fn connect_timeout<T>(&mut self, timeout: Option<T>) -> &mut Self
+    where
+        Duration: From<T>,
+    {
+        self.connect_timeout = timeout.map(Duration::from);
+        self
+    }
+
+    /// Sets the read timeout. By default there is none, as event streams may stay idle for long.
+    /// When the timeout is exceeded, the connection is treated as closed and reestablished.
+    pub fn read_timeout<T>(&mut self, timeout: Option<T>) -> &mut Self
+    where
+        Duration: From<T>,
+    {
+        self.read_timeout = timeout.map(Duration::from);
+        self
+    }
+
+    /// Adds the file containing the PEM-encoded certificates that should be added in the trusted root store.
+    pub fn root_cert_file_pem<T: Into<PathBuf>>(&mut self, file_path: T) -> &mut Self {
+        self.root_cert_file_pem = Some(file_path.into());
+        self
+    }
+
+    /// Returns the ID of the last received event.
+    pub fn get_last_event_id(&self) -> Option<&str> {
+        self.last_event_id.as_deref()
+    }
+
+    /// Returns the head of the most recent response from the server.
+    pub fn response(&self) -> Option<&Response> {
+        self.response.as_ref()
+    }
+
+    /// Opens a new connection and validates the response head.
+    /// Returns `Ok(false)` if the server doesn't want the client to (re)connect.
+    fn connect(&mut self) -> Result<bool, Error> {
+        let uri = Uri::try_from(self.uri.as_str())?;
+
+        let mut stream = Stream::connect(&uri, self.connect_timeout)?;
+        stream.set_read_timeout(self.read_timeout)?;
+        stream = Stream::try_to_https(stream, &uri, self.root_cert_file_pem.as_deref())?;
+
+        let mut message = RequestMessage::new(&uri);
+        message
+            .header("Accept", "text/event-stream")
+            .header("Cache-Control", "no-cache");
+
+        for (key, val) in self.headers.iter() {
+            message.header(key, val);
+        }
+
+        if let Some(id) = &self.last_event_id {
+            message.header("Last-Event-ID", id);
+        }
+
+        stream.write_all(&message.try_parse()?)?;
+
+        let mut reader = BufReader::new(stream);
+        let response = Response::from_head(&stream::read_head(&mut reader))?;
+        let is_event_stream = response
+            .headers()
+            .get("Content-Type")
+            .is_some_and(|t| t.trim_start().starts_with("text/event-stream"));
+        let proceed = u16::from(response.status_code()) == 200 && is_event_stream;
+
+        if proceed {
+            self.reader = if response.is_chunked() {
+                Some(Box::new(BufReader::new(ChunkReader::from(reader))))
+            } else {
+                Some(Box::new(reader))
+            };
+        }
+
+        self.response = Some(response);
+        Ok(proceed)
+    }
+}
+
+impl Iterator for EventSource {
+    type Item = Result<Event, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.closed {
+            let reader = match self.reader.as_mut() {
+                Some(reader) => reader,
+                None => {
+                    if self.reconnecting {
+                        thread::sleep(self.retry);
+                    }
+
+                    self.reconnecting = true;
+
+                    match self.connect() {
+                        Ok(true) => continue,
+                        Ok(false) => {
+                            self.closed = true;
+                            return None;
+                        }
+                        // The next call waits for the reconnection time and tries again.
+                        Err(e) => return Some(Err(e)),
+                    }
+                }
+            };
+
+            match read_event(reader, &mut self.last_event_id, &mut self.retry) {
+                Ok(Some(event)) => return Some(Ok(event)),
+                Ok(None) | Err(_) => self.reader = None,
+            }
+        }
+
+        None
+    }
+}
+
+/// Reads a single event from `reader`. Updates `last_event_id` and `retry`
+/// according to the fields received. Returns `None` when the stream ends.
+///
+/// Events which are not terminated with a blank line before the end of the stream
+/// are discarded, as described in the specification.
+pub fn read_event<R>(
+    reader: &mut R,
+    last_event_id: &mut Option<String>,
+    retry: &mut Duration,
+) -> io::Result<Option<Event>>
+where
+    R: BufRead + ?Sized,
+{
+    let mut event = String::new();
+    let mut data = String::new();
+    let mut event_retry = None;
+    let mut line = Vec::new();
+
+    loop {
+        line.clear();
+
+        if reader.read_until(LF, &mut line)? == 0 {
+            return Ok(None);
+        }
+
+        let line = String::from_utf8_lossy(&line);
+        let line = line.trim_end_matches('\n').trim_end_matches('\r');
+
+        if line.is_empty() {
+            if data.is_empty() {
+                event.clear();
+                continue;
+            }
+
+            data.pop();
+
+            return Ok(Some(Event {
+                id: last_event_id.clone(),
+                event: if event.is_empty() {
+                    "message".to_string()
+                } else {
+                    event
+                },
+                data,
+                retry: event_retry,
+            }));
+        }
+
+        if line.starts_with(':') {
+            continue;
+        }
+
+        let (field, value) = match line.find(':') {
+            Some(idx) => {
+                let value = &line[idx + 1..];
+                (&line[..idx], value.strip_prefix(' ').unwrap_or(value))
+            }
+            None => (line, ""),
+        };
+
+        match field {
+            "event" => event = value.to_string(),
+            "data" => {
+                data.push_str(value);
+                data.push('\n');
+            }
+            "id" if !value.contains('\0') => *last_event_id = Some(value.to_string()),
+            "retry" => {
+                if let Ok(ms) = value.parse() {
+                    *retry = Duration::from_millis(ms);
+                    event_retry = Some(*retry);
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TestServer;
+
+    const STREAM: &[u8] = b": comment\r\n\
+                            data: first\r\n\r\n\
+                            event: update\n\
+                            id: 7\n\
+                            data: line 1\n\
+                            data:line 2\n\n\
+                            retry: 50\n\
+                            data\n\n\
+                            data: unterminated";
+
+    fn read_all(stream: &[u8]) -> (Vec<Event>, Option<String>, Duration) {
+        let mut reader = BufReader::new(stream);
+        let mut last_event_id = None;
+        let mut retry = Duration::from_millis(DEFAULT_RETRY);
+        let mut events = Vec::new();
+
+        while let Some(event) = read_event(&mut reader, &mut last_event_id, &mut retry).unwrap() {
+            events.push(event);
+        }
+
+        (events, last_event_id, retry)
+    }
+
+    #[test]
+    fn fn_read_event() {
+        let (events, last_event_id, retry) = read_all(STREAM);
+
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[0].event(), "message");
+        assert_eq!(events[0].data(), "first");
+        assert_eq!(events[0].id(), None);
+
+        assert_eq!(events[1].event(), "update");
+        assert_eq!(events[1].data(), "line 1\nline 2");
+        assert_eq!(events[1].id(), Some("7"));
+
+        assert_eq!(events[2].data(), "");
+        assert_eq!(events[2].id(), Some("7"));
+        assert_eq!(events[2].retry(), Some(Duration::from_millis(50)));
+
+        assert_eq!(last_event_id, Some("7".to_string()));
+        assert_eq!(retry, Duration::from_millis(50));
+    }
+
+    #[test]
+    fn fn_read_event_without_data() {
+        let (events, _, _) = read_all(b"event: ping\n\nid: 1\n\ndata: x\n\n");
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event(), "message");
+        assert_eq!(events[0].id(), Some("1"));
+    }
+
+    #[test]
+    fn event_source_new() {
+        assert!(EventSource::new("http://127.0.0.1/events").is_ok());
+        assert!(EventSource::new("").is_err());
+    }
+
+    #[test]
+    fn event_source_reconnect() {
+        let server = TestServer::start([
+            "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\n\r\n\
+             retry: 10\nid: 1\ndata: a\n\n",
+            "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\n\r\ndata: b\n\n",
+            "HTTP/1.1 204 No Content\r\n\r\n",
+        ])
+        .unwrap();
+
+        let events: Vec<_> = EventSource::new(server.uri("/events"))
+            .unwrap()
+            .map(|e| e.unwrap())
+            .collect();
+
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].data(), "a");
+        assert_eq!(events[1].data(), "b");
+        assert_eq!(events[1].id(), Some("1"));
+
+        let requests = server.join().unwrap();
+        let (first, second) = (requests[0].head().headers(), requests[1].head().headers());
+
+        assert_eq!(first.get("Accept"), Some(&"text/event-stream".to_string()));
+        assert_eq!(first.get("Last-Event-ID"), None);
+        assert_eq!(second.get("Last-Event-ID"), Some(&"1".to_string()));
+    }
+}
@@ -1,26 +1,44 @@
 //! TCP stream
 use crate::{
-    error::{Error, ParseErr},
+    cancel::{self, CancelToken, Handle, Registration},
+    dns,
+    error::Error,
+    response::is_interim,
     tls::{self, Conn},
     uri::Uri,
     CR_LF, LF,
 };
+#[cfg(not(target_family = "wasm"))]
+use socket2::{SockRef, TcpKeepalive};
 use std::{
+    fmt,
     io::{self, BufRead, Read, Write},
-    net::{TcpStream, ToSocketAddrs},
+    net::{SocketAddr, TcpStream},
     path::Path,
-    sync::mpsc::{Receiver, RecvTimeoutError, Sender},
+    sync::{
+        mpsc::{self, Receiver, RecvTimeoutError, Sender},
+        Arc,
+    },
+    thread,
     time::{Duration, Instant},
 };
 
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+
 const BUF_SIZE: usize = 16 * 1000;
+pub(crate) const CONNECTION_ATTEMPT_DELAY: Duration = Duration::from_millis(250);
 
-/// Wrapper around TCP stream for HTTP and HTTPS protocols.
+/// Wrapper around TCP stream for HTTP and HTTPS protocols
+/// (or Unix domain socket for plain HTTP on Unix platforms).
 /// Allows to perform common operations on underlying stream.
 #[derive(Debug)]
+#[allow(clippy::large_enum_variant)]
 pub enum Stream {
     Http(TcpStream),
     Https(Conn<TcpStream>),
+    #[cfg(unix)]
+    Unix(UnixStream),
 }
 
 impl Stream {
@@ -28,16 +46,42 @@ impl Stream {
     pub fn connect(uri: &Uri, connect_timeout: Option<Duration>) -> Result<Stream, Error> {
         let host = match uri.host() {
             Some(h) => h,
-            None => return Err(Error::Parse(ParseErr::UriErr)),
+            None => return Err(Error::Parse(uri.missing_host_err())),
         };
         let port = uri.corr_port();
 
-        let stream = match connect_timeout {
-            Some(timeout) => connect_with_timeout(host, port, timeout)?,
-            None => TcpStream::connect((host, port))?,
+        let addrs = dns::resolve(host, port)?;
+        Stream::connect_to(&addrs, connect_timeout)
+    }
+
+    /// Opens a TCP connection to the first reachable address from `addrs`
+    /// with a connection timeout (if specified).
+    pub fn connect_to(
+        addrs: &[SocketAddr],
+        connect_timeout: Option<Duration>,
+    ) -> Result<Stream, Error> {
+        Ok(Stream::Http(connect_addrs(addrs, connect_timeout)?))
+    }
+
+    /// Registers the underlying socket in `token`, so it's shut down on cancellation.
+    pub(crate) fn register_cancel<'t>(
+        &self,
+        token: &'t CancelToken,
+    ) -> Result<Registration<'t>, Error> {
+        let handle = match self {
+            Stream::Http(stream) => Handle::Tcp(stream.try_clone()?),
+            Stream::Https(conn) => Handle::Tcp(conn.get_ref().try_clone()?),
+            #[cfg(unix)]
+            Stream::Unix(stream) => Handle::Unix(stream.try_clone()?),
         };
 
-        Ok(Stream::Http(stream))
+        Ok(token.register(handle))
+    }
+
+    /// Opens a connection to a Unix domain socket at `path`.
+    #[cfg(unix)]
+    pub fn connect_unix<P: AsRef<Path>>(path: P) -> Result<Stream, Error> {
+        Ok(Stream::Unix(UnixStream::connect(path)?))
     }
 
     /// Tries to establish a secure connection over TLS.
@@ -45,23 +89,35 @@ impl Stream {
     /// Checks if `uri` scheme denotes a HTTPS protocol:
     /// - If yes, attemps to establish a secure connection
     /// - Otherwise, returns the `stream` without any modification
+    ///
+    /// Connections to Unix domain sockets are always returned without any modification.
     pub fn try_to_https(
         stream: Stream,
         uri: &Uri,
         root_cert_file_pem: Option<&Path>,
+    ) -> Result<Stream, Error> {
+        let mut cnf = tls::Config::default();
+
+        if let (Stream::Http(_), "https", Some(p)) = (&stream, uri.scheme(), root_cert_file_pem) {
+            cnf.add_root_cert_file_pem(p)?;
+        }
+
+        Stream::try_to_https_with(stream, uri, &cnf)
+    }
+
+    /// Tries to establish a secure connection over TLS using the given configuration.
+    /// Behaves like `try_to_https` otherwise.
+    pub fn try_to_https_with(
+        stream: Stream,
+        uri: &Uri,
+        cnf: &tls::Config,
     ) -> Result<Stream, Error> {
         match stream {
             Stream::Http(http_stream) => {
                 if uri.scheme() == "https" {
                     let host = match uri.host() {
                         Some(h) => h,
-                        None => return Err(Error::Parse(ParseErr::UriErr)),
-                    };
-                    let mut cnf = tls::Config::default();
-
-                    let cnf = match root_cert_file_pem {
-                        Some(p) => cnf.add_root_cert_file_pem(p)?,
-                        None => &mut cnf,
+                        None => return Err(Error::Parse(uri.missing_host_err())),
                     };
 
                     let stream = cnf.connect(host, http_stream)?;
@@ -70,7 +126,7 @@ impl Stream {
                     Ok(Stream::Http(http_stream))
                 }
             }
-            Stream::Https(_) => Ok(stream),
+            _ => Ok(stream),
         }
     }
 
@@ -79,6 +135,8 @@ impl Stream {
         match self {
             Stream::Http(stream) => Ok(stream.set_read_timeout(dur)?),
             Stream::Https(conn) => Ok(conn.get_mut().set_read_timeout(dur)?),
+            #[cfg(unix)]
+            Stream::Unix(stream) => Ok(stream.set_read_timeout(dur)?),
         }
     }
 
@@ -87,6 +145,79 @@ impl Stream {
         match self {
             Stream::Http(stream) => Ok(stream.set_write_timeout(dur)?),
             Stream::Https(conn) => Ok(conn.get_mut().set_write_timeout(dur)?),
+            #[cfg(unix)]
+            Stream::Unix(stream) => Ok(stream.set_write_timeout(dur)?),
+        }
+    }
+
+    /// Sets the value of the `TCP_NODELAY` option on the underlying TCP stream.
+    /// If set, segments are sent as soon as possible, even if there is only a small amount of data.
+    ///
+    /// Does nothing for Unix domain sockets.
+    pub fn set_nodelay(&mut self, nodelay: bool) -> Result<(), Error> {
+        match self.tcp_stream() {
+            Some(stream) => Ok(stream.set_nodelay(nodelay)?),
+            None => Ok(()),
+        }
+    }
+
+    /// Enables TCP keepalive on the underlying TCP stream with the given parameters,
+    /// or disables it if `keepalive` is `None`.
+    ///
+    /// Does nothing for Unix domain sockets and on WebAssembly targets.
+    #[cfg(not(target_family = "wasm"))]
+    pub fn set_keepalive(&mut self, keepalive: Option<Keepalive>) -> Result<(), Error> {
+        let socket = match self.tcp_stream() {
+            Some(stream) => SockRef::from(stream),
+            None => return Ok(()),
+        };
+
+        match keepalive {
+            Some(keepalive) => socket.set_tcp_keepalive(&keepalive.into())?,
+            None => socket.set_keepalive(false)?,
+        }
+
+        Ok(())
+    }
+
+    /// Enables TCP keepalive on the underlying TCP stream with the given parameters,
+    /// or disables it if `keepalive` is `None`.
+    ///
+    /// Does nothing for Unix domain sockets and on WebAssembly targets.
+    #[cfg(target_family = "wasm")]
+    pub fn set_keepalive(&mut self, _keepalive: Option<Keepalive>) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Returns a reference to the underlying TCP stream, if there is one.
+    fn tcp_stream(&self) -> Option<&TcpStream> {
+        match self {
+            Stream::Http(stream) => Some(stream),
+            Stream::Https(conn) => Some(conn.get_ref()),
+            #[cfg(unix)]
+            Stream::Unix(_) => None,
+        }
+    }
+
+    /// Returns the socket address of the remote peer of this stream.
+    ///
+    /// Fails for Unix domain sockets, which aren't identified by a socket address.
+    pub fn peer_addr(&self) -> Result<SocketAddr, Error> {
+        match self.tcp_stream() {
+            Some(stream) => Ok(stream.peer_addr()?),
+            None => Err(Error::IO(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "Unix domain socket has no socket address",
+            ))),
+        }
+    }
+
+    /// Returns certificates presented by the server (see `Conn::peer_certificates`).
+    /// Returns an empty list for connections without TLS.
+    pub fn peer_certificates(&self) -> Vec<tls::Certificate> {
+        match self {
+            Stream::Https(conn) => conn.peer_certificates(),
+            _ => Vec::new(),
         }
     }
 }
@@ -96,6 +227,8 @@ impl Read for Stream {
         match self {
             Stream::Http(stream) => stream.read(buf),
             Stream::Https(stream) => stream.read(buf),
+            #[cfg(unix)]
+            Stream::Unix(stream) => stream.read(buf),
         }
     }
 }
@@ -105,12 +238,166 @@ impl Write for Stream {
         match self {
             Stream::Http(stream) => stream.write(buf),
             Stream::Https(stream) => stream.write(buf),
+            #[cfg(unix)]
+            Stream::Unix(stream) => stream.write(buf),
         }
     }
     fn flush(&mut self) -> Result<(), io::Error> {
         match self {
             Stream::Http(stream) => stream.flush(),
             Stream::Https(stream) => stream.flush(),
+            #[cfg(unix)]
+            Stream::Unix(stream) => stream.flush(),
+        }
+    }
+}
+
+/// Parameters of TCP keepalive.
+///
+/// # Examples
+/// ```
+/// use http_req::stream::Keepalive;
+/// use std::time::Duration;
+///
+/// let keepalive = Keepalive::new(Duration::from_secs(60))
+///     .with_interval(Duration::from_secs(10));
+/// ```
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Keepalive {
+    time: Duration,
+    interval: Option<Duration>,
+}
+
+impl Keepalive {
+    /// Creates new `Keepalive`, which starts sending probes after the connection
+    /// has been idle for `time`.
+    pub const fn new(time: Duration) -> Keepalive {
+        Keepalive {
+            time,
+            interval: None,
+        }
+    }
+
+    /// Sets the time between keepalive probes. If not set, the system default is used.
+    /// Ignored on platforms which don't support it.
+    pub const fn with_interval(mut self, interval: Duration) -> Keepalive {
+        self.interval = Some(interval);
+        self
+    }
+
+    /// Returns idle time after which keepalive probes are sent.
+    pub const fn time(&self) -> Duration {
+        self.time
+    }
+
+    /// Returns time between keepalive probes.
+    pub const fn interval(&self) -> Option<Duration> {
+        self.interval
+    }
+}
+
+#[cfg(not(target_family = "wasm"))]
+impl From<Keepalive> for TcpKeepalive {
+    fn from(keepalive: Keepalive) -> TcpKeepalive {
+        let params = TcpKeepalive::new().with_time(keepalive.time);
+
+        #[cfg(any(
+            target_os = "android",
+            target_os = "dragonfly",
+            target_os = "freebsd",
+            target_os = "fuchsia",
+            target_os = "illumos",
+            target_os = "ios",
+            target_os = "linux",
+            target_os = "macos",
+            target_os = "netbsd",
+            target_os = "tvos",
+            target_os = "watchos",
+            target_os = "windows",
+        ))]
+        let params = match keepalive.interval {
+            Some(interval) => params.with_interval(interval),
+            None => params,
+        };
+
+        params
+    }
+}
+
+/// Events in the lifetime of a connection, reported to the observer set with `Request::observer`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum StreamEvent {
+    /// TCP connection has been opened to the address.
+    Connected(SocketAddr),
+    /// Connection has been upgraded to TLS.
+    TlsEstablished,
+    /// Connection has been closed.
+    Closed(CloseReason),
+}
+
+/// Reasons for closing a connection.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum CloseReason {
+    /// The server closed the connection after sending the response.
+    Eof,
+    /// Reading from the connection failed.
+    Error(io::ErrorKind),
+    /// The connection was dropped before it was read to the end, e.g. after
+    /// a HEAD request, a redirect or a timeout.
+    Dropped,
+}
+
+/// Function set with `Request::observer`, compared by identity.
+#[derive(Clone)]
+pub(crate) struct Observer(pub(crate) Arc<dyn Fn(&StreamEvent) + Send + Sync>);
+
+impl fmt::Debug for Observer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Observer")
+    }
+}
+
+impl PartialEq for Observer {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// Reader which reports `StreamEvent::Closed` to `observer` when dropped.
+pub(crate) struct ObservedReader<R> {
+    inner: R,
+    observer: Option<Observer>,
+    reason: CloseReason,
+}
+
+impl<R> ObservedReader<R> {
+    pub(crate) fn new(inner: R, observer: Option<Observer>) -> ObservedReader<R> {
+        ObservedReader {
+            inner,
+            observer,
+            reason: CloseReason::Dropped,
+        }
+    }
+}
+
+impl<R: Read> Read for ObservedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, io::Error> {
+        let res = self.inner.read(buf);
+
+        match &res {
+            Ok(0) if !buf.is_empty() => self.reason = CloseReason::Eof,
+            Err(e) => self.reason = CloseReason::Error(e.kind()),
+            _ => (),
+        }
+
+        res
+    }
+}
+
+impl<R> Drop for ObservedReader<R> {
+    fn drop(&mut self) {
+        if let Some(Observer(observer)) = &self.observer {
+            observer(&StreamEvent::Closed(self.reason));
         }
     }
 }
@@ -141,7 +428,7 @@ where
                 Ok(0) | Err(_) => break,
                 Ok(len) => {
                     let filled_buf = buf[..len].to_vec();
-                    if let Err(_) = sender.send(filled_buf) {
+                    if sender.send(filled_buf).is_err() {
                         break;
                     }
                 }
@@ -160,6 +447,15 @@ pub trait ThreadReceive {
     /// or `deadline` is exceeded. Writes received data into this writer.
     fn receive_all(&mut self, receiver: &Receiver<Vec<u8>>, deadline: Instant)
         -> Result<(), Error>;
+
+    /// Works like `receive_all`, but additionally fails with `Error::Timeout`
+    /// if the transfer rate drops below `limit`.
+    fn receive_all_limited(
+        &mut self,
+        receiver: &Receiver<Vec<u8>>,
+        deadline: Instant,
+        limit: SpeedLimit,
+    ) -> Result<(), Error>;
 }
 
 impl<T> ThreadReceive for T
@@ -187,41 +483,252 @@ where
                 },
             };
 
-            self.write_all(&data_read).map_err(|e| Error::IO(e))?;
+            self.write_all(&data_read).map_err(Error::IO)?;
+            Ok(false)
+        })
+    }
+
+    fn receive_all_limited(
+        &mut self,
+        receiver: &Receiver<Vec<u8>>,
+        deadline: Instant,
+        limit: SpeedLimit,
+    ) -> Result<(), Error> {
+        let mut period_start = Instant::now();
+        let mut period_bytes = 0;
+
+        execute_with_deadline(deadline, |remaining_time| {
+            let now = Instant::now();
+            let period_end = period_start + limit.period;
+
+            if now >= period_end {
+                if period_bytes < limit.min_bytes() {
+                    return Err(Error::Timeout);
+                }
+
+                period_start = now;
+                period_bytes = 0;
+                return Ok(false);
+            }
+
+            let data_read = match receiver.recv_timeout(remaining_time.min(period_end - now)) {
+                Ok(data) => data,
+                Err(e) => match e {
+                    RecvTimeoutError::Timeout => return Ok(false),
+                    RecvTimeoutError::Disconnected => return Ok(true),
+                },
+            };
+
+            period_bytes += data_read.len() as u64;
+            self.write_all(&data_read).map_err(Error::IO)?;
             Ok(false)
         })
     }
 }
 
+/// Minimum transfer rate required while receiving the body of a response.
+///
+/// Guards against servers which send data so slowly (e.g. a byte at a time)
+/// that read timeouts never fire, independently of the timeout on entire request.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct SpeedLimit {
+    bytes_per_sec: u64,
+    period: Duration,
+}
+
+impl SpeedLimit {
+    /// Creates a new `SpeedLimit`. Transfer is aborted if fewer than
+    /// `bytes_per_sec` bytes per second are received over any `period`.
+    ///
+    /// # Examples
+    /// ```
+    /// use http_req::stream::SpeedLimit;
+    /// use std::time::Duration;
+    ///
+    /// let limit = SpeedLimit::new(1024, Duration::from_secs(30));
+    /// ```
+    pub fn new(bytes_per_sec: u64, period: Duration) -> SpeedLimit {
+        SpeedLimit {
+            bytes_per_sec,
+            period,
+        }
+    }
+
+    /// Returns the minimum number of bytes per second.
+    pub fn bytes_per_sec(&self) -> u64 {
+        self.bytes_per_sec
+    }
+
+    /// Returns the period over which the transfer rate is measured.
+    pub fn period(&self) -> Duration {
+        self.period
+    }
+
+    /// Returns the minimum number of bytes to receive within a single period.
+    fn min_bytes(&self) -> u64 {
+        (self.bytes_per_sec as f64 * self.period.as_secs_f64()) as u64
+    }
+}
+
 /// Connects to the target host with a specified timeout.
 pub fn connect_with_timeout<T, U>(host: T, port: u16, timeout: U) -> io::Result<TcpStream>
 where
     Duration: From<U>,
     T: AsRef<str>,
 {
-    let host = host.as_ref();
-    let timeout = Duration::from(timeout);
-    let addrs: Vec<_> = (host, port).to_socket_addrs()?.collect();
-    let count = addrs.len();
+    let addrs = dns::resolve(host.as_ref(), port)?;
+    connect_addrs(&addrs, Some(Duration::from(timeout)))
+}
 
-    for (idx, addr) in addrs.into_iter().enumerate() {
-        match TcpStream::connect_timeout(&addr, timeout) {
-            Ok(stream) => return Ok(stream),
-            Err(err) => match err.kind() {
-                io::ErrorKind::TimedOut => return Err(err),
+/// Connects to the first reachable address from `addrs` with a timeout (if specified).
+///
+/// Follows Happy Eyeballs (RFC 8305): addresses of both families are interleaved,
+/// starting with the family of the first address, and connection attempts are started
+/// in parallel, each `CONNECTION_ATTEMPT_DELAY` after the previous one (or as soon as it fails).
+/// The first established connection is returned. Timeout limits the whole operation.
+pub fn connect_addrs(addrs: &[SocketAddr], timeout: Option<Duration>) -> io::Result<TcpStream> {
+    connect_addrs_with(addrs, timeout, None)
+}
+
+/// Works like `connect_addrs`, but returns immediately once `cancel` is cancelled.
+pub(crate) fn connect_addrs_with(
+    addrs: &[SocketAddr],
+    timeout: Option<Duration>,
+    cancel: Option<&CancelToken>,
+) -> io::Result<TcpStream> {
+    let deadline = timeout.map(|timeout| Instant::now() + timeout);
+    let mut addrs = interleave_addrs(addrs).into_iter();
+    let (sender, receiver) = mpsc::channel();
+    let _registration = cancel.map(|token| token.register(Handle::Waker(sender.clone())));
+    let mut pending = 0;
+    let mut last_err = None;
+
+    loop {
+        if let Some(addr) = addrs.next() {
+            let sender = sender.clone();
+            let timeout =
+                deadline.map(|deadline| deadline.saturating_duration_since(Instant::now()));
+
+            thread::spawn(move || {
+                let res = match timeout {
+                    Some(timeout) => TcpStream::connect_timeout(&addr, timeout),
+                    None => TcpStream::connect(addr),
+                };
+                sender.send(res).unwrap_or(());
+            });
+            pending += 1;
+        }
+
+        if pending == 0 {
+            break;
+        }
+
+        let mut wait = match addrs.len() {
+            0 => None,
+            _ => Some(CONNECTION_ATTEMPT_DELAY),
+        };
+
+        if let Some(deadline) = deadline {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            wait = Some(wait.map_or(remaining, |wait| wait.min(remaining)));
+        }
+
+        let res = match wait {
+            Some(wait) => receiver.recv_timeout(wait),
+            None => receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+        };
+
+        if cancel.is_some_and(|token| token.is_cancelled()) {
+            return Err(cancel::cancelled());
+        }
+
+        match res {
+            Ok(Ok(stream)) => return Ok(stream),
+            Ok(Err(err)) => {
+                pending -= 1;
+                last_err = Some(err);
+            }
+            Err(_) => {
+                if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                    return Err(io::Error::new(
+                        io::ErrorKind::TimedOut,
+                        "Connection timed out",
+                    ));
+                }
+            }
+        }
+    }
+
+    Err(last_err.unwrap_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::AddrNotAvailable,
+            "No addresses to connect to",
+        )
+    }))
+}
+
+/// Works like `connect_addrs`, but tries addresses one after another in the current
+/// thread, for targets where threads can't be spawned. `cancel` is checked before
+/// each attempt, as a pending attempt can't be interrupted without another thread.
+pub(crate) fn connect_addrs_sequential(
+    addrs: &[SocketAddr],
+    timeout: Option<Duration>,
+    cancel: Option<&CancelToken>,
+) -> io::Result<TcpStream> {
+    let deadline = timeout.map(|timeout| Instant::now() + timeout);
+    let mut last_err = None;
+
+    for addr in interleave_addrs(addrs) {
+        if cancel.is_some_and(|token| token.is_cancelled()) {
+            return Err(cancel::cancelled());
+        }
+
+        let res = match deadline {
+            Some(deadline) => match deadline.checked_duration_since(Instant::now()) {
+                Some(remaining) if !remaining.is_zero() => {
+                    TcpStream::connect_timeout(&addr, remaining)
+                }
                 _ => {
-                    if idx + 1 == count {
-                        return Err(err);
-                    }
+                    return Err(io::Error::new(
+                        io::ErrorKind::TimedOut,
+                        "Connection timed out",
+                    ))
                 }
             },
+            None => TcpStream::connect(addr),
         };
+
+        match res {
+            Ok(stream) => return Ok(stream),
+            Err(err) => last_err = Some(err),
+        }
+    }
+
+    Err(last_err.unwrap_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::AddrNotAvailable,
+            "No addresses to connect to",
+        )
+    }))
+}
+
+/// Orders `addrs` so that IPv6 and IPv4 addresses alternate,
+/// starting with the family of the first address.
+pub(crate) fn interleave_addrs(addrs: &[SocketAddr]) -> Vec<SocketAddr> {
+    let first_ipv6 = addrs.first().is_some_and(|addr| addr.is_ipv6());
+    let (first, second): (Vec<SocketAddr>, Vec<SocketAddr>) =
+        addrs.iter().partition(|addr| addr.is_ipv6() == first_ipv6);
+    let (mut first, mut second) = (first.into_iter(), second.into_iter());
+    let mut interleaved = Vec::with_capacity(addrs.len());
+
+    loop {
+        match (first.next(), second.next()) {
+            (None, None) => break,
+            (a, b) => interleaved.extend(a.into_iter().chain(b)),
+        }
     }
 
-    Err(io::Error::new(
-        io::ErrorKind::AddrNotAvailable,
-        format!("Could not resolve address for {:?}", host),
-    ))
+    interleaved
 }
 
 /// Exexcutes a function in a loop until operation is completed or deadline is exceeded.
@@ -257,15 +764,59 @@ where
     Ok(())
 }
 
+/// Copies all bytes from `reader` to `writer` until EOF. Fails with `Error::Timeout`
+/// if `deadline` is exceeded or the transfer rate drops below `limit`, which is checked
+/// between reads.
+pub(crate) fn copy_with_deadline<R, W>(
+    reader: &mut R,
+    writer: &mut W,
+    deadline: Instant,
+    limit: Option<SpeedLimit>,
+) -> Result<(), Error>
+where
+    R: Read,
+    W: Write,
+{
+    let mut buf = [0; BUF_SIZE];
+    let mut period_start = Instant::now();
+    let mut period_bytes = 0;
+
+    execute_with_deadline(deadline, |_| {
+        let len = reader.read(&mut buf)?;
+        writer.write_all(&buf[..len])?;
+
+        if let Some(limit) = limit {
+            let now = Instant::now();
+            period_bytes += len as u64;
+
+            if now >= period_start + limit.period {
+                if period_bytes < limit.min_bytes() {
+                    return Err(Error::Timeout);
+                }
+
+                period_start = now;
+                period_bytes = 0;
+            }
+        }
+
+        Ok(len == 0)
+    })
+}
+
 /// Reads the head of HTTP response from `reader`.
 ///
 /// Reads from `reader` (line by line) until a blank line is identified,
 /// which indicates that all meta-information has been read,
+///
+/// Heads of informational (1xx) responses, e.g. `100 Continue`, are followed by
+/// another head. In such case reading continues until the final head is read.
+/// All of them are returned and can be parsed with `Response::from_head`.
 pub fn read_head<B>(reader: &mut B) -> Vec<u8>
 where
     B: BufRead,
 {
     let mut buf = Vec::with_capacity(BUF_SIZE);
+    let mut head_start = 0;
 
     loop {
         match reader.read_until(LF, &mut buf) {
@@ -274,6 +825,11 @@ where
                 let full_len = buf.len();
 
                 if len == 2 && &buf[full_len - 2..] == CR_LF {
+                    if is_interim(&buf[head_start..]) {
+                        head_start = full_len;
+                        continue;
+                    }
+
                     break;
                 }
             }
@@ -286,9 +842,14 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::{io::BufReader, sync::mpsc, thread};
+    use crate::test_util::TestServer;
+    #[cfg(feature = "native-tls")]
+    use crate::test_util::CERT_PEM;
+    use std::{
+        io::BufReader,
+        net::{Ipv4Addr, Ipv6Addr, TcpListener},
+    };
 
-    const URI: &str = "http://doc.rust-lang.org/std/string/index.html";
     const URI_S: &str = "https://en.wikipedia.org/wiki/Hypertext_Transfer_Protocol";
     const TIMEOUT: Duration = Duration::from_secs(3);
     const RESPONSE: &[u8; 129] = b"HTTP/1.1 200 OK\r\n\
@@ -302,56 +863,64 @@ mod tests {
                                    Content-Type: text/html\r\n\
                                    Content-Length: 100\r\n\r\n";
 
+    /// Starts a TLS server and returns a secure stream connected to it.
+    #[cfg(feature = "native-tls")]
+    fn connect_tls() -> (TestServer, Stream) {
+        let server = TestServer::start_tls([RESPONSE]).unwrap();
+        let path = std::env::temp_dir().join(format!("http_req-{}.pem", server.addr().port()));
+        std::fs::write(&path, CERT_PEM).unwrap();
+
+        let uri = server.uri("/");
+        let uri = Uri::try_from(uri.as_str()).unwrap();
+        let stream = Stream::connect(&uri, None).unwrap();
+        let stream = Stream::try_to_https(stream, &uri, Some(&path));
+        std::fs::remove_file(&path).unwrap();
+
+        (server, stream.unwrap())
+    }
+
     #[test]
     fn stream_new() {
-        {
-            let uri = Uri::try_from(URI).unwrap();
-            let stream = Stream::connect(&uri, None);
+        let server = TestServer::start([RESPONSE]).unwrap();
+        let uri = server.uri("/");
+        let uri = Uri::try_from(uri.as_str()).unwrap();
 
-            assert!(stream.is_ok());
-        }
-        {
-            let uri = Uri::try_from(URI).unwrap();
-            let stream = Stream::connect(&uri, Some(TIMEOUT));
+        // Both connections are kept open, so the server is still listening for the second one
+        let stream = Stream::connect(&uri, None);
+        assert!(stream.is_ok());
 
-            assert!(stream.is_ok());
-        }
+        let stream_timeout = Stream::connect(&uri, Some(TIMEOUT));
+        assert!(stream_timeout.is_ok());
     }
 
     #[test]
     fn stream_try_to_https() {
+        #[cfg(feature = "native-tls")]
         {
-            let uri = Uri::try_from(URI_S).unwrap();
-            let stream = Stream::connect(&uri, None).unwrap();
-            let https_stream = Stream::try_to_https(stream, &uri, None);
-
-            assert!(https_stream.is_ok());
-
             // Scheme is `https`, therefore stream should be converted into HTTPS variant
-            match https_stream.unwrap() {
-                Stream::Http(_) => assert!(false),
-                Stream::Https(_) => assert!(true),
-            }
+            let (_server, https_stream) = connect_tls();
+            assert!(matches!(https_stream, Stream::Https(_)));
         }
         {
-            let uri = Uri::try_from(URI).unwrap();
+            let server = TestServer::start([RESPONSE]).unwrap();
+            let uri = server.uri("/");
+            let uri = Uri::try_from(uri.as_str()).unwrap();
             let stream = Stream::connect(&uri, None).unwrap();
             let https_stream = Stream::try_to_https(stream, &uri, None);
 
             assert!(https_stream.is_ok());
 
             // Scheme is `http`, therefore stream should returned without changes
-            match https_stream.unwrap() {
-                Stream::Http(_) => assert!(true),
-                Stream::Https(_) => assert!(false),
-            }
+            assert!(matches!(https_stream.unwrap(), Stream::Http(_)));
         }
     }
 
     #[test]
     fn stream_set_read_timeot() {
         {
-            let uri = Uri::try_from(URI).unwrap();
+            let server = TestServer::start([RESPONSE]).unwrap();
+            let uri = server.uri("/");
+            let uri = Uri::try_from(uri.as_str()).unwrap();
             let mut stream = Stream::connect(&uri, None).unwrap();
             stream.set_read_timeout(Some(TIMEOUT)).unwrap();
 
@@ -363,10 +932,9 @@ mod tests {
 
             assert_eq!(inner_read_timeout, Some(TIMEOUT));
         }
+        #[cfg(feature = "native-tls")]
         {
-            let uri = Uri::try_from(URI_S).unwrap();
-            let mut stream = Stream::connect(&uri, None).unwrap();
-            stream = Stream::try_to_https(stream, &uri, None).unwrap();
+            let (_server, mut stream) = connect_tls();
             stream.set_read_timeout(Some(TIMEOUT)).unwrap();
 
             let inner_read_timeout = if let Stream::Https(inner) = stream {
@@ -382,7 +950,9 @@ mod tests {
     #[test]
     fn stream_set_write_timeot() {
         {
-            let uri = Uri::try_from(URI).unwrap();
+            let server = TestServer::start([RESPONSE]).unwrap();
+            let uri = server.uri("/");
+            let uri = Uri::try_from(uri.as_str()).unwrap();
             let mut stream = Stream::connect(&uri, None).unwrap();
             stream.set_write_timeout(Some(TIMEOUT)).unwrap();
 
@@ -394,10 +964,9 @@ mod tests {
 
             assert_eq!(inner_read_timeout, Some(TIMEOUT));
         }
+        #[cfg(feature = "native-tls")]
         {
-            let uri = Uri::try_from(URI_S).unwrap();
-            let mut stream = Stream::connect(&uri, None).unwrap();
-            stream = Stream::try_to_https(stream, &uri, None).unwrap();
+            let (_server, mut stream) = connect_tls();
             stream.set_write_timeout(Some(TIMEOUT)).unwrap();
 
             let inner_read_timeout = if let Stream::Https(inner) = stream {
@@ -474,6 +1043,57 @@ mod tests {
         assert_eq!(buf, RESPONSE);
     }
 
+    #[test]
+    fn thread_receive_receive_all_limited() {
+        let limit = SpeedLimit::new(100, Duration::from_millis(200));
+        {
+            let (sender, receiver) = mpsc::channel();
+            let deadline = Instant::now() + TIMEOUT;
+
+            thread::spawn(move || {
+                let res = [RESPONSE[..50].to_vec(), RESPONSE[50..].to_vec()];
+
+                for part in res {
+                    sender.send(part).unwrap();
+                }
+            });
+
+            let mut buf = Vec::with_capacity(BUF_SIZE);
+            buf.receive_all_limited(&receiver, deadline, limit).unwrap();
+
+            assert_eq!(buf, RESPONSE);
+        }
+        {
+            let (sender, receiver) = mpsc::channel();
+            let start_time = Instant::now();
+            let deadline = start_time + TIMEOUT;
+
+            thread::spawn(move || {
+                for byte in RESPONSE.iter() {
+                    if sender.send(vec![*byte]).is_err() {
+                        break;
+                    }
+                    thread::sleep(Duration::from_millis(50));
+                }
+            });
+
+            let mut buf = Vec::with_capacity(BUF_SIZE);
+            let res = buf.receive_all_limited(&receiver, deadline, limit);
+
+            assert!(matches!(res, Err(Error::Timeout)));
+            assert!(start_time.elapsed() < TIMEOUT);
+        }
+    }
+
+    #[test]
+    fn speed_limit_min_bytes() {
+        let limit = SpeedLimit::new(1000, Duration::from_millis(1500));
+
+        assert_eq!(limit.bytes_per_sec(), 1000);
+        assert_eq!(limit.period(), Duration::from_millis(1500));
+        assert_eq!(limit.min_bytes(), 1500);
+    }
+
     #[ignore]
     #[test]
     fn fn_execute_with_deadline() {
@@ -513,6 +1133,111 @@ mod tests {
         }
     }
 
+    #[test]
+    fn stream_set_nodelay_keepalive() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let mut stream = Stream::connect_to(&[addr], Some(TIMEOUT)).unwrap();
+        let keepalive =
+            Keepalive::new(Duration::from_secs(60)).with_interval(Duration::from_secs(5));
+
+        stream.set_nodelay(true).unwrap();
+        assert!(stream.tcp_stream().unwrap().nodelay().unwrap());
+
+        stream.set_keepalive(Some(keepalive)).unwrap();
+        assert!(SockRef::from(stream.tcp_stream().unwrap())
+            .keepalive()
+            .unwrap());
+
+        stream.set_keepalive(None).unwrap();
+        assert!(!SockRef::from(stream.tcp_stream().unwrap())
+            .keepalive()
+            .unwrap());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn stream_connect_unix() {
+        use std::os::unix::net::UnixListener;
+
+        let path = std::env::temp_dir().join(format!("http_req-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+
+        let mut stream = Stream::connect_unix(&path).unwrap();
+        let (mut server, _) = listener.accept().unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        stream.set_read_timeout(Some(TIMEOUT)).unwrap();
+        stream.set_nodelay(true).unwrap();
+        stream.set_keepalive(None).unwrap();
+        assert!(stream.peer_addr().is_err());
+
+        stream.write_all(b"ping").unwrap();
+        let mut buf = [0; 4];
+        server.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"ping");
+
+        let uri = Uri::try_from(URI_S).unwrap();
+        let stream = Stream::try_to_https(stream, &uri, None).unwrap();
+        assert!(matches!(stream, Stream::Unix(_)));
+    }
+
+    #[test]
+    fn fn_connect_addrs() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        // Address from TEST-NET-1, which is never reachable.
+        let addrs = [
+            SocketAddr::from(([192, 0, 2, 1], port)),
+            SocketAddr::from(([127, 0, 0, 1], port)),
+        ];
+
+        let start_time = Instant::now();
+        let stream = connect_addrs(&addrs, Some(TIMEOUT)).unwrap();
+
+        assert_eq!(stream.peer_addr().unwrap(), addrs[1]);
+        assert!(start_time.elapsed() < TIMEOUT);
+
+        drop(listener);
+        assert!(connect_addrs(&addrs[1..], Some(TIMEOUT)).is_err());
+        assert!(connect_addrs(&[], Some(TIMEOUT)).is_err());
+    }
+
+    #[test]
+    fn fn_connect_addrs_sequential() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let stream = connect_addrs_sequential(&[addr], Some(TIMEOUT), None).unwrap();
+        assert_eq!(stream.peer_addr().unwrap(), addr);
+
+        let token = CancelToken::new();
+        token.cancel();
+        let err = connect_addrs_sequential(&[addr], Some(TIMEOUT), Some(&token)).unwrap_err();
+        assert_eq!(err.kind(), cancel::cancelled().kind());
+
+        drop(listener);
+        assert!(connect_addrs_sequential(&[addr], Some(TIMEOUT), None).is_err());
+        assert!(connect_addrs_sequential(&[], Some(TIMEOUT), None).is_err());
+    }
+
+    #[test]
+    fn fn_interleave_addrs() {
+        let v4 = |n| SocketAddr::from((Ipv4Addr::new(10, 0, 0, n), 80));
+        let v6 = |n| SocketAddr::from((Ipv6Addr::new(0xfd, 0, 0, 0, 0, 0, 0, n), 80));
+
+        assert_eq!(
+            interleave_addrs(&[v6(1), v6(2), v6(3), v4(1), v4(2)]),
+            [v6(1), v4(1), v6(2), v4(2), v6(3)]
+        );
+        assert_eq!(
+            interleave_addrs(&[v4(1), v4(2), v6(1), v6(2), v6(3)]),
+            [v4(1), v6(1), v4(2), v6(2), v6(3)]
+        );
+        assert_eq!(interleave_addrs(&[]), []);
+    }
+
     #[test]
     fn fn_read_head() {
         let reader = RESPONSE.as_slice();
@@ -521,4 +1246,23 @@ mod tests {
 
         assert_eq!(raw_head, RESPONSE_H);
     }
+
+    #[test]
+    fn fn_read_head_interim() {
+        {
+            let response = [b"HTTP/1.1 100 Continue\r\n\r\n".as_slice(), RESPONSE].concat();
+            let mut buf_reader = BufReader::new(response.as_slice());
+            let raw_head = read_head(&mut buf_reader);
+
+            assert_eq!(raw_head, response[..response.len() - 27]);
+        }
+        {
+            const SWITCH: &[u8] = b"HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\n\r\n";
+            let response = [SWITCH, b"data"].concat();
+            let mut buf_reader = BufReader::new(response.as_slice());
+            let raw_head = read_head(&mut buf_reader);
+
+            assert_eq!(raw_head, SWITCH);
+        }
+    }
 }
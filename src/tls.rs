@@ -1,25 +1,37 @@
 //! secure connection over TLS
-use crate::error::Error as HttpError;
-use std::{
-    fs::File,
-    io::{self, BufReader},
-    path::Path,
+use crate::{
+    date::from_civil,
+    error::{CertificateError, Error as HttpError, ParseErr},
 };
+use base64::engine::{general_purpose::STANDARD, Engine};
+use sha2::{Digest, Sha256};
+use std::{io, path::Path, time::SystemTime};
+
+#[cfg(any(feature = "native-tls", feature = "rust-tls"))]
+use std::{fs::File, io::BufReader};
 
 #[cfg(feature = "native-tls")]
 use std::io::prelude::*;
 
+#[cfg(any(feature = "tokio-native-tls", feature = "tokio-rust-tls"))]
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+#[cfg(any(feature = "tokio-native-tls", feature = "tokio-rust-tls"))]
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
 #[cfg(feature = "rust-tls")]
 use rustls::{ClientConnection, StreamOwned};
 #[cfg(feature = "rust-tls")]
 use rustls_pki_types::ServerName;
 
-#[cfg(not(any(feature = "native-tls", feature = "rust-tls")))]
-compile_error!("one of the `native-tls` or `rust-tls` features must be enabled");
-
 /// Wrapper around TLS Stream, depends on selected TLS library:
 /// - native_tls: `TlsStream<S>`
 /// - rustls: `StreamOwned<ClientConnection, S>`
+///
+/// Without any of them (e.g. on WebAssembly targets), secure connections can't be
+/// established and `Config::connect` always fails.
 #[derive(Debug)]
 pub struct Conn<S: io::Read + io::Write> {
     #[cfg(feature = "native-tls")]
@@ -27,6 +39,50 @@ pub struct Conn<S: io::Read + io::Write> {
 
     #[cfg(feature = "rust-tls")]
     stream: rustls::StreamOwned<rustls::ClientConnection, S>,
+
+    #[cfg(not(any(feature = "native-tls", feature = "rust-tls")))]
+    stream: NoTls<S>,
+}
+
+/// Wrapper around asynchronous TLS Stream of Tokio runtime, depends on selected TLS library:
+/// - native_tls: `tokio_native_tls::TlsStream<S>`
+/// - rustls: `tokio_rustls::client::TlsStream<S>`
+#[cfg(any(feature = "tokio-native-tls", feature = "tokio-rust-tls"))]
+#[derive(Debug)]
+pub struct AsyncConn<S> {
+    #[cfg(feature = "tokio-native-tls")]
+    stream: tokio_native_tls::TlsStream<S>,
+
+    #[cfg(feature = "tokio-rust-tls")]
+    stream: tokio_rustls::client::TlsStream<S>,
+}
+
+/// Stand-in for a TLS stream when no TLS library is enabled. It can't be created.
+#[cfg(not(any(feature = "native-tls", feature = "rust-tls")))]
+#[derive(Debug)]
+struct NoTls<S>(std::convert::Infallible, std::marker::PhantomData<S>);
+
+#[cfg(not(any(feature = "native-tls", feature = "rust-tls")))]
+impl<S> NoTls<S> {
+    fn get_ref(&self) -> &S {
+        match self.0 {}
+    }
+
+    fn get_mut(&mut self) -> &mut S {
+        match self.0 {}
+    }
+
+    fn read(&mut self, _buf: &mut [u8]) -> Result<usize, io::Error> {
+        match self.0 {}
+    }
+
+    fn write(&mut self, _buf: &[u8]) -> Result<usize, io::Error> {
+        match self.0 {}
+    }
+
+    fn flush(&mut self) -> Result<(), io::Error> {
+        match self.0 {}
+    }
 }
 
 impl<S> Conn<S>
@@ -42,6 +98,126 @@ where
     pub fn get_mut(&mut self) -> &mut S {
         self.stream.get_mut()
     }
+
+    /// Returns certificates presented by the server, starting with its own certificate.
+    /// native-tls exposes only the certificate of the server, without the rest of the chain.
+    pub fn peer_certificates(&self) -> Vec<Certificate> {
+        #[cfg(feature = "native-tls")]
+        let chain: Vec<Vec<u8>> = self
+            .stream
+            .peer_certificate()
+            .ok()
+            .flatten()
+            .and_then(|cert| cert.to_der().ok())
+            .into_iter()
+            .collect();
+
+        #[cfg(feature = "rust-tls")]
+        let chain: Vec<Vec<u8>> = self
+            .stream
+            .conn
+            .peer_certificates()
+            .unwrap_or_default()
+            .iter()
+            .map(|cert| cert.to_vec())
+            .collect();
+
+        #[cfg(not(any(feature = "native-tls", feature = "rust-tls")))]
+        let chain: Vec<Vec<u8>> = Vec::new();
+
+        chain.into_iter().map(Certificate::from_der).collect()
+    }
+}
+
+#[cfg(any(feature = "tokio-native-tls", feature = "tokio-rust-tls"))]
+impl<S> AsyncConn<S>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    /// Returns a reference to the underlying socket.
+    pub fn get_ref(&self) -> &S {
+        #[cfg(feature = "tokio-native-tls")]
+        let stream = self.stream.get_ref().get_ref().get_ref();
+
+        #[cfg(feature = "tokio-rust-tls")]
+        let (stream, _) = self.stream.get_ref();
+
+        stream
+    }
+
+    /// Returns a mutable reference to the underlying socket.
+    pub fn get_mut(&mut self) -> &mut S {
+        #[cfg(feature = "tokio-native-tls")]
+        let stream = self.stream.get_mut().get_mut().get_mut();
+
+        #[cfg(feature = "tokio-rust-tls")]
+        let (stream, _) = self.stream.get_mut();
+
+        stream
+    }
+
+    /// Returns certificates presented by the server (see `Conn::peer_certificates`).
+    pub fn peer_certificates(&self) -> Vec<Certificate> {
+        #[cfg(feature = "tokio-native-tls")]
+        let chain: Vec<Vec<u8>> = self
+            .stream
+            .get_ref()
+            .peer_certificate()
+            .ok()
+            .flatten()
+            .and_then(|cert| cert.to_der().ok())
+            .into_iter()
+            .collect();
+
+        #[cfg(feature = "tokio-rust-tls")]
+        let chain: Vec<Vec<u8>> = self
+            .stream
+            .get_ref()
+            .1
+            .peer_certificates()
+            .unwrap_or_default()
+            .iter()
+            .map(|cert| cert.to_vec())
+            .collect();
+
+        chain.into_iter().map(Certificate::from_der).collect()
+    }
+}
+
+#[cfg(any(feature = "tokio-native-tls", feature = "tokio-rust-tls"))]
+impl<S> AsyncRead for AsyncConn<S>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().stream).poll_read(cx, buf)
+    }
+}
+
+#[cfg(any(feature = "tokio-native-tls", feature = "tokio-rust-tls"))]
+impl<S> AsyncWrite for AsyncConn<S>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.get_mut().stream).poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().stream).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().stream).poll_shutdown(cx)
+    }
 }
 
 impl<S> io::Read for Conn<S>
@@ -82,35 +258,201 @@ where
     }
 }
 
+/// Versions of TLS protocol.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum TlsVersion {
+    Tls10,
+    Tls11,
+    Tls12,
+    Tls13,
+}
+
 /// Client configuration for TLS connection.
 pub struct Config {
     #[cfg(feature = "native-tls")]
     extra_root_certs: Vec<native_tls::Certificate>,
+    #[cfg(feature = "native-tls")]
+    native_tls_connector: Option<native_tls::TlsConnector>,
     #[cfg(feature = "rust-tls")]
     root_certs: std::sync::Arc<rustls::RootCertStore>,
+    #[cfg(feature = "rust-tls")]
+    rustls_config: Option<std::sync::Arc<rustls::ClientConfig>>,
+    accept_invalid_certs: bool,
+    accept_invalid_hostnames: bool,
+    min_version: Option<TlsVersion>,
+    max_version: Option<TlsVersion>,
+    pins: Vec<[u8; 32]>,
+    server_name: Option<String>,
 }
 
 impl Default for Config {
+    #[cfg(not(any(feature = "native-tls", feature = "rust-tls")))]
+    fn default() -> Self {
+        Config {
+            accept_invalid_certs: false,
+            accept_invalid_hostnames: false,
+            min_version: None,
+            max_version: None,
+            pins: Vec::new(),
+            server_name: None,
+        }
+    }
+
     #[cfg(feature = "native-tls")]
     fn default() -> Self {
         Config {
             extra_root_certs: vec![],
+            native_tls_connector: None,
+            accept_invalid_certs: false,
+            accept_invalid_hostnames: false,
+            min_version: None,
+            max_version: None,
+            pins: Vec::new(),
+            server_name: None,
         }
     }
 
     #[cfg(feature = "rust-tls")]
     fn default() -> Self {
-        let root_store = rustls::RootCertStore {
-            roots: webpki_roots::TLS_SERVER_ROOTS.iter().cloned().collect(),
+        #[allow(unused_mut)]
+        let mut root_store = rustls::RootCertStore {
+            roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
         };
 
+        #[cfg(feature = "rust-tls-native-roots")]
+        root_store.add_parsable_certificates(native_root_certs().iter().cloned());
+
         Config {
             root_certs: std::sync::Arc::new(root_store),
+            rustls_config: None,
+            accept_invalid_certs: false,
+            accept_invalid_hostnames: false,
+            min_version: None,
+            max_version: None,
+            pins: Vec::new(),
+            server_name: None,
         }
     }
 }
 
 impl Config {
+    /// Controls the use of certificate validation. Defaults to `false`.
+    ///
+    /// # Warning
+    /// If set to `true`, any certificate is trusted for use, including expired,
+    /// self-signed and revoked ones, as well as certificates issued for other hosts.
+    /// This makes the connection vulnerable to man-in-the-middle attacks. It should be
+    /// used only for talking to known development servers and appliances.
+    pub fn danger_accept_invalid_certs(&mut self, accept: bool) -> &mut Self {
+        self.accept_invalid_certs = accept;
+        self
+    }
+
+    /// Controls the use of hostname verification. Defaults to `false`.
+    ///
+    /// # Warning
+    /// If set to `true`, certificates issued for any host are accepted, as long as
+    /// they're otherwise valid (e.g. signed by a trusted root, not expired). This makes
+    /// the connection vulnerable to man-in-the-middle attacks by anyone holding a valid
+    /// certificate. It's meant e.g. for connecting to an IP address of a server, whose
+    /// certificate only names its domain.
+    pub fn danger_accept_invalid_hostnames(&mut self, accept: bool) -> &mut Self {
+        self.accept_invalid_hostnames = accept;
+        self
+    }
+
+    /// Sets the minimum supported version of TLS protocol. If `None` is provided,
+    /// the default of the TLS library is used.
+    ///
+    /// rustls supports only TLS 1.2 and 1.3. native-tls can't require TLS 1.3,
+    /// connecting fails in such a case.
+    pub fn min_protocol_version(&mut self, version: Option<TlsVersion>) -> &mut Self {
+        self.min_version = version;
+        self
+    }
+
+    /// Sets the maximum supported version of TLS protocol. If `None` is provided,
+    /// the default of the TLS library is used.
+    pub fn max_protocol_version(&mut self, version: Option<TlsVersion>) -> &mut Self {
+        self.max_version = version;
+        self
+    }
+
+    /// Sets the server name, which is sent in SNI and checked against the certificate,
+    /// instead of the host passed to `connect`. If `None` is provided, the host is used.
+    pub fn server_name(&mut self, name: Option<&str>) -> &mut Self {
+        self.server_name = name.map(String::from);
+        self
+    }
+
+    /// Pins a public key by SHA-256 fingerprint of its DER-encoded SubjectPublicKeyInfo.
+    /// If any keys are pinned, connecting fails unless the certificate chain presented
+    /// by the server contains one of them.
+    ///
+    /// Pinning is checked in addition to the usual validation of certificates.
+    /// native-tls exposes only the certificate of the server itself, so keys of
+    /// intermediate and root certificates can be pinned only with rustls.
+    pub fn pin_public_key(&mut self, sha256: [u8; 32]) -> &mut Self {
+        self.pins.push(sha256);
+        self
+    }
+
+    /// Pins a public key by Base64-encoded SHA-256 fingerprint (as in `pin-sha256`
+    /// directives), e.g. produced with:
+    ///
+    /// `openssl x509 -pubkey -noout -in cert.pem | openssl pkey -pubin -outform der | openssl dgst -sha256 -binary | base64`
+    pub fn pin_public_key_base64(&mut self, pin: &str) -> Result<&mut Self, HttpError> {
+        let sha256 = STANDARD
+            .decode(pin.trim())
+            .ok()
+            .and_then(|pin| <[u8; 32]>::try_from(pin).ok())
+            .ok_or(ParseErr::Invalid)?;
+
+        Ok(self.pin_public_key(sha256))
+    }
+
+    /// Checks if any certificate from `chain` (DER-encoded) contains a pinned public key.
+    #[cfg_attr(
+        not(any(feature = "native-tls", feature = "rust-tls")),
+        allow(dead_code)
+    )]
+    fn check_pins<'c, I>(&self, chain: I) -> Result<(), HttpError>
+    where
+        I: IntoIterator<Item = &'c [u8]>,
+    {
+        if self.pins.is_empty() {
+            return Ok(());
+        }
+
+        let pinned = chain
+            .into_iter()
+            .filter_map(public_key_sha256)
+            .any(|key| self.pins.contains(&key));
+
+        if pinned {
+            Ok(())
+        } else {
+            Err(HttpError::Certificate(CertificateError::NotPinned))
+        }
+    }
+
+    /// Adds root certificates (X.509) from PEM file.
+    /// Fails, as no TLS library is enabled.
+    #[cfg(not(any(feature = "native-tls", feature = "rust-tls")))]
+    pub fn add_root_cert_file_pem(&mut self, _file_path: &Path) -> Result<&mut Self, HttpError> {
+        Err(no_tls())
+    }
+
+    /// Establishes a secure connection. Fails, as no TLS library is enabled.
+    #[cfg(not(any(feature = "native-tls", feature = "rust-tls")))]
+    pub fn connect<H, S>(&self, _hostname: H, _stream: S) -> Result<Conn<S>, HttpError>
+    where
+        H: AsRef<str>,
+        S: io::Read + io::Write,
+    {
+        Err(no_tls())
+    }
+
     /// Adds root certificates (X.509) from PEM file.
     #[cfg(feature = "native-tls")]
     pub fn add_root_cert_file_pem(&mut self, file_path: &Path) -> Result<&mut Self, HttpError> {
@@ -134,6 +476,18 @@ impl Config {
         Ok(self)
     }
 
+    /// Uses the prepared `native_tls::TlsConnector` for connections, giving access to
+    /// options of the platform TLS library, which aren't covered by this `Config`.
+    ///
+    /// If set, root certificates, protocol versions and the `danger_*` settings of this
+    /// `Config` are ignored, as they're part of `native_tls::TlsConnector`. Pinned public
+    /// keys are still checked.
+    #[cfg(feature = "native-tls")]
+    pub fn native_tls_connector(&mut self, connector: native_tls::TlsConnector) -> &mut Self {
+        self.native_tls_connector = Some(connector);
+        self
+    }
+
     /// Establishes a secure connection.
     #[cfg(feature = "native-tls")]
     pub fn connect<H, S>(&self, hostname: H, stream: S) -> Result<Conn<S>, HttpError>
@@ -141,16 +495,83 @@ impl Config {
         H: AsRef<str>,
         S: io::Read + io::Write,
     {
+        let hostname = self.server_name.as_deref().unwrap_or(hostname.as_ref());
+        let connector = match &self.native_tls_connector {
+            Some(connector) => connector.clone(),
+            None => self.build_native_tls_connector()?,
+        };
+
+        let stream = connector
+            .connect(hostname, stream)
+            .map_err(handshake_error)?;
+
+        if !self.pins.is_empty() {
+            let cert = stream.peer_certificate()?.map(|c| c.to_der()).transpose()?;
+            self.check_pins(cert.as_deref())?;
+        }
+
+        Ok(Conn { stream })
+    }
+
+    /// Establishes a secure connection asynchronously, in Tokio runtime.
+    #[cfg(feature = "tokio-native-tls")]
+    pub async fn connect_async<H, S>(
+        &self,
+        hostname: H,
+        stream: S,
+    ) -> Result<AsyncConn<S>, HttpError>
+    where
+        H: AsRef<str>,
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        let hostname = self.server_name.as_deref().unwrap_or(hostname.as_ref());
+        let connector = match &self.native_tls_connector {
+            Some(connector) => connector.clone(),
+            None => self.build_native_tls_connector()?,
+        };
+
+        let stream = tokio_native_tls::TlsConnector::from(connector)
+            .connect(hostname, stream)
+            .await
+            .map_err(|e| handshake_failure(&e))?;
+
+        if !self.pins.is_empty() {
+            let cert = stream.get_ref().peer_certificate()?;
+            let cert = cert.map(|c| c.to_der()).transpose()?;
+            self.check_pins(cert.as_deref())?;
+        }
+
+        Ok(AsyncConn { stream })
+    }
+
+    /// Creates `native_tls::TlsConnector` from the settings of this `Config`.
+    #[cfg(feature = "native-tls")]
+    fn build_native_tls_connector(&self) -> Result<native_tls::TlsConnector, HttpError> {
         let mut connector_builder = native_tls::TlsConnector::builder();
 
         for crt in self.extra_root_certs.iter() {
             connector_builder.add_root_certificate((*crt).clone());
         }
 
-        let connector = connector_builder.build()?;
-        let stream = connector.connect(hostname.as_ref(), stream)?;
+        connector_builder.danger_accept_invalid_certs(self.accept_invalid_certs);
+        connector_builder.danger_accept_invalid_hostnames(self.accept_invalid_hostnames);
 
-        Ok(Conn { stream })
+        let protocol = |version| match version {
+            TlsVersion::Tls10 => Some(native_tls::Protocol::Tlsv10),
+            TlsVersion::Tls11 => Some(native_tls::Protocol::Tlsv11),
+            TlsVersion::Tls12 => Some(native_tls::Protocol::Tlsv12),
+            TlsVersion::Tls13 => None,
+        };
+
+        if let Some(min) = self.min_version {
+            let min = protocol(min).ok_or_else(unsupported_versions)?;
+            connector_builder.min_protocol_version(Some(min));
+        }
+
+        // TLS 1.3 is the highest version, so it doesn't limit anything.
+        connector_builder.max_protocol_version(self.max_version.and_then(protocol));
+
+        Ok(connector_builder.build()?)
     }
 
     /// Adds root certificates (X.509) from a PEM file.
@@ -176,6 +597,18 @@ impl Config {
         Ok(self)
     }
 
+    /// Uses the prepared `rustls::ClientConfig` for connections, giving full control over
+    /// ciphers, certificate verification, ALPN, session resumption etc.
+    ///
+    /// If set, root certificates, protocol versions and the `danger_*` settings of this
+    /// `Config` are ignored, as they're part of `rustls::ClientConfig`. Pinned public keys
+    /// are still checked.
+    #[cfg(feature = "rust-tls")]
+    pub fn rustls_config(&mut self, config: std::sync::Arc<rustls::ClientConfig>) -> &mut Self {
+        self.rustls_config = Some(config);
+        self
+    }
+
     /// Establishes a secure connection.
     #[cfg(feature = "rust-tls")]
     pub fn connect<H, S>(&self, hostname: H, stream: S) -> Result<Conn<S>, HttpError>
@@ -183,20 +616,607 @@ impl Config {
         H: AsRef<str>,
         S: io::Read + io::Write,
     {
-        let hostname = hostname.as_ref().to_string();
-
-        let client_config = rustls::ClientConfig::builder()
-            .with_root_certificates(self.root_certs.clone())
-            .with_no_client_auth();
+        let hostname = self
+            .server_name
+            .clone()
+            .unwrap_or_else(|| hostname.as_ref().to_string());
+        let client_config = match &self.rustls_config {
+            Some(config) => config.clone(),
+            None => std::sync::Arc::new(self.build_rustls_config()?),
+        };
 
         let session = ClientConnection::new(
-            std::sync::Arc::new(client_config),
+            client_config,
             ServerName::try_from(hostname).map_err(|_| HttpError::Tls)?,
         )
         .map_err(|_| HttpError::Tls)?;
 
-        let stream = StreamOwned::new(session, stream);
+        let mut stream = StreamOwned::new(session, stream);
+
+        // Handshake is completed eagerly, so that its errors can be told apart from IO errors.
+        while stream.conn.is_handshaking() {
+            stream
+                .conn
+                .complete_io(&mut stream.sock)
+                .map_err(handshake_error)?;
+        }
+
+        let chain = stream.conn.peer_certificates().unwrap_or_default();
+        self.check_pins(chain.iter().map(|cert| cert.as_ref()))?;
 
         Ok(Conn { stream })
     }
+
+    /// Establishes a secure connection asynchronously, in Tokio runtime.
+    #[cfg(feature = "tokio-rust-tls")]
+    pub async fn connect_async<H, S>(
+        &self,
+        hostname: H,
+        stream: S,
+    ) -> Result<AsyncConn<S>, HttpError>
+    where
+        H: AsRef<str>,
+        S: AsyncRead + AsyncWrite + Unpin,
+    {
+        let hostname = self
+            .server_name
+            .clone()
+            .unwrap_or_else(|| hostname.as_ref().to_string());
+        let client_config = match &self.rustls_config {
+            Some(config) => config.clone(),
+            None => std::sync::Arc::new(self.build_rustls_config()?),
+        };
+        let server_name = ServerName::try_from(hostname).map_err(|_| HttpError::Tls)?;
+
+        let stream = tokio_rustls::TlsConnector::from(client_config)
+            .connect(server_name, stream)
+            .await
+            .map_err(handshake_error)?;
+
+        let chain = stream.get_ref().1.peer_certificates().unwrap_or_default();
+        self.check_pins(chain.iter().map(|cert| cert.as_ref()))?;
+
+        Ok(AsyncConn { stream })
+    }
+
+    /// Creates `rustls::ClientConfig` from the settings of this `Config`.
+    #[cfg(feature = "rust-tls")]
+    fn build_rustls_config(&self) -> Result<rustls::ClientConfig, HttpError> {
+        let versions: Vec<_> = [
+            (TlsVersion::Tls12, &rustls::version::TLS12),
+            (TlsVersion::Tls13, &rustls::version::TLS13),
+        ]
+        .into_iter()
+        .filter(|(v, _)| self.min_version.is_none_or(|min| *v >= min))
+        .filter(|(v, _)| self.max_version.is_none_or(|max| *v <= max))
+        .map(|(_, version)| version)
+        .collect();
+
+        if versions.is_empty() {
+            return Err(unsupported_versions());
+        }
+
+        let mut client_config = rustls::ClientConfig::builder_with_protocol_versions(&versions)
+            .with_root_certificates(self.root_certs.clone())
+            .with_no_client_auth();
+
+        if self.accept_invalid_certs || self.accept_invalid_hostnames {
+            let verifier = DangerVerifier {
+                provider: client_config.crypto_provider().clone(),
+                roots: if self.accept_invalid_certs {
+                    None
+                } else {
+                    Some(self.root_certs.clone())
+                },
+            };
+
+            client_config
+                .dangerous()
+                .set_certificate_verifier(std::sync::Arc::new(verifier));
+        }
+
+        Ok(client_config)
+    }
+}
+
+/// Returns root certificates from the trust store of the operating system.
+/// They're loaded once, certificates which can't be loaded are skipped.
+#[cfg(feature = "rust-tls-native-roots")]
+fn native_root_certs() -> &'static [rustls_pki_types::CertificateDer<'static>] {
+    static CERTS: std::sync::OnceLock<Vec<rustls_pki_types::CertificateDer<'static>>> =
+        std::sync::OnceLock::new();
+
+    CERTS.get_or_init(|| rustls_native_certs::load_native_certs().certs)
+}
+
+/// Returns SHA-256 fingerprint of the public key (DER-encoded SubjectPublicKeyInfo)
+/// from a DER-encoded X.509 certificate. Returns `None` if the certificate is malformed.
+///
+/// # Examples
+/// ```
+/// use http_req::tls::public_key_sha256;
+///
+/// assert_eq!(public_key_sha256(b"not a certificate"), None);
+/// ```
+pub fn public_key_sha256(cert: &[u8]) -> Option<[u8; 32]> {
+    let [.., spki] = tbs_fields(cert)?;
+    Some(Sha256::digest(spki).into())
+}
+
+/// X.509 certificate presented by a server.
+///
+/// # Examples
+/// ```no_run
+/// use http_req::request;
+///
+/// let mut body = Vec::new();
+/// let response = request::get("https://www.rust-lang.org/learn", &mut body).unwrap();
+///
+/// for cert in response.peer_certificates() {
+///     println!("{:?} issued by {:?}", cert.subject(), cert.issuer());
+///     println!("expires at {:?}", cert.not_after());
+/// }
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+pub struct Certificate {
+    der: Vec<u8>,
+}
+
+impl Certificate {
+    /// Creates a new `Certificate` from its DER encoding.
+    pub fn from_der(der: Vec<u8>) -> Certificate {
+        Certificate { der }
+    }
+
+    /// Returns DER encoding of this certificate.
+    pub fn der(&self) -> &[u8] {
+        &self.der
+    }
+
+    /// Returns the subject, e.g. `C=US, O=Example, CN=example.com`.
+    /// Returns `None` if the certificate is malformed.
+    pub fn subject(&self) -> Option<String> {
+        let [_, _, _, _, subject, _] = tbs_fields(&self.der)?;
+        fmt_name(subject)
+    }
+
+    /// Returns the issuer, e.g. `C=US, O=Example CA, CN=Example Root`.
+    /// Returns `None` if the certificate is malformed.
+    pub fn issuer(&self) -> Option<String> {
+        let [_, _, issuer, _, _, _] = tbs_fields(&self.der)?;
+        fmt_name(issuer)
+    }
+
+    /// Returns the time from which the certificate is valid.
+    /// Returns `None` if the certificate is malformed.
+    pub fn not_before(&self) -> Option<SystemTime> {
+        self.validity().map(|(not_before, _)| not_before)
+    }
+
+    /// Returns the time at which the certificate expires.
+    /// Returns `None` if the certificate is malformed.
+    pub fn not_after(&self) -> Option<SystemTime> {
+        self.validity().map(|(_, not_after)| not_after)
+    }
+
+    /// Returns SHA-256 fingerprint of the public key. See `public_key_sha256`.
+    pub fn public_key_sha256(&self) -> Option<[u8; 32]> {
+        public_key_sha256(&self.der)
+    }
+
+    fn validity(&self) -> Option<(SystemTime, SystemTime)> {
+        let [_, _, _, validity, _, _] = tbs_fields(&self.der)?;
+        let (_, validity, _) = der_next(validity)?;
+        let (not_before, _, rest) = der_next(validity)?;
+        let (not_after, _, _) = der_next(rest)?;
+
+        Some((parse_time(not_before)?, parse_time(not_after)?))
+    }
+}
+
+/// Returns DER elements of TBSCertificate of a DER-encoded X.509 certificate:
+/// serial number, signature algorithm, issuer, validity, subject and SubjectPublicKeyInfo.
+fn tbs_fields(cert: &[u8]) -> Option<[&[u8]; 6]> {
+    let (_, cert, _) = der_next(cert)?;
+    let (_, mut tbs, _) = der_next(cert)?;
+
+    // Version is optional and tagged explicitly with [0].
+    if tbs.first() == Some(&0xa0) {
+        tbs = der_next(tbs)?.2;
+    }
+
+    let mut fields = [&[][..]; 6];
+    for field in fields.iter_mut() {
+        let (element, _, rest) = der_next(tbs)?;
+        *field = element;
+        tbs = rest;
+    }
+
+    Some(fields)
+}
+
+/// Formats a DER-encoded X.501 Name as comma-separated `type=value` pairs.
+fn fmt_name(name: &[u8]) -> Option<String> {
+    let (_, mut rdns, _) = der_next(name)?;
+    let mut parts = Vec::new();
+
+    while !rdns.is_empty() {
+        let (_, mut attrs, rest) = der_next(rdns)?;
+        rdns = rest;
+
+        while !attrs.is_empty() {
+            let (_, attr, rest) = der_next(attrs)?;
+            attrs = rest;
+
+            let (_, oid, attr) = der_next(attr)?;
+            let (value, content, _) = der_next(attr)?;
+
+            let value = match value[0] {
+                // BMPString
+                0x1e => {
+                    let units: Vec<u16> = content
+                        .chunks(2)
+                        .map(|c| u16::from_be_bytes([c[0], *c.get(1).unwrap_or(&0)]))
+                        .collect();
+                    String::from_utf16_lossy(&units)
+                }
+                _ => String::from_utf8_lossy(content).into_owned(),
+            };
+
+            parts.push(format!("{}={}", fmt_attr_type(oid), value));
+        }
+    }
+
+    Some(parts.join(", "))
+}
+
+/// Returns the short name of a common attribute type or its OID in dotted notation.
+fn fmt_attr_type(oid: &[u8]) -> String {
+    let name = match oid {
+        [0x55, 0x04, 0x03] => "CN",
+        [0x55, 0x04, 0x06] => "C",
+        [0x55, 0x04, 0x07] => "L",
+        [0x55, 0x04, 0x08] => "ST",
+        [0x55, 0x04, 0x0a] => "O",
+        [0x55, 0x04, 0x0b] => "OU",
+        [0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x09, 0x01] => "emailAddress",
+        _ => {
+            let mut arcs = Vec::new();
+            let mut arc: u64 = 0;
+
+            for b in oid {
+                arc = (arc << 7) | (b & 0x7f) as u64;
+
+                if b & 0x80 == 0 {
+                    if arcs.is_empty() {
+                        let first = (arc / 40).min(2);
+                        arcs.push(first);
+                        arcs.push(arc - first * 40);
+                    } else {
+                        arcs.push(arc);
+                    }
+                    arc = 0;
+                }
+            }
+
+            let arcs: Vec<_> = arcs.iter().map(u64::to_string).collect();
+            return arcs.join(".");
+        }
+    };
+
+    name.to_string()
+}
+
+/// Parses a DER-encoded UTCTime (`YYMMDDHHMMSSZ`) or GeneralizedTime (`YYYYMMDDHHMMSSZ`).
+fn parse_time(time: &[u8]) -> Option<SystemTime> {
+    let (_, content, _) = der_next(time)?;
+    let content = std::str::from_utf8(content).ok()?.strip_suffix('Z')?;
+
+    // Lengths below are in bytes, so other characters could split a multibyte one.
+    if !content.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    let (year, rest) = match time[0] {
+        0x17 if content.len() == 12 => {
+            // Years 50-99 are 1950-1999, 00-49 are 2000-2049 (RFC 5280).
+            let year: i64 = content[..2].parse().ok()?;
+            (
+                if year < 50 { 2000 + year } else { 1900 + year },
+                &content[2..],
+            )
+        }
+        0x18 if content.len() == 14 => (content[..4].parse().ok()?, &content[4..]),
+        _ => return None,
+    };
+
+    let num = |i: usize| rest.get(i..i + 2)?.parse::<u32>().ok();
+
+    from_civil(
+        year,
+        num(0)?,
+        num(2)?,
+        num(4)? as i64,
+        num(6)? as i64,
+        num(8)? as i64,
+    )
+}
+
+/// Reads the next DER element from `data`. Returns the whole element, its content
+/// and the remaining data.
+fn der_next(data: &[u8]) -> Option<(&[u8], &[u8], &[u8])> {
+    let first_len = *data.get(1)? as usize;
+
+    let (header_len, len) = if first_len < 0x80 {
+        (2, first_len)
+    } else {
+        let len_bytes = first_len & 0x7f;
+        if len_bytes == 0 || len_bytes > 4 {
+            return None;
+        }
+
+        let len = data
+            .get(2..2 + len_bytes)?
+            .iter()
+            .fold(0, |len, b| (len << 8) | *b as usize);
+        (2 + len_bytes, len)
+    };
+
+    let end = header_len.checked_add(len)?;
+    let element = data.get(..end)?;
+
+    Some((element, &element[header_len..], &data[end..]))
+}
+
+/// Returns error for a range of TLS protocol versions, none of which is supported by the TLS library.
+#[cfg(any(feature = "native-tls", feature = "rust-tls"))]
+fn unsupported_versions() -> HttpError {
+    HttpError::IO(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "None of allowed TLS protocol versions is supported by the TLS library",
+    ))
+}
+
+/// Returns error for secure connections, which require one of TLS libraries.
+#[cfg(not(any(feature = "native-tls", feature = "rust-tls")))]
+fn no_tls() -> HttpError {
+    HttpError::IO(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "HTTPS requires `native-tls` or `rust-tls` feature",
+    ))
+}
+
+/// Converts error of TLS handshake, telling which check of the certificate failed (if any).
+///
+/// native-tls doesn't expose the reason in a structured way, so it's recognized by
+/// the messages of OpenSSL. On other platforms, certificate errors are reported as `Error::Tls`.
+#[cfg(feature = "native-tls")]
+fn handshake_error<S>(e: native_tls::HandshakeError<S>) -> HttpError {
+    match e {
+        native_tls::HandshakeError::Failure(e) => handshake_failure(&e),
+        native_tls::HandshakeError::WouldBlock(_) => HttpError::Tls,
+    }
+}
+
+/// Converts error of failed TLS handshake (see `handshake_error`).
+#[cfg(feature = "native-tls")]
+fn handshake_failure(e: &native_tls::Error) -> HttpError {
+    const REASONS: [(&str, CertificateError); 8] = [
+        ("certificate has expired", CertificateError::Expired),
+        (
+            "certificate is not yet valid",
+            CertificateError::NotYetValid,
+        ),
+        ("hostname mismatch", CertificateError::WrongHost),
+        ("ip address mismatch", CertificateError::WrongHost),
+        (
+            "unable to get local issuer",
+            CertificateError::UnknownIssuer,
+        ),
+        ("self-signed", CertificateError::SelfSigned),
+        ("self signed", CertificateError::SelfSigned),
+        ("certificate revoked", CertificateError::Revoked),
+    ];
+
+    let msg = e.to_string().to_lowercase();
+
+    match REASONS.iter().find(|(reason, _)| msg.contains(reason)) {
+        Some((_, reason)) => HttpError::Certificate(*reason),
+        None if msg.contains("certificate verify failed") => {
+            HttpError::Certificate(CertificateError::Other)
+        }
+        None => HttpError::Tls,
+    }
+}
+
+/// Converts error of TLS handshake, telling which check of the certificate failed (if any).
+///
+/// rustls doesn't tell self-signed certificates apart, they're reported as `UnknownIssuer` or `Other`.
+#[cfg(feature = "rust-tls")]
+fn handshake_error(e: io::Error) -> HttpError {
+    use rustls::CertificateError::*;
+
+    let reason = match e.get_ref().and_then(|e| e.downcast_ref::<rustls::Error>()) {
+        Some(rustls::Error::InvalidCertificate(reason)) => reason,
+        Some(_) => return HttpError::Tls,
+        None => return HttpError::IO(e),
+    };
+
+    HttpError::Certificate(match reason {
+        Expired => CertificateError::Expired,
+        NotValidYet => CertificateError::NotYetValid,
+        NotValidForName => CertificateError::WrongHost,
+        UnknownIssuer => CertificateError::UnknownIssuer,
+        Revoked => CertificateError::Revoked,
+        _ => CertificateError::Other,
+    })
+}
+
+/// Verifier used when some of the checks of certificates are disabled.
+/// Signatures of the handshake are always checked.
+#[cfg(feature = "rust-tls")]
+#[derive(Debug)]
+struct DangerVerifier {
+    provider: std::sync::Arc<rustls::crypto::CryptoProvider>,
+    /// Roots, to which the chain of certificates is verified, ignoring the server name.
+    /// If `None`, any certificate is accepted.
+    roots: Option<std::sync::Arc<rustls::RootCertStore>>,
+}
+
+#[cfg(feature = "rust-tls")]
+impl rustls::client::danger::ServerCertVerifier for DangerVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &rustls_pki_types::CertificateDer<'_>,
+        intermediates: &[rustls_pki_types::CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        now: rustls_pki_types::UnixTime,
+    ) -> Result<rustls::client::danger::ServerCertVerified, rustls::Error> {
+        if let Some(roots) = &self.roots {
+            rustls::client::verify_server_cert_signed_by_trust_anchor(
+                &rustls::server::ParsedCertificate::try_from(end_entity)?,
+                roots,
+                intermediates,
+                now,
+                self.provider.signature_verification_algorithms.all,
+            )?;
+        }
+
+        Ok(rustls::client::danger::ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &rustls_pki_types::CertificateDer<'_>,
+        dss: &rustls::DigitallySignedStruct,
+    ) -> Result<rustls::client::danger::HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &rustls_pki_types::CertificateDer<'_>,
+        dss: &rustls::DigitallySignedStruct,
+    ) -> Result<rustls::client::danger::HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<rustls::SignatureScheme> {
+        self.provider
+            .signature_verification_algorithms
+            .supported_schemes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "rust-tls-native-roots")]
+    #[test]
+    fn config_native_roots() {
+        let cnf = Config::default();
+        let native = native_root_certs().len();
+
+        assert!(cnf.root_certs.len() >= webpki_roots::TLS_SERVER_ROOTS.len());
+        assert!(cnf.root_certs.len() <= webpki_roots::TLS_SERVER_ROOTS.len() + native);
+    }
+
+    // Self-signed certificate with P-256 key.
+    const CERT: &str = "MIIBezCCASGgAwIBAgIUMSQ2w2L86LkVPcSSAEyhAvwCJ4UwCgYIKoZIzj0EAwIwEzERMA8GA1UEAwwIcGluLnRlc3QwHhcNMjYxMDE3MDQ1NjUyWhcNMjYxMDE4MDQ1NjUyWjATMREwDwYDVQQDDAhwaW4udGVzdDBZMBMGByqGSM49AgEGCCqGSM49AwEHA0IABF1BQLExXPOoSR/hOjOMzWeplQUMF+kHJqXn/d3vdXfdzU0/bcYyMzmm1cE9aZol2q9ZIMJ2AkPw+LEY3tCc4UWjUzBRMB0GA1UdDgQWBBQN12Vk6NH482EO4mFuI1z0w24hXjAfBgNVHSMEGDAWgBQN12Vk6NH482EO4mFuI1z0w24hXjAPBgNVHRMBAf8EBTADAQH/MAoGCCqGSM49BAMCA0gAMEUCIFJ1TMqjZPJ4q21esRKwAI+xekKlttT6qLAmZbgM33sgAiEAn5ZoCd8Wc65ayHAMp35aRL+geO7IJIL2m0ffph9pJRs=";
+    const PIN: &str = "qoLBco0mNC5tCMwRJM18HK51pOIyJ6ExaoNStsC2LIY=";
+
+    #[test]
+    fn fn_public_key_sha256() {
+        let cert = STANDARD.decode(CERT).unwrap();
+        let pin = STANDARD.decode(PIN).unwrap();
+
+        assert_eq!(public_key_sha256(&cert).map(Vec::from), Some(pin));
+        assert_eq!(public_key_sha256(&cert[..100]), None);
+        assert_eq!(public_key_sha256(&[]), None);
+    }
+
+    #[test]
+    fn certificate() {
+        let cert = Certificate::from_der(STANDARD.decode(CERT).unwrap());
+        let not_before = from_civil(2026, 10, 17, 4, 56, 52).unwrap();
+        let not_after = from_civil(2026, 10, 18, 4, 56, 52).unwrap();
+
+        assert_eq!(cert.subject().as_deref(), Some("CN=pin.test"));
+        assert_eq!(cert.issuer().as_deref(), Some("CN=pin.test"));
+        assert_eq!(cert.not_before(), Some(not_before));
+        assert_eq!(cert.not_after(), Some(not_after));
+        assert_eq!(
+            cert.public_key_sha256().map(Vec::from),
+            Some(STANDARD.decode(PIN).unwrap())
+        );
+
+        let cert = Certificate::from_der(b"not a certificate".to_vec());
+        assert_eq!(cert.subject(), None);
+        assert_eq!(cert.not_after(), None);
+    }
+
+    #[test]
+    fn fn_fmt_attr_type() {
+        assert_eq!(fmt_attr_type(&[0x55, 0x04, 0x0a]), "O");
+        // 2.5.4.97 (organizationIdentifier)
+        assert_eq!(fmt_attr_type(&[0x55, 0x04, 0x61]), "2.5.4.97");
+        // 1.3.6.1.4.1.311.60.2.1.3 (jurisdictionCountryName)
+        assert_eq!(
+            fmt_attr_type(&[0x2b, 0x06, 0x01, 0x04, 0x01, 0x82, 0x37, 0x3c, 0x02, 0x01, 0x03]),
+            "1.3.6.1.4.1.311.60.2.1.3"
+        );
+    }
+
+    #[test]
+    fn fn_parse_time() {
+        assert_eq!(
+            parse_time(b"\x17\x0d491231235959Z"),
+            from_civil(2049, 12, 31, 23, 59, 59)
+        );
+        assert_eq!(
+            parse_time(b"\x17\x0d500101000000Z"),
+            from_civil(1950, 1, 1, 0, 0, 0)
+        );
+        assert_eq!(
+            parse_time(b"\x18\x0f20500101000000Z"),
+            from_civil(2050, 1, 1, 0, 0, 0)
+        );
+        assert_eq!(parse_time(b"\x17\x0d501301000000Z"), None);
+        assert_eq!(parse_time(b"\x17\x0c5001010000Z0"), None);
+        assert_eq!(parse_time("\x17\x0d5\u{e9}101000000Z".as_bytes()), None);
+        assert_eq!(parse_time("\x18\x0f205\u{e9}010100000Z".as_bytes()), None);
+        assert_eq!(parse_time(b"\x17\x0d+50101000000Z"), None);
+    }
+
+    #[test]
+    fn config_check_pins() {
+        let cert = STANDARD.decode(CERT).unwrap();
+        let mut cnf = Config::default();
+
+        assert!(cnf.check_pins([]).is_ok());
+
+        cnf.pin_public_key([0; 32]);
+        assert!(matches!(
+            cnf.check_pins([cert.as_slice()]),
+            Err(HttpError::Certificate(CertificateError::NotPinned))
+        ));
+
+        cnf.pin_public_key_base64(PIN).unwrap();
+        assert!(cnf.check_pins([cert.as_slice()]).is_ok());
+        assert!(cnf.pin_public_key_base64("AAAA").is_err());
+    }
 }
@@ -0,0 +1,196 @@
+//! recording and replaying HTTP exchanges
+use crate::{
+    error::Error,
+    request::Method,
+    response::{find_slice, Response, CR_LF_2},
+    uri::Uri,
+};
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/// Modes of `Vcr`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum VcrMode {
+    /// Sends every request and records the response, overwriting existing cassettes.
+    Record,
+    /// Never sends requests. Fails if there is no cassette for a request.
+    Replay,
+    /// Replays the cassette if there is one, otherwise sends the request and records it.
+    Auto,
+}
+
+/// Records responses to files ("cassettes") in a directory and replays them for
+/// the same requests later, so that tests can run offline and deterministically.
+///
+/// Requests are matched by method, URI and body. Headers of requests are not taken
+/// into account. A cassette contains the head of the response followed by its body
+/// (already decoded from chunked transfer coding).
+///
+/// # Examples
+/// ```
+/// use http_req::{request::Request, uri::Uri, vcr::{Vcr, VcrMode}};
+/// use std::convert::TryFrom;
+///
+/// let uri = Uri::try_from("https://www.rust-lang.org/learn").unwrap();
+/// let vcr = Vcr::new("tests/cassettes", VcrMode::Auto);
+///
+/// let request = Request::new(&uri)
+///     .vcr(&vcr);
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+pub struct Vcr {
+    dir: PathBuf,
+    mode: VcrMode,
+}
+
+impl Vcr {
+    /// Creates a new `Vcr` storing cassettes in `dir`.
+    pub fn new<P: Into<PathBuf>>(dir: P, mode: VcrMode) -> Vcr {
+        Vcr {
+            dir: dir.into(),
+            mode,
+        }
+    }
+
+    /// Returns directory, in which cassettes are stored.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Returns mode of this `Vcr`.
+    pub const fn mode(&self) -> VcrMode {
+        self.mode
+    }
+
+    /// Returns path of the cassette for a request with the given method, URI and body.
+    ///
+    /// # Examples
+    /// ```
+    /// use http_req::{request::Method, uri::Uri, vcr::{Vcr, VcrMode}};
+    /// use std::convert::TryFrom;
+    ///
+    /// let uri = Uri::try_from("https://www.rust-lang.org/learn").unwrap();
+    /// let vcr = Vcr::new("tests/cassettes", VcrMode::Replay);
+    ///
+    /// let path = vcr.cassette(&Method::GET, &uri, None);
+    /// assert!(path.starts_with("tests/cassettes"));
+    /// ```
+    pub fn cassette(&self, method: &Method, uri: &Uri, body: Option<&[u8]>) -> PathBuf {
+        let key = format!("{} {}\n", method, uri);
+        let mut hash = FNV_OFFSET;
+
+        // FNV-1a is used as it is stable between builds, unlike `DefaultHasher`.
+        for b in key.as_bytes().iter().chain(body.unwrap_or_default()) {
+            hash ^= *b as u64;
+            hash = hash.wrapping_mul(FNV_PRIME);
+        }
+
+        self.dir.join(format!("{}-{:016x}.http", method, hash))
+    }
+
+    /// Loads the response from the cassette at `path` and writes its body to `writer`.
+    /// Returns `None` if the cassette should not be replayed in the current mode.
+    pub(crate) fn replay<T: Write>(
+        &self,
+        path: &Path,
+        writer: &mut T,
+    ) -> Result<Option<Response>, Error> {
+        let cassette = match (self.mode, fs::read(path)) {
+            (VcrMode::Record, _) => return Ok(None),
+            (VcrMode::Auto, Err(e)) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            (_, res) => res?,
+        };
+
+        let end = find_slice(&cassette, &CR_LF_2).unwrap_or(cassette.len());
+        let response = Response::from_head(&cassette[..end])?;
+        writer.write_all(&cassette[end..])?;
+
+        Ok(Some(response))
+    }
+
+    /// Saves `response` with its `body` to the cassette at `path`.
+    pub(crate) fn record(
+        &self,
+        path: &Path,
+        response: &Response,
+        body: &[u8],
+    ) -> Result<(), Error> {
+        let mut cassette = format!(
+            "{} {} {}\r\n",
+            response.version(),
+            response.status_code(),
+            response.reason()
+        );
+
+        for (key, val) in response.headers().iter() {
+            cassette = cassette + key + ": " + val + "\r\n";
+        }
+
+        let mut cassette = (cassette + "\r\n").into_bytes();
+        cassette.extend(body);
+
+        fs::create_dir_all(&self.dir)?;
+        fs::write(path, cassette)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryFrom;
+
+    const HEAD: &[u8] = b"HTTP/1.1 200 OK\r\nContent-Length: 11\r\n\r\n";
+    const BODY: &[u8] = b"hello world";
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("http_req-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn vcr_cassette() {
+        let uri = Uri::try_from("http://example.com/a").unwrap();
+        let other = Uri::try_from("http://example.com/b").unwrap();
+        let vcr = Vcr::new("cassettes", VcrMode::Auto);
+
+        let path = vcr.cassette(&Method::GET, &uri, None);
+        assert_eq!(path, vcr.cassette(&Method::GET, &uri, Some(b"")));
+        assert_eq!(path.parent(), Some(Path::new("cassettes")));
+        assert_ne!(path, vcr.cassette(&Method::POST, &uri, None));
+        assert_ne!(path, vcr.cassette(&Method::GET, &other, None));
+        assert_ne!(path, vcr.cassette(&Method::GET, &uri, Some(b"body")));
+    }
+
+    #[test]
+    fn vcr_record_replay() {
+        let dir = temp_dir("vcr");
+        let uri = Uri::try_from("http://example.com/").unwrap();
+        let vcr = Vcr::new(&dir, VcrMode::Auto);
+        let path = vcr.cassette(&Method::GET, &uri, None);
+
+        let mut writer = Vec::new();
+        assert!(vcr.replay(&path, &mut writer).unwrap().is_none());
+
+        let response = Response::from_head(HEAD).unwrap();
+        vcr.record(&path, &response, BODY).unwrap();
+
+        let replayed = vcr.replay(&path, &mut writer).unwrap().unwrap();
+        assert_eq!(replayed, response);
+        assert_eq!(writer, BODY);
+
+        let vcr = Vcr::new(&dir, VcrMode::Record);
+        assert!(vcr.replay(&path, &mut writer).unwrap().is_none());
+
+        fs::remove_dir_all(&dir).unwrap();
+        let vcr = Vcr::new(&dir, VcrMode::Replay);
+        assert!(vcr.replay(&path, &mut writer).is_err());
+    }
+}